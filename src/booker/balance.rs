@@ -12,8 +12,10 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::decimal::Decimal;
+use crate::lotter::booking::selects_lot;
 use crate::parser::posting::{Amount, Costs, Posting};
 use crate::parser::transaction::Transaction;
+use crate::resolver::Bookings;
 
 use super::error::{BookError, BookErrorKind, Residual};
 
@@ -25,7 +27,9 @@ use super::error::{BookError, BookErrorKind, Residual};
 /// multi-commodity entry) whenever any `@`/`@@` cost is present, which
 /// ties every commodity together including fee legs in a different
 /// unit. A lot `{…}` cost alone does not (its counter may balance in
-/// the face commodity).
+/// the face commodity). Nor does a `{…}` that only names the lot a
+/// `specific` disposal closes ([`selects_lot`]): that leg weighs as if
+/// unannotated, and the lotter books its gain.
 ///
 /// Assumes any balance-assignment posting (`= X` without amount) has
 /// already been resolved by the caller. Balance-assertion-only
@@ -37,6 +41,7 @@ pub(super) fn balance_tx(
     file: &Arc<str>,
     start_line: usize,
     end_line: usize,
+    bookings: &Bookings,
 ) -> Result<(), BookError> {
     let mut sums: HashMap<String, Decimal> = HashMap::new();
     let mut max_decimals: HashMap<String, usize> = HashMap::new();
@@ -60,7 +65,7 @@ pub(super) fn balance_tx(
         if p.costs.is_some() {
             has_costs = true;
         }
-        match effective_amount(p) {
+        match effective_amount(p, selects_lot(bookings, p)) {
             Some(eff) => {
                 *sums.entry(eff.commodity.clone()).or_insert(Decimal::zero()) += eff.value;
                 let entry = max_decimals.entry(eff.commodity.clone()).or_insert(0);
@@ -178,8 +183,10 @@ pub(super) fn balance_tx(
 ///    the posting's sign.
 /// 4. No annotation — posting contributes its own amount.
 ///
+/// A lot cost that only `selects` the lot to close is skipped.
+///
 /// Returns `None` only when the posting has no amount.
-fn effective_amount(p: &Posting) -> Option<Amount> {
+fn effective_amount(p: &Posting, selects: bool) -> Option<Amount> {
     let amt = p.amount.as_ref()?;
     // `decimals` on the effective amount drives the balance-check
     // tolerance (via `is_display_zero`). Cost-derived effective
@@ -190,7 +197,7 @@ fn effective_amount(p: &Posting) -> Option<Amount> {
     // *direct* posting in the same commodity (e.g. a plain `€44.06`
     // in the same tx), defaulting to 0 if the commodity only ever
     // appears via cost conversion.
-    if let Some(lot) = &p.lot_cost {
+    if let Some(lot) = p.lot_cost.as_ref().filter(|_| !selects) {
         return Some(Amount {
             commodity: lot.amount.commodity.clone(),
             value: lot.weight(amt.value),
@@ -239,7 +246,7 @@ mod tests {
             .map(|p| p.line)
             .max()
             .unwrap_or(first.line);
-        balance_tx(&mut first.value, &first.file, first.line, end)?;
+        balance_tx(&mut first.value, &first.file, first.line, end, &resolved.bookings)?;
         Ok(first.value)
     }
 
@@ -269,14 +276,14 @@ mod tests {
     fn errors_on_multiple_missing() {
         let src = "2024-06-15 * X\n    expenses:food\n    assets:cash\n";
         let err = balance_one(src).unwrap_err();
-        assert!(matches!(err.kind, BookErrorKind::MultipleMissing));
+        assert!(matches!(*err.kind, BookErrorKind::MultipleMissing));
     }
 
     #[test]
     fn errors_on_unbalanced_sum() {
         let src = "2024-06-15 * X\n    expenses:food   5 USD\n    assets:cash  -3 USD\n";
         let err = balance_one(src).unwrap_err();
-        assert!(matches!(err.kind, BookErrorKind::Unbalanced { .. }));
+        assert!(matches!(*err.kind, BookErrorKind::Unbalanced { .. }));
     }
 
     #[test]
@@ -326,7 +333,7 @@ mod tests {
                    \ta:fee  LTC0.123123123123\n\
                    \ta:x    LTC-0.0108975\n";
         let err = balance_one(src).unwrap_err();
-        assert!(matches!(err.kind, BookErrorKind::Unbalanced { .. }));
+        assert!(matches!(*err.kind, BookErrorKind::Unbalanced { .. }));
     }
 
     #[test]
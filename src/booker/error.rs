@@ -11,7 +11,7 @@ pub struct BookError {
     pub file: Arc<str>,
     pub start_line: usize,
     pub end_line: usize,
-    pub kind: BookErrorKind,
+    pub kind: Box<BookErrorKind>,
 }
 
 /// One residual commodity in an unbalanced transaction.
@@ -45,13 +45,13 @@ pub enum BookErrorKind {
 
 impl BookError {
     pub fn new(file: Arc<str>, start_line: usize, end_line: usize, kind: BookErrorKind) -> Self {
-        Self { file, start_line, end_line, kind }
+        Self { file, start_line, end_line, kind: Box::new(kind) }
     }
 
     /// Single-line headline (with residual/assertion detail inlined in
     /// parentheses). Used as the `path:line: MESSAGE` header.
     pub fn headline(&self) -> String {
-        match &self.kind {
+        match &*self.kind {
             BookErrorKind::Unbalanced { residuals } => {
                 let joined = residuals
                     .iter()
@@ -29,44 +29,172 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::decimal::Decimal;
+use crate::parser::entry::Split;
 use crate::parser::located::Located;
-use crate::parser::posting::{Amount, Posting};
-use crate::parser::transaction::Transaction;
+use crate::parser::metadata::Metadata;
+use crate::parser::posting::{Amount, Costs, Posting};
+use crate::parser::transaction::{State, Transaction};
+use crate::resolver::Bookings;
+
+/// The tag every generated split swap carries: the lotter rescales lots
+/// on a transaction only when it is marked so.
+pub const SWAP_TAG: &str = "split-swap";
 
 /// Book every transaction. Input must be date-sorted (resolver
 /// guarantees this). Returns transactions with every posting's amount
 /// filled in and every balance assertion verified.
 pub fn book(
     transactions: Vec<Located<Transaction>>,
+) -> Result<Vec<Located<Transaction>>, BookError> {
+    book_with_splits(transactions, &[], &Bookings::default())
+}
+
+/// [`book`], plus the `booking` methods (a `specific` disposal's `{cost}`
+/// names its lot instead of weighing the leg, see [`balance::balance_tx`])
+/// and the `split` / `convert` directives (date-sorted). Each
+/// one becomes a generated transaction ahead of the first transaction on
+/// or after its date: for every account holding the old commodity, the
+/// holding is swapped out at the ratio (`-b OLD @ k NEW`) and `b·k NEW`
+/// swapped in. Written at that cost the swap balances, and the later
+/// phases see a like-for-like exchange rather than a trade — the lotter
+/// rescales its lots on it instead of realizing them. The swap carries
+/// the [`SWAP_TAG`] tag.
+pub fn book_with_splits(
+    transactions: Vec<Located<Transaction>>,
+    splits: &[Located<Split>],
+    bookings: &Bookings,
 ) -> Result<Vec<Located<Transaction>>, BookError> {
     let mut balances: HashMap<(String, String), Decimal> = HashMap::new();
+    let mut decimals: HashMap<String, usize> = HashMap::new();
     let mut result = Vec::with_capacity(transactions.len());
+    let mut pending = splits.iter().peekable();
 
-    for Located { file, line, mut value } in transactions {
-        let end_line = value
-            .postings
-            .iter()
-            .map(|p| p.line)
-            .max()
-            .unwrap_or(line);
-        // 1. Resolve balance-assignment postings (amount from running
-        // balance vs target).
-        for lp in &mut value.postings {
-            resolve_assignment(&mut lp.value, &balances);
+    for tx in transactions {
+        while let Some(split) = pending.next_if(|s| s.value.date <= tx.value.date) {
+            if let Some(swap) = split_tx(split, &balances, &decimals) {
+                result.push(book_tx(swap, &mut balances, &mut decimals, bookings)?);
+            }
         }
-        // 2. Transaction-local balance (sum = 0, cost-aware).
-        balance::balance_tx(&mut value, &file, line, end_line)?;
-        // 3. Apply each posting to the running balance and check any
-        // assertion targets.
-        for lp in &value.postings {
-            apply_and_check(&lp.value, &file, line, end_line, &mut balances)?;
+        result.push(book_tx(tx, &mut balances, &mut decimals, bookings)?);
+    }
+    for split in pending {
+        if let Some(swap) = split_tx(split, &balances, &decimals) {
+            result.push(book_tx(swap, &mut balances, &mut decimals, bookings)?);
         }
-        result.push(Located { file, line, value });
     }
 
     Ok(result)
 }
 
+/// Run the three booking steps on one transaction.
+fn book_tx(
+    Located { file, line, mut value }: Located<Transaction>,
+    balances: &mut HashMap<(String, String), Decimal>,
+    decimals: &mut HashMap<String, usize>,
+    bookings: &Bookings,
+) -> Result<Located<Transaction>, BookError> {
+    let end_line = value
+        .postings
+        .iter()
+        .map(|p| p.line)
+        .max()
+        .unwrap_or(line);
+    // 1. Resolve balance-assignment postings (amount from running
+    // balance vs target).
+    for lp in &mut value.postings {
+        resolve_assignment(&mut lp.value, balances);
+    }
+    // 2. Transaction-local balance (sum = 0, cost-aware).
+    balance::balance_tx(&mut value, &file, line, end_line, bookings)?;
+    // 3. Apply each posting to the running balance and check any
+    // assertion targets.
+    for lp in &value.postings {
+        apply_and_check(&lp.value, &file, line, end_line, balances)?;
+        if let Some(a) = &lp.value.amount {
+            let d = decimals.entry(a.commodity.clone()).or_default();
+            *d = (*d).max(a.decimals);
+        }
+    }
+    Ok(Located { file, line, value })
+}
+
+/// The generated swap transaction for one split, or `None` when no
+/// account holds the old commodity. Accounts come out in name order.
+fn split_tx(
+    split: &Located<Split>,
+    balances: &HashMap<(String, String), Decimal>,
+    decimals: &HashMap<String, usize>,
+) -> Option<Located<Transaction>> {
+    let s = &split.value;
+    let ratio = s.ratio();
+    let mut held: Vec<(&str, Decimal)> = balances
+        .iter()
+        .filter(|((_, commodity), b)| *commodity == s.from && !b.is_zero())
+        .map(|((account, _), b)| (account.as_str(), *b))
+        .collect();
+    if held.is_empty() {
+        return None;
+    }
+    held.sort_by(|a, b| a.0.cmp(b.0));
+
+    let from_decimals = decimals.get(&s.from).copied().unwrap_or(0);
+    let amount = |commodity: &str, value: Decimal, known: usize| Amount {
+        commodity: commodity.to_string(),
+        value,
+        decimals: known.max(natural_decimals(value)),
+    };
+    let posting = |account: &str, amount: Amount, costs: Option<Costs>| Located {
+        file: split.file.clone(),
+        line: split.line,
+        value: Posting {
+            account: account.to_string(),
+            amount: Some(amount),
+            costs,
+            lot_cost: None,
+            lot_date: None,
+            aux_date: None,
+            balance_assertion: None,
+            state: None,
+            is_virtual: false,
+            balanced: true,
+            comments: Vec::new(),
+            metadata: Metadata::default(),
+        },
+    };
+    let mut postings = Vec::with_capacity(held.len() * 2);
+    for (account, b) in held {
+        let rate = Costs::PerUnit(amount(&s.to, ratio, 0));
+        postings.push(posting(account, amount(&s.from, -b, from_decimals), Some(rate)));
+        let to_decimals = decimals.get(&s.to).copied().unwrap_or(from_decimals);
+        postings.push(posting(account, amount(&s.to, b.mul_rounded(ratio), to_decimals), None));
+    }
+    let mut metadata = Metadata::default();
+    metadata.add_comment(&format!(":{}:", SWAP_TAG));
+    Some(Located {
+        file: split.file.clone(),
+        line: split.line,
+        value: Transaction {
+            date: s.date,
+            aux_date: None,
+            state: State::Cleared,
+            code: None,
+            description: s.to_string(),
+            postings,
+            comments: Vec::new(),
+            metadata,
+        },
+    })
+}
+
+/// Fractional digits `value` needs to print exactly.
+fn natural_decimals(value: Decimal) -> usize {
+    let s = value.to_string();
+    match s.split_once('.') {
+        Some((_, frac)) => frac.trim_end_matches('0').len(),
+        None => 0,
+    }
+}
+
 /// If the posting is a balance-assignment (`= TARGET` with no amount),
 /// fill its amount so that the account balance reaches `TARGET` after
 /// this posting.
@@ -184,7 +312,7 @@ mod tests {
     fn assertion_fails_on_mismatch() {
         let src = "2024-01-01 * Deposit\n    assets:bank   100 USD = 999 USD\n    equity:opening  -100 USD\n";
         let err = pipeline(src).unwrap_err();
-        match err.kind {
+        match *err.kind {
             BookErrorKind::AssertionFailed { ref account, .. } => {
                 assert_eq!(account, "assets:bank");
             }
@@ -116,6 +116,16 @@ impl Account {
         total
     }
 
+    /// Flip the sign of every balance in this subtree.
+    pub fn negate(&mut self) {
+        for value in self.balance.values_mut() {
+            *value = -*value;
+        }
+        for child in self.children.values_mut() {
+            child.negate();
+        }
+    }
+
     /// Depth-first walk. `f` is invoked for every account node except
     /// the invisible root.
     pub fn walk<F>(&self, f: &mut F)
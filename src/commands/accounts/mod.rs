@@ -7,18 +7,57 @@
 //! - **tree** (`--tree`): hierarchical indented view, built from the
 //!   colon-separated account paths.
 //!
+//! Under `-O json|csv|tsv` each account is one row — its full path, its
+//! balance label, and the `file` / `line` of its first posting (empty for
+//! a parent the tree mode adds that has no posting of its own).
+//!
 //! Runs after the filter phase — the journal is already scoped to the
 //! user's query, so every posting seen here is a match.
 
 mod flat;
 mod tree;
 
-use crate::loader::Journal;
+use std::collections::BTreeMap;
 
-pub fn run(journal: &Journal, tree_mode: bool) {
-    if tree_mode {
+use crate::commands::account::Account;
+use crate::commands::output::{Format, Table};
+use crate::loader::{Journal, LabelView};
+use crate::parser::located::Located;
+use crate::parser::posting::Posting;
+
+pub fn run(journal: &Journal, tree_mode: bool, format: Format) {
+    if format != Format::Text {
+        table(journal, tree_mode).print(format);
+    } else if tree_mode {
         tree::print(journal);
     } else {
         flat::print(journal);
     }
 }
+
+fn table(journal: &Journal, tree_mode: bool) -> Table {
+    let mut first: BTreeMap<&str, &Located<Posting>> = BTreeMap::new();
+    for lp in journal.transactions.iter().flat_map(|tx| tx.value.postings.iter()) {
+        first.entry(lp.value.account.as_str()).or_insert(lp);
+    }
+    let mut names: Vec<String> = Vec::new();
+    if tree_mode {
+        Account::from_transactions(&journal.transactions)
+            .walk(&mut |node| names.push(node.fullname.clone()));
+    } else {
+        names.extend(first.keys().map(|a| a.to_string()));
+    }
+
+    let mut table = Table::new(&["account", "label", "file", "line"]);
+    for name in names {
+        let at = first.get(name.as_str());
+        let label = journal.label_for(&name, LabelView::Balance).map(String::from);
+        table.push(vec![
+            Some(name),
+            label,
+            at.map(|lp| lp.file.to_string()),
+            at.map(|lp| lp.line.to_string()),
+        ]);
+    }
+    table
+}
@@ -4,18 +4,137 @@
 //! Runs after the filter phase, so the journal already contains only
 //! the postings the user wants summed. The `--empty` / `-E` flag
 //! controls whether accounts whose total nets to zero are rendered —
-//! in both flat and tree mode.
+//! in both flat and tree mode. With `columns` the report becomes a
+//! matrix, one column per period (see [`periodic`]).
+//!
+//! Under `-O json|csv|tsv` the report is one row per account and
+//! commodity — every account node with its subtree total in tree mode,
+//! each posted-to account with its own in flat mode — and, with
+//! columns, one per period too. The grand total is left out: it is the
+//! sum of the top-level rows.
 
 mod common;
 mod flat;
+mod periodic;
 mod tree;
 
-use crate::loader::Journal;
+pub use periodic::{Columns, Interval};
+pub(crate) use periodic::periods;
+pub(crate) use tree::{calculate_width, print_section, print_total, total_width};
+
+use crate::commands::account::Account;
+use crate::commands::output::{exact, Format, Table};
+use crate::commands::util::shows_nonzero;
+use crate::loader::{Journal, LabelView};
+
+pub fn run(
+    journal: &Journal,
+    tree_mode: bool,
+    show_empty: bool,
+    columns: Option<&Columns>,
+    format: Format,
+) {
+    if format != Format::Text {
+        let table = match columns {
+            Some(columns) => periodic::table(journal, tree_mode, show_empty, columns),
+            None => table(journal, tree_mode, show_empty),
+        };
+        return table.print(format);
+    }
+    match columns {
+        Some(columns) => periodic::print(journal, tree_mode, show_empty, columns),
+        None if tree_mode => tree::print(journal, show_empty),
+        None => flat::print(journal, show_empty),
+    }
+}
+
+fn table(journal: &Journal, tree_mode: bool, show_empty: bool) -> Table {
+    let root = Account::from_transactions(&journal.transactions);
+    let mut table = Table::new(&["account", "label", "commodity", "amount"]);
+    root.walk(&mut |node| {
+        let amounts = if tree_mode { node.total() } else { node.balance.clone() };
+        let label = journal.label_for(&node.fullname, LabelView::Balance);
+        for (commodity, value) in &amounts {
+            if show_empty || shows_nonzero(commodity, value, &journal.precisions) {
+                table.push(vec![
+                    Some(node.fullname.clone()),
+                    label.map(String::from),
+                    Some(commodity.clone()),
+                    Some(exact(value)),
+                ]);
+            }
+        }
+    });
+    table
+}
+
+/// The structured rows of a financial statement (`bs`, `is`,
+/// `cashflow`): one per section, account node and commodity, each node
+/// with its subtree total as in tree mode. `sections` pairs each
+/// section's name with its tree.
+pub(crate) fn statement_table(journal: &Journal, sections: &[(&str, &Account)], show_empty: bool) -> Table {
+    let mut table = Table::new(&["section", "account", "label", "commodity", "amount"]);
+    for (section, root) in sections {
+        root.walk(&mut |node| {
+            let label = journal.label_for(&node.fullname, LabelView::Balance);
+            for (commodity, value) in &node.total() {
+                if show_empty || shows_nonzero(commodity, value, &journal.precisions) {
+                    table.push(vec![
+                        Some(section.to_string()),
+                        Some(node.fullname.clone()),
+                        label.map(String::from),
+                        Some(commodity.clone()),
+                        Some(exact(value)),
+                    ]);
+                }
+            }
+        });
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn structured_rows_per_account_and_commodity() {
+        let src = "\
+            2024-01-05 * a\n    expenses:food   10.25 USD\n    assets:cash\n\
+            2024-01-20 * b\n    expenses:rent  100 USD\n    assets:cash\n";
+        let journal = crate::testutil::load(src);
+
+        let render = |tree_mode: bool| {
+            let mut out = Vec::new();
+            table(&journal, tree_mode, false).write(&mut out, Format::Csv).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            render(false),
+            "account,label,commodity,amount\n\
+             assets:cash,,USD,-110.25\n\
+             expenses:food,,USD,10.25\n\
+             expenses:rent,,USD,100\n"
+        );
+        assert!(render(true).contains("\nexpenses,,USD,110.25\n"));
+    }
 
-pub fn run(journal: &Journal, tree_mode: bool, show_empty: bool) {
-    if tree_mode {
-        tree::print(journal, show_empty);
-    } else {
-        flat::print(journal, show_empty);
+    #[test]
+    fn statement_rows_carry_their_section() {
+        let src = "\
+            2024-01-05 * a\n    expenses:food   10 USD\n    assets:cash\n";
+        let journal = crate::testutil::load(src);
+        let root = Account::from_transactions(&journal.transactions);
+        let (assets, expenses) = (&root.children["assets"], &root.children["expenses"]);
+        let mut out = Vec::new();
+        statement_table(&journal, &[("assets", assets), ("expenses", expenses)], false)
+            .write(&mut out, Format::Csv)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "section,account,label,commodity,amount\n\
+             assets,assets:cash,,USD,-10\n\
+             expenses,expenses:food,,USD,10\n"
+        );
     }
 }
@@ -0,0 +1,419 @@
+//! `balance` periodic mode — one column per month, quarter or year.
+//!
+//! Layout, accounts as rows (flat or tree), periods as columns, each
+//! cell the account's change over that period:
+//!
+//! ```text
+//!                  2024-01   2024-02   2024-03     Total   Average
+//! expenses:food    USD120     USD95    USD140    USD355  USD118.33
+//! ------------------------------------------------------------------
+//!                  USD120     USD95    USD140    USD355  USD118.33
+//! ```
+//!
+//! Every column is its own [`Account`] tree over the postings dated in
+//! that period, so a tree row is its subtree's total and a flat row its
+//! own postings. The columns span `-b` / `-e` (or a single `-p`) when
+//! given, else the journal's first to last posting, snapped outward to
+//! whole periods. Under `-X` the rebalancer has already converted every
+//! posting at its own date, so each column carries its historical value.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufWriter, Write};
+
+use colored::Colorize;
+
+use super::common::label_suffix;
+use crate::commands::account::Account;
+use crate::commands::output::{exact, Table};
+use crate::commands::util::{format_amount, shows_nonzero, write_spaces};
+use crate::date::Date;
+use crate::decimal::Decimal;
+use crate::loader::Journal;
+
+const GAP: usize = 2;
+
+/// Column width of a periodic balance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interval {
+    Daily,
+    /// Weeks start on Monday.
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl Interval {
+    /// First day of the period containing `date`.
+    pub(crate) fn start(self, date: Date) -> Date {
+        let month = match self {
+            Interval::Daily => return date,
+            // 1970-01-01, day 0, was a Thursday.
+            Interval::Weekly => return Date::from_days(date.days().saturating_sub((date.days() + 3) % 7)),
+            Interval::Monthly => date.month(),
+            Interval::Quarterly => (date.month() - 1) / 3 * 3 + 1,
+            Interval::Yearly => 1,
+        };
+        Date::parse(&format!("{:04}-{:02}-01", date.year(), month)).expect("valid period start")
+    }
+
+    /// First day of the period after the one starting at `start`.
+    pub(crate) fn next(self, start: Date) -> Date {
+        match self {
+            Interval::Daily => start.next_day(),
+            Interval::Weekly => Date::from_days(start.days() + 7),
+            Interval::Monthly => start.next_month_start(),
+            Interval::Quarterly => start.next_month_start().next_month_start().next_month_start(),
+            Interval::Yearly => start.next_year_start(),
+        }
+    }
+
+    /// Column header for the period starting at `start`.
+    pub(crate) fn label(self, start: Date) -> String {
+        match self {
+            Interval::Daily | Interval::Weekly => start.to_string(),
+            Interval::Monthly => format!("{:04}-{:02}", start.year(), start.month()),
+            Interval::Quarterly => format!("{:04}Q{}", start.year(), (start.month() - 1) / 3 + 1),
+            Interval::Yearly => format!("{:04}", start.year()),
+        }
+    }
+}
+
+/// What the periodic report shows. `begin` / `end` (exclusive) bound the
+/// columns; either left `None` falls back to the journal's own span.
+#[derive(Debug, Clone, Copy)]
+pub struct Columns {
+    pub interval: Interval,
+    pub begin: Option<Date>,
+    pub end: Option<Date>,
+    /// Append a `Total` column — the row's sum over all periods.
+    pub row_total: bool,
+    /// Append an `Average` column — the row total over the period count.
+    pub average: bool,
+    /// Date postings by their auxiliary date (`--aux-date`).
+    pub aux_date: bool,
+}
+
+/// One report row: the account (indented in tree mode) and its cells per
+/// commodity, one value per period.
+struct Row {
+    path: String,
+    label: String,
+    amounts: BTreeMap<String, Vec<Decimal>>,
+}
+
+pub(super) fn print(journal: &Journal, tree_mode: bool, show_empty: bool, columns: &Columns) {
+    let periods = periods(journal, columns);
+    if periods.is_empty() {
+        return;
+    }
+    let trees = column_trees(journal, &periods, columns.aux_date);
+    let rows = build_rows(journal, &trees, tree_mode, show_empty);
+    if rows.is_empty() {
+        return;
+    }
+    let total = Row {
+        path: String::new(),
+        label: String::new(),
+        amounts: merge(trees.iter().map(|t| t.total()).collect()),
+    };
+
+    let p = &journal.precisions;
+    let mut header: Vec<String> = periods.iter().map(|(start, _)| columns.interval.label(*start)).collect();
+    if columns.row_total {
+        header.push("Total".to_string());
+    }
+    if columns.average {
+        header.push("Average".to_string());
+    }
+    // One line per commodity that shows in any period; a row netting to
+    // zero throughout gets a single line of `0`s.
+    let cells = |row: &Row| -> Vec<Vec<(String, bool)>> {
+        let mut lines: Vec<Vec<(String, bool)>> = row
+            .amounts
+            .iter()
+            .filter(|(c, values)| values.iter().any(|v| shows_nonzero(c, v, p)))
+            .map(|(c, values)| row_cells(c, values, columns, p))
+            .collect();
+        if lines.is_empty() {
+            lines.push(header.iter().map(|_| ("0".to_string(), false)).collect());
+        }
+        lines
+    };
+    let row_cells: Vec<Vec<Vec<(String, bool)>>> = rows.iter().map(cells).collect();
+    let total_cells = cells(&total);
+
+    let label_width = rows.iter().map(|r| r.label.chars().count() + label_len(&r.path, journal)).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            row_cells
+                .iter()
+                .flatten()
+                .chain(total_cells.iter())
+                .map(|line| line[i].0.chars().count())
+                .chain(std::iter::once(header[i].len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    // One locked, buffered writer for the whole report.
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let _ = write_spaces(&mut out, label_width);
+    let header: Vec<(String, bool)> = header.into_iter().map(|h| (h, false)).collect();
+    let _ = print_cells(&mut out, &header, &widths);
+    for (row, lines) in rows.iter().zip(&row_cells) {
+        for (i, line) in lines.iter().enumerate() {
+            let mut used = 0;
+            if i == 0 {
+                let _ = write!(out, "{}{}", row.label.blue(), label_suffix(&row.path, journal));
+                used = row.label.chars().count() + label_len(&row.path, journal);
+            }
+            let _ = write_spaces(&mut out, label_width - used);
+            let _ = print_cells(&mut out, line, &widths);
+        }
+    }
+    let _ = writeln!(out, "{}", "-".repeat(label_width + widths.iter().map(|w| w + GAP).sum::<usize>()));
+    for line in &total_cells {
+        let _ = write_spaces(&mut out, label_width);
+        let _ = print_cells(&mut out, line, &widths);
+    }
+    let _ = out.flush();
+}
+
+/// The report as structured rows: one per period, account and commodity,
+/// skipping cells that round to zero unless `show_empty`. The total and
+/// average columns are left out — they are sums over the rows.
+pub(super) fn table(journal: &Journal, tree_mode: bool, show_empty: bool, columns: &Columns) -> Table {
+    let mut table = Table::new(&["period", "account", "label", "commodity", "amount"]);
+    let periods = periods(journal, columns);
+    let trees = column_trees(journal, &periods, columns.aux_date);
+    let rows = build_rows(journal, &trees, tree_mode, show_empty);
+    for (i, (start, _)) in periods.iter().enumerate() {
+        let period = columns.interval.label(*start);
+        for row in &rows {
+            let label = journal.label_for(&row.path, crate::loader::LabelView::Balance);
+            for (commodity, values) in &row.amounts {
+                if show_empty || shows_nonzero(commodity, &values[i], &journal.precisions) {
+                    table.push(vec![
+                        Some(period.clone()),
+                        Some(row.path.clone()),
+                        label.map(String::from),
+                        Some(commodity.clone()),
+                        Some(exact(&values[i])),
+                    ]);
+                }
+            }
+        }
+    }
+    table
+}
+
+/// Visible width of an account's ` (label)` suffix.
+fn label_len(path: &str, journal: &Journal) -> usize {
+    journal
+        .label_for(path, crate::loader::LabelView::Balance)
+        .map_or(0, |label| label.chars().count() + 3)
+}
+
+/// The row's cells for one commodity: its value per period, then the
+/// optional total and average. Each cell carries whether it is negative.
+fn row_cells(
+    commodity: &str,
+    values: &[Decimal],
+    columns: &Columns,
+    precisions: &HashMap<String, usize>,
+) -> Vec<(String, bool)> {
+    let cell = |v: Decimal| {
+        let text = if shows_nonzero(commodity, &v, precisions) {
+            format_amount(commodity, &v, precisions)
+        } else {
+            "0".to_string()
+        };
+        (text, shows_nonzero(commodity, &v, precisions) && v.is_negative())
+    };
+    let sum = values.iter().fold(Decimal::zero(), |acc, v| acc + *v);
+    let mut cells: Vec<(String, bool)> = values.iter().map(|v| cell(*v)).collect();
+    if columns.row_total {
+        cells.push(cell(sum));
+    }
+    if columns.average {
+        cells.push(cell(sum.div_rounded(Decimal::from(values.len() as i64))));
+    }
+    cells
+}
+
+/// Write one line of right-aligned cells after the account column.
+/// Negative cells are painted red.
+fn print_cells<W: Write>(out: &mut W, cells: &[(String, bool)], widths: &[usize]) -> io::Result<()> {
+    for ((cell, negative), width) in cells.iter().zip(widths) {
+        write_spaces(out, GAP + width.saturating_sub(cell.chars().count()))?;
+        if *negative {
+            write!(out, "{}", cell.red())?;
+        } else {
+            write!(out, "{}", cell)?;
+        }
+    }
+    writeln!(out)
+}
+
+/// The `[start, end)` periods the columns cover.
+pub(crate) fn periods(journal: &Journal, columns: &Columns) -> Vec<(Date, Date)> {
+    let dates = journal.transactions.iter().flat_map(|lt| {
+        lt.value.postings.iter().map(|lp| lt.value.posting_date(&lp.value, columns.aux_date))
+    });
+    let (first, last) = dates.fold((None, None), |(lo, hi): (Option<Date>, Option<Date>), d| {
+        (Some(lo.map_or(d, |lo| lo.min(d))), Some(hi.map_or(d, |hi| hi.max(d))))
+    });
+    let (Some(begin), Some(end)) = (columns.begin.or(first), columns.end.or(last.map(Date::next_day))) else {
+        return Vec::new();
+    };
+    let mut periods = Vec::new();
+    let mut start = columns.interval.start(begin);
+    while start < end {
+        let next = columns.interval.next(start);
+        periods.push((start, next));
+        start = next;
+    }
+    periods
+}
+
+/// One account tree per period, over the postings dated in it.
+fn column_trees(journal: &Journal, periods: &[(Date, Date)], aux_date: bool) -> Vec<Account> {
+    let mut trees: Vec<Account> = periods.iter().map(|_| Account::root()).collect();
+    for lt in &journal.transactions {
+        for lp in &lt.value.postings {
+            let p = &lp.value;
+            let Some(amount) = &p.amount else { continue };
+            let date = lt.value.posting_date(p, aux_date);
+            let i = periods.partition_point(|(start, _)| *start <= date);
+            if i == 0 || date >= periods[i - 1].1 {
+                continue;
+            }
+            trees[i - 1].find_or_create(&p.account).add_amount(&amount.commodity, amount.value);
+        }
+    }
+    trees
+}
+
+/// Per-period amounts into `commodity → [value per period]`.
+fn merge(per_period: Vec<BTreeMap<String, Decimal>>) -> BTreeMap<String, Vec<Decimal>> {
+    let n = per_period.len();
+    let mut merged: BTreeMap<String, Vec<Decimal>> = BTreeMap::new();
+    for (i, amounts) in per_period.into_iter().enumerate() {
+        for (commodity, value) in amounts {
+            merged.entry(commodity).or_insert_with(|| vec![Decimal::zero(); n])[i] += value;
+        }
+    }
+    merged
+}
+
+fn build_rows(journal: &Journal, trees: &[Account], tree_mode: bool, show_empty: bool) -> Vec<Row> {
+    let structure = Account::from_transactions(&journal.transactions);
+    let p = &journal.precisions;
+    let shows = |row: &Row| row.amounts.iter().any(|(c, values)| values.iter().any(|v| shows_nonzero(c, v, p)));
+    let mut rows = Vec::new();
+    if tree_mode {
+        for child in structure.children.values() {
+            tree_rows(child, "", trees, &|r| show_empty || shows(r), &mut rows);
+        }
+    } else {
+        structure.walk(&mut |node| {
+            if node.balance.is_empty() {
+                return;
+            }
+            let row = Row {
+                path: node.fullname.clone(),
+                label: node.fullname.clone(),
+                amounts: merge(
+                    trees
+                        .iter()
+                        .map(|t| t.find(&node.fullname).map(|a| a.balance.clone()).unwrap_or_default())
+                        .collect(),
+                ),
+            };
+            if show_empty || shows(&row) {
+                rows.push(row);
+            }
+        });
+    }
+    rows
+}
+
+/// Append `node`'s row and its descendants'. A node that nets to zero in
+/// every period still shows when a descendant does, so that descendant
+/// stays nested under it.
+fn tree_rows(node: &Account, indent: &str, trees: &[Account], keep: &dyn Fn(&Row) -> bool, rows: &mut Vec<Row>) {
+    let row = Row {
+        path: node.fullname.clone(),
+        label: format!("{}{}", indent, node.name),
+        amounts: merge(
+            trees
+                .iter()
+                .map(|t| t.find(&node.fullname).map(Account::total).unwrap_or_default())
+                .collect(),
+        ),
+    };
+    let at = rows.len();
+    let kept = keep(&row);
+    let child_indent = format!("{}  ", indent);
+    for child in node.children.values() {
+        tree_rows(child, &child_indent, trees, keep, rows);
+    }
+    if kept || rows.len() > at {
+        rows.insert(at, row);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal(src: &str) -> Journal {
+        let mut journal = crate::testutil::load(src);
+        crate::pipeline::enrich(&mut journal, None, false, None, None).unwrap();
+        journal
+    }
+
+    const SRC: &str = "\
+        2024-01-05 * a\n    expenses:food   10 USD\n    assets:cash\n\
+        2024-01-20 * b\n    expenses:rent  100 USD\n    assets:cash\n\
+        2024-03-02 * c\n    expenses:food   20 USD\n    assets:cash\n";
+
+    fn columns(interval: Interval) -> Columns {
+        Columns { interval, begin: None, end: None, row_total: true, average: true, aux_date: false }
+    }
+
+    #[test]
+    fn columns_snap_to_whole_periods() {
+        let j = journal(SRC);
+        let labels = |c: &Columns| -> Vec<String> {
+            periods(&j, c).iter().map(|(s, _)| c.interval.label(*s)).collect()
+        };
+        assert_eq!(labels(&columns(Interval::Monthly)), ["2024-01", "2024-02", "2024-03"]);
+        assert_eq!(labels(&columns(Interval::Quarterly)), ["2024Q1"]);
+        let mut wide = columns(Interval::Quarterly);
+        wide.begin = Date::parse("2023-11-15").ok();
+        wide.end = Date::parse("2024-04-01").ok();
+        assert_eq!(labels(&wide), ["2023Q4", "2024Q1"]);
+    }
+
+    #[test]
+    fn tree_rows_carry_subtree_totals_per_period() {
+        let j = journal(SRC);
+        let periods = periods(&j, &columns(Interval::Monthly));
+        let trees = column_trees(&j, &periods, false);
+        let rows = build_rows(&j, &trees, true, false);
+        let labels: Vec<&str> = rows.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, ["assets", "  cash", "expenses", "  food", "  rent"]);
+        let usd = |i: usize| rows[i].amounts["USD"].iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        let d = |s: &str| Decimal::parse(s).unwrap().to_string();
+        assert_eq!(usd(2), [d("110"), d("0"), d("20")]);
+        assert_eq!(usd(3), [d("10"), d("0"), d("20")]);
+
+        let flat = build_rows(&j, &trees, false, false);
+        let labels: Vec<&str> = flat.iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, ["assets:cash", "expenses:food", "expenses:rent"]);
+    }
+}
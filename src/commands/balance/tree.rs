@@ -28,32 +28,56 @@ struct Ctx<'a> {
 
 pub(super) fn print(journal: &Journal, show_empty: bool) {
     let root = Account::from_transactions(&journal.transactions);
+    let width = calculate_width(&root, &journal.precisions);
+    print_accounts(journal, &root, show_empty, width);
+    if root.children.is_empty() {
+        return;
+    }
+    println!("{}", "-".repeat(width));
+    print_total(&root.total(), width, &journal.precisions);
+}
 
+/// Render every top-level branch of `root` as an indented tree, amounts
+/// right-aligned to `width`. The grand total is left to the caller, so
+/// the financial statements can stack several trees with their own
+/// subtotals.
+fn print_accounts(journal: &Journal, root: &Account, show_empty: bool, width: usize) {
     let ctx = Ctx {
-        width: calculate_width(&root, &journal.precisions),
+        width,
         precisions: &journal.precisions,
         journal,
         show_empty,
     };
-
     for child in root.children.values() {
         if show_empty || child.has_balance(ctx.precisions) {
             print_account(&ctx, "", &child.name, child);
         }
     }
+}
 
-    if root.children.is_empty() {
-        return;
-    }
+/// A titled block of a financial statement: the title, the tree under
+/// `root`, a rule and the section total.
+pub(crate) fn print_section(journal: &Journal, title: &str, root: &Account, show_empty: bool, width: usize) {
+    println!("{}", title.bold());
+    print_accounts(journal, root, show_empty, width);
+    println!("{}", "-".repeat(width));
+    print_total(&root.total(), width, &journal.precisions);
+    println!();
+}
 
-    println!("{}", "-".repeat(ctx.width));
-    let total = root.total();
-    if total.iter().all(|(c, v)| !shows_nonzero(c, v, ctx.precisions)) {
-        println!("{:>w$} ", 0, w = ctx.width);
+/// One line per commodity of `total` that shows non-zero, or a single
+/// `0` when none does.
+pub(crate) fn print_total(
+    total: &BTreeMap<String, Decimal>,
+    width: usize,
+    precisions: &HashMap<String, usize>,
+) {
+    if total.iter().all(|(c, v)| !shows_nonzero(c, v, precisions)) {
+        println!("{:>w$} ", 0, w = width);
     } else {
-        for (commodity, value) in &total {
-            if shows_nonzero(commodity, value, ctx.precisions) {
-                print_commodity_amount(commodity, *value, ctx.width, ctx.precisions);
+        for (commodity, value) in total {
+            if shows_nonzero(commodity, value, precisions) {
+                print_commodity_amount(commodity, *value, width, precisions);
                 println!();
             }
         }
@@ -100,24 +124,21 @@ fn print_account(ctx: &Ctx, indent: &str, path: &str, account: &Account) {
     }
 }
 
-fn calculate_width(root: &Account, precisions: &HashMap<String, usize>) -> usize {
+/// Widest formatted amount over every node of `root` and its grand total.
+pub(crate) fn calculate_width(root: &Account, precisions: &HashMap<String, usize>) -> usize {
     let mut max_width = 0;
-    let mut visit = |acc: &Account| {
-        for (commodity, value) in acc.total() {
-            let w = format_amount(&commodity, &value, precisions).chars().count();
-            if w > max_width {
-                max_width = w;
-            }
-        }
-    };
-    root.walk(&mut visit);
+    root.walk(&mut |acc: &Account| {
+        max_width = max_width.max(total_width(&acc.total(), precisions));
+    });
     // Grand total over the root also contributes.
-    let grand: BTreeMap<String, Decimal> = root.total();
-    for (commodity, value) in &grand {
-        let w = format_amount(commodity, value, precisions).chars().count();
-        if w > max_width {
-            max_width = w;
-        }
-    }
-    max_width
+    max_width.max(total_width(&root.total(), precisions))
+}
+
+/// Widest formatted amount in `total`.
+pub(crate) fn total_width(total: &BTreeMap<String, Decimal>, precisions: &HashMap<String, usize>) -> usize {
+    total
+        .iter()
+        .map(|(commodity, value)| format_amount(commodity, value, precisions).chars().count())
+        .max()
+        .unwrap_or(0)
 }
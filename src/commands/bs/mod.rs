@@ -0,0 +1,164 @@
+//! `bs` command — balance sheet: assets, liabilities and equity at a
+//! date, one indented tree per section.
+//!
+//! Accounts are sorted into sections by their declared `type` (see
+//! [`AccountTypes`](crate::resolver::AccountTypes)). Income and expense
+//! never appear as such: their sum is rolled into equity, as a
+//! `net income` node for the report period and a `retained earnings`
+//! node for everything before it. Amounts keep their ledger signs, so
+//! liabilities and equity read negative and the final total is zero once
+//! every account with a balance carries a type.
+//!
+//! Balances are cumulative: the caller keeps every transaction up to the
+//! end date and passes `-b` only as the start of the net-income period.
+//!
+//! Under `-O json|csv|tsv` the sheet is one row per section, account
+//! node and commodity (see `balance`); the total is left out.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::commands::account::Account;
+use crate::commands::balance::{calculate_width, print_section, print_total, statement_table, total_width};
+use crate::commands::output::Format;
+use crate::date::Date;
+use crate::decimal::Decimal;
+use crate::loader::Journal;
+use crate::resolver::AccountType;
+
+/// The three section trees, plus the accounts left out for want of a type.
+struct Sheet {
+    assets: Account,
+    liabilities: Account,
+    equity: Account,
+    untyped: BTreeSet<String>,
+}
+
+pub fn run(journal: &Journal, show_empty: bool, begin: Option<Date>, format: Format) {
+    if journal.account_types.is_empty() {
+        eprintln!("bs: no account types declared — add `type asset|liability|equity|income|expense` under `account NAME`");
+        return;
+    }
+    let sheet = build(journal, begin);
+    if !sheet.untyped.is_empty() {
+        let names: Vec<&str> = sheet.untyped.iter().map(String::as_str).collect();
+        eprintln!("bs: left out, no declared type: {}", names.join(", "));
+    }
+
+    if format != Format::Text {
+        let sections = [
+            ("assets", &sheet.assets),
+            ("liabilities", &sheet.liabilities),
+            ("equity", &sheet.equity),
+        ];
+        return statement_table(journal, &sections, show_empty).print(format);
+    }
+
+    let sections = [
+        ("Assets", &sheet.assets),
+        ("Liabilities", &sheet.liabilities),
+        ("Equity", &sheet.equity),
+    ];
+    let mut total: BTreeMap<String, Decimal> = BTreeMap::new();
+    for (_, root) in &sections {
+        for (commodity, value) in root.total() {
+            *total.entry(commodity).or_insert_with(Decimal::zero) += value;
+        }
+    }
+    let width = sections
+        .iter()
+        .map(|(_, root)| calculate_width(root, &journal.precisions))
+        .chain(std::iter::once(total_width(&total, &journal.precisions)))
+        .max()
+        .unwrap_or(0);
+
+    for (title, root) in sections {
+        print_section(journal, title, root, show_empty, width);
+    }
+    println!("{}", "=".repeat(width));
+    print_total(&total, width, &journal.precisions);
+}
+
+fn build(journal: &Journal, begin: Option<Date>) -> Sheet {
+    let mut sheet = Sheet {
+        assets: Account::root(),
+        liabilities: Account::root(),
+        equity: Account::root(),
+        untyped: BTreeSet::new(),
+    };
+    for lt in &journal.transactions {
+        for lp in &lt.value.postings {
+            let p = &lp.value;
+            let Some(amount) = &p.amount else { continue };
+            let node = match journal.account_types.get(&p.account) {
+                Some(AccountType::Asset) => sheet.assets.find_or_create(&p.account),
+                Some(AccountType::Liability) => sheet.liabilities.find_or_create(&p.account),
+                Some(AccountType::Equity) => sheet.equity.find_or_create(&p.account),
+                Some(AccountType::Income | AccountType::Expense) => {
+                    if begin.is_some_and(|b| lt.value.date < b) {
+                        sheet.equity.find_or_create("retained earnings")
+                    } else {
+                        sheet.equity.find_or_create("net income")
+                    }
+                }
+                None => {
+                    sheet.untyped.insert(p.account.to_string());
+                    continue;
+                }
+            };
+            node.add_amount(&amount.commodity, amount.value);
+        }
+    }
+    sheet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal(src: &str) -> Journal {
+        let mut journal = crate::testutil::load(src);
+        crate::pipeline::enrich(&mut journal, None, false, None, None).unwrap();
+        journal
+    }
+
+    const SRC: &str = "\
+        account assets\n    type asset\n\
+        account liabilities\n    type liability\n\
+        account equity\n    type equity\n\
+        account income\n    type income\n\
+        account expenses\n    type expense\n\
+        2024-01-01 * opening\n\
+        \tassets:bank  1000 EUR\n\
+        \tequity:opening\n\
+        2024-02-01 * salary\n\
+        \tassets:bank  3000 EUR\n\
+        \tincome:salary\n\
+        2024-03-01 * rent\n\
+        \texpenses:rent  800 EUR\n\
+        \tliabilities:card\n\
+        2024-03-02 * gift\n\
+        \tassets:bank  50 EUR\n\
+        \tother\n";
+
+    fn eur(root: &Account, path: &str) -> Decimal {
+        root.find(path).unwrap().total()["EUR"]
+    }
+
+    #[test]
+    fn rolls_income_and_expense_into_equity() {
+        let sheet = build(&journal(SRC), None);
+        assert_eq!(eur(&sheet.assets, "assets"), Decimal::from(4050));
+        assert_eq!(eur(&sheet.liabilities, "liabilities"), Decimal::from(-800));
+        assert_eq!(eur(&sheet.equity, "equity"), Decimal::from(-1000));
+        assert_eq!(eur(&sheet.equity, "net income"), Decimal::from(-2200));
+        assert!(sheet.equity.find("retained earnings").is_none());
+        assert_eq!(sheet.untyped.iter().collect::<Vec<_>>(), ["other"]);
+    }
+
+    #[test]
+    fn income_before_begin_is_retained() {
+        let sheet = build(&journal(SRC), Some(Date::parse("2024-03-01").unwrap()));
+        assert_eq!(eur(&sheet.equity, "retained earnings"), Decimal::from(-3000));
+        assert_eq!(eur(&sheet.equity, "net income"), Decimal::from(800));
+    }
+}
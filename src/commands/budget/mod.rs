@@ -0,0 +1,331 @@
+//! `budget` command — actual against budgeted amounts, per account and
+//! period.
+//!
+//! Layout, budgeted accounts as an indented tree, one line per commodity:
+//!
+//! ```text
+//!                ACTUAL   BUDGET    DIFF  USED
+//! expenses       EUR950  EUR1000  EUR-50   95%
+//!   food         EUR450   EUR500  EUR-50   90%
+//!   rent         EUR500   EUR500       0  100%
+//! --------------------------------------------
+//!                EUR950  EUR1000  EUR-50   95%
+//! ```
+//!
+//! The budget is the occurrences of `~~` budget blocks
+//! ([`Journal::budget`]); the rows are the accounts they budget and
+//! their parents, each cell its subtree's total, so actual postings on an
+//! unbudgeted sub-account count against the budgeted parent. `DIFF` is
+//! actual less budget, `USED` actual as a percentage of budget.
+//!
+//! The report runs from `-b` (else the first budgeted date) to `-e`;
+//! with an interval it repeats per month, quarter or year. The caller
+//! has already filtered and, under `-X`, converted both the actual
+//! transactions and the budget.
+//!
+//! Under `-O json|csv|tsv` it is one row per period, account and
+//! commodity with exact `actual`, `budget` and `diff`; the totals and the
+//! percentage are left out.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{self, BufWriter, Write};
+
+use colored::Colorize;
+
+use crate::commands::account::Account;
+use crate::commands::balance::Interval;
+use crate::commands::output::{exact, Format, Table};
+use crate::commands::util::{format_amount, shows_nonzero, write_spaces};
+use crate::date::Date;
+use crate::decimal::Decimal;
+use crate::filter::PatternMatcher;
+use crate::loader::Journal;
+
+const GAP: usize = 2;
+
+/// The `-O` columns.
+const COLUMNS: &[&str] = &["begin", "end", "account", "commodity", "actual", "budget", "diff"];
+
+/// One report line: the (indented) account on its first commodity, and
+/// that commodity's actual and budgeted amounts.
+struct Line {
+    label: String,
+    commodity: String,
+    actual: Decimal,
+    budget: Decimal,
+}
+
+pub fn run(
+    journal: &Journal,
+    patterns: &[String],
+    begin: Option<Date>,
+    end: Option<Date>,
+    interval: Option<Interval>,
+    aux_date: bool,
+    format: Format,
+) {
+    let matcher = PatternMatcher::from_parts(patterns);
+    let in_range = |d: Date| begin.is_none_or(|b| d >= b) && end.is_none_or(|e| d < e);
+    let budgeted: Vec<(Date, &str, &str, Decimal)> = journal
+        .budget
+        .iter()
+        .flat_map(|lt| {
+            lt.value.postings.iter().filter_map(|lp| {
+                let p = &lp.value;
+                let amount = p.amount.as_ref()?;
+                let date = lt.value.posting_date(p, aux_date);
+                (in_range(date) && matcher.matches(&p.account))
+                    .then_some((date, p.account.as_str(), amount.commodity.as_str(), amount.value))
+            })
+        })
+        .collect();
+    let Some(first) = budgeted.iter().map(|b| b.0).min() else {
+        eprintln!("budget: nothing budgeted in range — declare a `~~ YEAR [monthly]` block");
+        return;
+    };
+
+    let actual_dates = journal.transactions.iter().flat_map(|lt| {
+        lt.value.postings.iter().map(|lp| lt.value.posting_date(&lp.value, aux_date))
+    });
+    let last = budgeted.iter().map(|b| b.0).chain(actual_dates).max().unwrap_or(first);
+    let periods = periods(begin.unwrap_or(first), end.unwrap_or(last.next_day()), interval);
+
+    let mut table = Table::new(COLUMNS);
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    for (i, (start, stop)) in periods.iter().enumerate() {
+        let mut plan = Account::root();
+        for (date, account, commodity, value) in &budgeted {
+            if date >= start && date < stop {
+                plan.find_or_create(account).add_amount(commodity, *value);
+            }
+        }
+        if plan.children.is_empty() {
+            continue;
+        }
+        let mut actual = Account::root();
+        for lt in &journal.transactions {
+            for lp in &lt.value.postings {
+                let p = &lp.value;
+                let Some(amount) = &p.amount else { continue };
+                let date = lt.value.posting_date(p, aux_date);
+                if date >= *start && date < *stop {
+                    actual.find_or_create(&p.account).add_amount(&amount.commodity, amount.value);
+                }
+            }
+        }
+        if format != Format::Text {
+            push_rows(&mut table, (*start, *stop), &plan, &actual);
+            continue;
+        }
+        if i > 0 {
+            let _ = writeln!(out);
+        }
+        if let Some(interval) = interval {
+            let _ = writeln!(out, "{}", interval.label(*start).bold());
+        }
+        let _ = print_table(&mut out, journal, &plan, &actual);
+    }
+    if format != Format::Text {
+        let _ = table.write(&mut out, format);
+    }
+    let _ = out.flush();
+}
+
+/// The `[start, end)` report periods: one for the whole range, or one per
+/// interval snapped outward to whole periods.
+fn periods(begin: Date, end: Date, interval: Option<Interval>) -> Vec<(Date, Date)> {
+    let Some(interval) = interval else {
+        return vec![(begin, end)];
+    };
+    let mut periods = Vec::new();
+    let mut start = interval.start(begin);
+    while start < end {
+        let next = interval.next(start);
+        periods.push((start, next));
+        start = next;
+    }
+    periods
+}
+
+/// The rows for `node` and its budgeted descendants, depth first.
+fn tree_lines(node: &Account, indent: &str, actual: &Account, lines: &mut Vec<Line>) {
+    let spent = actual.find(&node.fullname).map(Account::total).unwrap_or_default();
+    push_lines(format!("{}{}", indent, node.name), &spent, &node.total(), lines);
+    let child_indent = format!("{}  ", indent);
+    for child in node.children.values() {
+        tree_lines(child, &child_indent, actual, lines);
+    }
+}
+
+/// The structured rows of one period: every budgeted account node and
+/// commodity, as in [`tree_lines`].
+fn push_rows(table: &mut Table, (start, stop): (Date, Date), plan: &Account, actual: &Account) {
+    plan.walk(&mut |node| {
+        let spent = actual.find(&node.fullname).map(Account::total).unwrap_or_default();
+        let mut lines = Vec::new();
+        push_lines(String::new(), &spent, &node.total(), &mut lines);
+        for line in lines {
+            table.push(vec![
+                Some(start.to_string()),
+                Some(stop.to_string()),
+                Some(node.fullname.clone()),
+                Some(line.commodity),
+                Some(exact(&line.actual)),
+                Some(exact(&line.budget)),
+                Some(exact(&(line.actual - line.budget))),
+            ]);
+        }
+    });
+}
+
+/// One line per commodity in either total; the label goes on the first.
+fn push_lines(
+    label: String,
+    actual: &BTreeMap<String, Decimal>,
+    budget: &BTreeMap<String, Decimal>,
+    lines: &mut Vec<Line>,
+) {
+    let commodities: BTreeSet<&String> = actual.keys().chain(budget.keys()).collect();
+    for (i, commodity) in commodities.into_iter().enumerate() {
+        lines.push(Line {
+            label: if i == 0 { label.clone() } else { String::new() },
+            commodity: commodity.clone(),
+            actual: actual.get(commodity).copied().unwrap_or_default(),
+            budget: budget.get(commodity).copied().unwrap_or_default(),
+        });
+    }
+}
+
+fn print_table<W: Write>(out: &mut W, journal: &Journal, plan: &Account, actual: &Account) -> io::Result<()> {
+    let mut lines = Vec::new();
+    for child in plan.children.values() {
+        tree_lines(child, "", actual, &mut lines);
+    }
+    let mut spent: BTreeMap<String, Decimal> = BTreeMap::new();
+    for child in plan.children.values() {
+        for (commodity, value) in actual.find(&child.fullname).map(Account::total).unwrap_or_default() {
+            *spent.entry(commodity).or_default() += value;
+        }
+    }
+    let mut total = Vec::new();
+    push_lines(String::new(), &spent, &plan.total(), &mut total);
+
+    let p = &journal.precisions;
+    let header = ["ACTUAL", "BUDGET", "DIFF", "USED"].map(|h| (h.to_string(), false));
+    let cells = |lines: &[Line]| -> Vec<[(String, bool); 4]> { lines.iter().map(|l| line_cells(l, p)).collect() };
+    let (line_cells, total_cells) = (cells(&lines), cells(&total));
+    let label_width = lines.iter().map(|l| l.label.chars().count()).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..4)
+        .map(|i| {
+            line_cells
+                .iter()
+                .chain(&total_cells)
+                .map(|c| c[i].0.chars().count())
+                .chain(std::iter::once(header[i].0.len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    write_spaces(out, label_width)?;
+    print_cells(out, &header, &widths)?;
+    for (line, cells) in lines.iter().zip(&line_cells) {
+        write!(out, "{}", line.label.blue())?;
+        write_spaces(out, label_width - line.label.chars().count())?;
+        print_cells(out, cells, &widths)?;
+    }
+    writeln!(out, "{}", "-".repeat(label_width + widths.iter().map(|w| w + GAP).sum::<usize>()))?;
+    for cells in &total_cells {
+        write_spaces(out, label_width)?;
+        print_cells(out, cells, &widths)?;
+    }
+    Ok(())
+}
+
+/// `ACTUAL`, `BUDGET`, `DIFF` and `USED` for one line, each carrying
+/// whether it is negative. `USED` is `-` when nothing is budgeted.
+fn line_cells(line: &Line, precisions: &HashMap<String, usize>) -> [(String, bool); 4] {
+    let amount = |v: Decimal| {
+        if shows_nonzero(&line.commodity, &v, precisions) {
+            (format_amount(&line.commodity, &v, precisions), v.is_negative())
+        } else {
+            ("0".to_string(), false)
+        }
+    };
+    let used = if shows_nonzero(&line.commodity, &line.budget, precisions) {
+        let pct = line.actual.mul_rounded(Decimal::from(100)).div_rounded(line.budget);
+        (format!("{}%", pct.format_decimal(0)), pct.is_negative())
+    } else {
+        ("-".to_string(), false)
+    };
+    [amount(line.actual), amount(line.budget), amount(line.actual - line.budget), used]
+}
+
+/// Write one line of right-aligned cells after the account column.
+/// Negative cells are painted red.
+fn print_cells<W: Write>(out: &mut W, cells: &[(String, bool)], widths: &[usize]) -> io::Result<()> {
+    for ((cell, negative), width) in cells.iter().zip(widths) {
+        write_spaces(out, GAP + width.saturating_sub(cell.chars().count()))?;
+        if *negative {
+            write!(out, "{}", cell.red())?;
+        } else {
+            write!(out, "{}", cell)?;
+        }
+    }
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_roll_actuals_up_into_budgeted_accounts() {
+        let src = "\
+            ~~ 2025 monthly\n\
+            \texpenses:food  6000 EUR\n\
+            \texpenses:rent  12000 EUR\n\
+            2025-01-05 * shop\n\
+            \texpenses:food:groceries  450 EUR\n\
+            \tassets:bank\n\
+            2025-01-06 * rent\n\
+            \texpenses:rent  1000 EUR\n\
+            \tassets:bank\n";
+        let journal = crate::testutil::load(src);
+        // The budget books nothing: only the two real transactions exist.
+        assert_eq!(journal.transactions.len(), 2);
+        assert_eq!(journal.budget.len(), 12);
+
+        let mut plan = Account::root();
+        for lp in &journal.budget[0].value.postings {
+            let a = lp.value.amount.as_ref().unwrap();
+            plan.find_or_create(&lp.value.account).add_amount(&a.commodity, a.value);
+        }
+        let actual = Account::from_transactions(&journal.transactions);
+        let mut lines = Vec::new();
+        tree_lines(&plan.children["expenses"], "", &actual, &mut lines);
+        let got: Vec<(&str, Decimal, Decimal)> =
+            lines.iter().map(|l| (l.label.as_str(), l.actual, l.budget)).collect();
+        assert_eq!(
+            got,
+            [
+                ("expenses", Decimal::from(1450), Decimal::from(1500)),
+                ("  food", Decimal::from(450), Decimal::from(500)),
+                ("  rent", Decimal::from(1000), Decimal::from(1000)),
+            ]
+        );
+        let cells = line_cells(&lines[1], &journal.precisions);
+        assert_eq!(cells[2].0, "EUR-50");
+        assert_eq!(cells[3].0, "90%");
+
+        let mut table = Table::new(COLUMNS);
+        let period = (Date::parse("2025-01-01").unwrap(), Date::parse("2025-02-01").unwrap());
+        push_rows(&mut table, period, &plan, &actual);
+        let mut out = Vec::new();
+        table.write(&mut out, Format::Csv).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        assert_eq!(csv.lines().count(), 4);
+        assert!(csv.contains("\n2025-01-01,2025-02-01,expenses:food,EUR,450,500,-50\n"));
+    }
+}
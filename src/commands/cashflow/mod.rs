@@ -0,0 +1,239 @@
+//! `cashflow` command — cash flow statement: movement on a set of cash
+//! accounts over a period, grouped by where the cash came from or went.
+//!
+//! The cash accounts are the positional pattern. The caller keeps whole
+//! transactions (every posting of any entry that touches cash) and drops
+//! nothing before `-b`: earlier cash postings make up the opening balance.
+//! Within the period each transaction's cash movement is attributed to
+//! its counter-party postings — the siblings `--related` would show —
+//! each claiming its own negated amount in the commodity the cash moved
+//! in. What a trade leaves over (cash paid for a commodity that isn't
+//! cash) goes to the first counter-party in another commodity, so buying
+//! shares books the full price against the share account. Transfers
+//! between two cash accounts have no counter-party and show only in the
+//! balances.
+//!
+//! Counter-parties are sectioned by their `cashflow operating|investing|
+//! financing` declaration (see [`CashflowSections`](crate::resolver::CashflowSections));
+//! undeclared ones are operating.
+//!
+//! Under `-O json|csv|tsv` the statement is one row per section, account
+//! node and commodity (see `balance`), then the `opening`, `net` and
+//! `closing` balances as rows with no account.
+
+use std::collections::BTreeMap;
+
+use colored::Colorize;
+
+use crate::commands::account::Account;
+use crate::commands::balance::{calculate_width, print_section, print_total, statement_table, total_width};
+use crate::commands::output::{exact, Format};
+use crate::commands::util::shows_nonzero;
+use crate::date::Date;
+use crate::decimal::Decimal;
+use crate::filter::PatternMatcher;
+use crate::loader::Journal;
+use crate::resolver::CashflowSection;
+
+/// Per-commodity totals.
+type Totals = BTreeMap<String, Decimal>;
+
+/// The statement: opening balance, one counter-party tree per section
+/// and the net change over the period.
+struct Flows {
+    opening: Totals,
+    operating: Account,
+    investing: Account,
+    financing: Account,
+    net: Totals,
+}
+
+pub fn run(journal: &Journal, patterns: &[String], begin: Option<Date>, show_empty: bool, format: Format) {
+    if patterns.is_empty() {
+        eprintln!("cashflow: name the cash accounts, e.g. `acc cashflow ^assets:bank`");
+        return;
+    }
+    let flows = build(journal, patterns, begin);
+    let mut closing = flows.opening.clone();
+    add_into(&mut closing, &flows.net);
+
+    if format != Format::Text {
+        let sections = [
+            ("operating", &flows.operating),
+            ("investing", &flows.investing),
+            ("financing", &flows.financing),
+        ];
+        let mut table = statement_table(journal, &sections, show_empty);
+        for (section, totals) in [("opening", &flows.opening), ("net", &flows.net), ("closing", &closing)] {
+            for (commodity, value) in totals {
+                if show_empty || shows_nonzero(commodity, value, &journal.precisions) {
+                    table.push(vec![
+                        Some(section.to_string()),
+                        None,
+                        None,
+                        Some(commodity.clone()),
+                        Some(exact(value)),
+                    ]);
+                }
+            }
+        }
+        return table.print(format);
+    }
+
+    let sections = [
+        ("Operating", &flows.operating),
+        ("Investing", &flows.investing),
+        ("Financing", &flows.financing),
+    ];
+    let precisions = &journal.precisions;
+    let width = sections
+        .iter()
+        .map(|(_, root)| calculate_width(root, precisions))
+        .chain([&flows.opening, &flows.net, &closing].map(|t| total_width(t, precisions)))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    println!("{}", "Opening balance".bold());
+    print_total(&flows.opening, width, precisions);
+    println!();
+    for (title, root) in sections {
+        if show_empty || !root.children.is_empty() {
+            print_section(journal, title, root, show_empty, width);
+        }
+    }
+    println!("{}", "=".repeat(width));
+    println!("{}", "Net change".bold());
+    print_total(&flows.net, width, precisions);
+    println!("{}", "Closing balance".bold());
+    print_total(&closing, width, precisions);
+}
+
+fn build(journal: &Journal, patterns: &[String], begin: Option<Date>) -> Flows {
+    let matcher = PatternMatcher::from_parts(patterns);
+    let mut flows = Flows {
+        opening: Totals::new(),
+        operating: Account::root(),
+        investing: Account::root(),
+        financing: Account::root(),
+        net: Totals::new(),
+    };
+    for lt in &journal.transactions {
+        let tx = &lt.value;
+        let desc_lower = tx.description.to_lowercase();
+        let code_lower = tx.code.as_deref().unwrap_or("").to_lowercase();
+        let (cash, others): (Vec<_>, Vec<_>) = tx
+            .postings
+            .iter()
+            .filter_map(|lp| Some((&lp.value, lp.value.amount.as_ref()?)))
+            .partition(|(p, _)| matcher.matches_full(p, &desc_lower, &code_lower, tx.state, &tx.metadata));
+
+        let mut delta = Totals::new();
+        for (_, amount) in &cash {
+            *delta
+                .entry(amount.commodity.to_string())
+                .or_insert_with(Decimal::zero) += amount.value;
+        }
+        if begin.is_some_and(|b| tx.date < b) {
+            add_into(&mut flows.opening, &delta);
+            continue;
+        }
+        add_into(&mut flows.net, &delta);
+
+        for (commodity, moved) in &delta {
+            let mut left = *moved;
+            for (p, amount) in &others {
+                if *amount.commodity == **commodity {
+                    flows
+                        .section(journal, &p.account)
+                        .find_or_create(&p.account)
+                        .add_amount(commodity, -amount.value);
+                    left += amount.value;
+                }
+            }
+            if left.is_zero() {
+                continue;
+            }
+            let payee = others.iter().find(|(_, a)| *a.commodity != **commodity);
+            if let Some((p, _)) = payee {
+                flows
+                    .section(journal, &p.account)
+                    .find_or_create(&p.account)
+                    .add_amount(commodity, left);
+            }
+        }
+    }
+    flows
+}
+
+impl Flows {
+    /// The section tree a counter-party account books into.
+    fn section(&mut self, journal: &Journal, account: &str) -> &mut Account {
+        match journal.cashflow_sections.get(account) {
+            Some(CashflowSection::Investing) => &mut self.investing,
+            Some(CashflowSection::Financing) => &mut self.financing,
+            Some(CashflowSection::Operating) | None => &mut self.operating,
+        }
+    }
+}
+
+fn add_into(into: &mut Totals, from: &Totals) {
+    for (commodity, value) in from {
+        *into.entry(commodity.clone()).or_insert_with(Decimal::zero) += *value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal(src: &str) -> Journal {
+        let journal = crate::testutil::load(src);
+        journal
+    }
+
+    const SRC: &str = "\
+        account assets:broker\n    cashflow investing\n\
+        account liabilities:loan\n    cashflow financing\n\
+        2024-01-01 * opening\n\
+        \tassets:bank  1000 EUR\n\
+        \tequity:opening\n\
+        2024-02-01 * salary\n\
+        \tassets:bank  3000 EUR\n\
+        \tincome:salary\n\
+        2024-02-10 * save\n\
+        \tassets:savings  500 EUR\n\
+        \tassets:bank\n\
+        2024-03-01 * buy\n\
+        \tassets:broker  10 AAPL @ 100 EUR\n\
+        \texpenses:fees  5 EUR\n\
+        \tassets:bank  -1005 EUR\n\
+        2024-04-01 * loan\n\
+        \tassets:bank  2000 EUR\n\
+        \tliabilities:loan\n";
+
+    fn eur(root: &Account, path: &str) -> Decimal {
+        root.find(path).unwrap().total()["EUR"]
+    }
+
+    #[test]
+    fn attributes_cash_movement_to_counter_parties() {
+        let j = journal(SRC);
+        let flows = build(
+            &j,
+            &["^assets:bank".to_string(), "^assets:savings".to_string()],
+            Date::parse("2024-02-01").ok(),
+        );
+        assert_eq!(flows.opening["EUR"], Decimal::from(1000));
+        assert_eq!(flows.net["EUR"], Decimal::from(3995));
+        assert_eq!(eur(&flows.operating, "income:salary"), Decimal::from(3000));
+        assert_eq!(eur(&flows.operating, "expenses:fees"), Decimal::from(-5));
+        assert_eq!(eur(&flows.investing, "assets:broker"), Decimal::from(-1000));
+        assert_eq!(
+            eur(&flows.financing, "liabilities:loan"),
+            Decimal::from(2000)
+        );
+        // The transfer to savings stays between cash accounts.
+        assert!(flows.operating.find("assets").is_none());
+    }
+}
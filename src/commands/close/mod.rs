@@ -0,0 +1,309 @@
+//! `close` command — year-end closing and opening-balance entries.
+//!
+//! Everything before the closing date is summed per account and
+//! commodity. The *closing* entries, dated the day before, zero every
+//! income and expense account into the retained-earnings account, one
+//! entry per commodity. The *opening* entries, dated on the closing date,
+//! restore every other account — assets, liabilities, equity including
+//! the retained earnings just booked, and accounts without a declared
+//! type — against the opening account: one entry per commodity for plain
+//! balances, and one per account, commodity and cost commodity for open
+//! lots. A lot is written `qty {{total cost}} [acquired]`, which the
+//! lotter re-opens at that cost and date (see
+//! [`lotter`](crate::lotter)), so a year loaded on its own still realizes
+//! gains against the original purchase. Every counter leg is left bare
+//! for the booker to fill.
+//!
+//! Like `sweep`, the entries are printed to **stdout**, aligned and
+//! date-sorted via `format::format_source`; the closing entries belong at
+//! the end of the old year's file, the opening ones at the head of the
+//! new one. The caller has already cut the journal to the transactions
+//! before the date and run the enrichment phases, so lots and realized
+//! gains are in place.
+
+use std::collections::BTreeMap;
+
+use colored::Colorize;
+
+use crate::commands::util::{format_amount, shows_nonzero};
+use crate::date::Date;
+use crate::decimal::Decimal;
+use crate::error::Error;
+use crate::loader::Journal;
+use crate::lotter::OpenLot;
+use crate::resolver::AccountType;
+
+/// Per-account, per-commodity balances.
+type Balances = BTreeMap<String, BTreeMap<String, Decimal>>;
+
+/// Carried lots by account, commodity and cost commodity, each with the
+/// quantity carried.
+type LotGroups<'a> = BTreeMap<(&'a str, &'a str, &'a str), Vec<(Decimal, &'a OpenLot)>>;
+
+pub fn run(journal: &Journal, date: Date, retained: &str, opening: &str) -> Result<(), Error> {
+    if journal.account_types.is_empty() {
+        return Err(Error::from(
+            "close: no account types declared — add `type asset|liability|equity|income|expense` under `account NAME`",
+        ));
+    }
+    let balances = balances(journal);
+    let untyped: Vec<&str> = balances
+        .keys()
+        .filter(|a| journal.account_types.get(a).is_none() && *a != opening)
+        .map(String::as_str)
+        .collect();
+    if !untyped.is_empty() {
+        eprintln!(
+            "close: carried forward, no declared type: {}",
+            untyped.join(", ")
+        );
+    }
+
+    let (out, count) = render_entries(journal, balances, date, retained, opening);
+    if count == 0 {
+        eprintln!("{} nothing to close before {}", "!".yellow(), date);
+        return Ok(());
+    }
+    print!("{}", crate::commands::format::format_source(&out, true)?);
+
+    let label = if count == 1 { "entry" } else { "entries" };
+    eprintln!("{} {} closing and opening {}", "✓".green(), count, label);
+    Ok(())
+}
+
+/// Every account's balance, rounded to display precision; balances that
+/// round to zero are dropped. Unbalanced `(virtual)` postings are left
+/// out: they are annotations, not money to carry forward.
+fn balances(journal: &Journal) -> Balances {
+    let mut balances = Balances::new();
+    for lt in &journal.transactions {
+        for lp in &lt.value.postings {
+            let p = &lp.value;
+            if p.is_virtual && !p.balanced {
+                continue;
+            }
+            let Some(a) = &p.amount else { continue };
+            *balances
+                .entry(p.account.clone())
+                .or_default()
+                .entry(a.commodity.clone())
+                .or_default() += a.value;
+        }
+    }
+    let precisions = &journal.precisions;
+    for commodities in balances.values_mut() {
+        commodities.retain(|c, v| shows_nonzero(c, v, precisions));
+        for (c, v) in commodities.iter_mut() {
+            *v = v.round(precisions.get(c).copied().unwrap_or(2));
+        }
+    }
+    balances.retain(|_, commodities| !commodities.is_empty());
+    balances
+}
+
+/// The closing and opening entries as ledger text, and how many there
+/// are. Pure (no I/O) so the output can be loaded back in tests.
+fn render_entries(
+    journal: &Journal,
+    mut balances: Balances,
+    date: Date,
+    retained: &str,
+    opening: &str,
+) -> (String, usize) {
+    let precisions = &journal.precisions;
+    let is_pnl = |account: &str| {
+        matches!(
+            journal.account_types.get(account),
+            Some(AccountType::Income | AccountType::Expense)
+        )
+    };
+    let mut out = String::new();
+    let mut count = 0;
+
+    // Closing: income and expense into retained earnings, per commodity.
+    let closing_date = Date::from_days(date.days().saturating_sub(1));
+    let mut closing: BTreeMap<String, Vec<(String, Decimal)>> = BTreeMap::new();
+    for (account, commodities) in &balances {
+        if is_pnl(account) {
+            for (commodity, value) in commodities {
+                closing
+                    .entry(commodity.clone())
+                    .or_default()
+                    .push((account.clone(), *value));
+            }
+        }
+    }
+    for (commodity, legs) in &closing {
+        push_header(&mut out, closing_date, "closing");
+        let mut total = Decimal::zero();
+        for (account, value) in legs {
+            push_leg(
+                &mut out,
+                account,
+                &format_amount(commodity, &-*value, precisions),
+            );
+            total += *value;
+        }
+        push_leg(&mut out, retained, "");
+        out.push('\n');
+        count += 1;
+        *balances
+            .entry(retained.to_string())
+            .or_default()
+            .entry(commodity.clone())
+            .or_default() += total;
+    }
+
+    // Opening: open lots first, each group against its cost commodity;
+    // what the lots don't cover is a plain balance. The lotter may track
+    // more than an account holds (a receipt opens a lot, an earlier
+    // payment had none to close), so lots are carried newest first up to
+    // the balance.
+    let mut held: BTreeMap<(&str, &str), Vec<&OpenLot>> = BTreeMap::new();
+    for lot in &journal.lots {
+        if !lot.qty.is_negative() && !is_pnl(&lot.account) && lot.account != opening {
+            held.entry((&lot.account, &lot.commodity))
+                .or_default()
+                .push(lot);
+        }
+    }
+    let mut lots = LotGroups::new();
+    for ((account, commodity), mut group) in held {
+        let Some(balance) = balances.get_mut(account).and_then(|c| c.get_mut(commodity)) else {
+            continue;
+        };
+        group.sort_by_key(|lot| std::cmp::Reverse(lot.date));
+        for lot in group {
+            if balance.is_negative() || balance.is_zero() {
+                break;
+            }
+            let qty = lot.qty.min(*balance);
+            *balance -= qty;
+            lots.entry((account, commodity, &lot.cost_commodity))
+                .or_default()
+                .push((qty, lot));
+        }
+    }
+    for ((account, commodity, cost_commodity), mut group) in lots {
+        group.sort_by_key(|(_, lot)| lot.date);
+        push_header(&mut out, date, "opening balances");
+        for (qty, lot) in group {
+            let cost = qty.mul_rounded(lot.cost_per_unit);
+            let leg = format!(
+                "{} {{{{{}}}}} [{}]",
+                format_amount(commodity, &qty, precisions),
+                format_amount(cost_commodity, &cost, precisions),
+                lot.date
+            );
+            push_leg(&mut out, account, &leg);
+        }
+        push_leg(&mut out, opening, "");
+        out.push('\n');
+        count += 1;
+    }
+
+    let mut plain: BTreeMap<&str, Vec<(&str, Decimal)>> = BTreeMap::new();
+    for (account, commodities) in &balances {
+        if is_pnl(account) || account == opening {
+            continue;
+        }
+        for (commodity, value) in commodities {
+            if shows_nonzero(commodity, value, precisions) {
+                plain.entry(commodity).or_default().push((account, *value));
+            }
+        }
+    }
+    for (commodity, legs) in &plain {
+        push_header(&mut out, date, "opening balances");
+        for (account, value) in legs {
+            push_leg(
+                &mut out,
+                account,
+                &format_amount(commodity, value, precisions),
+            );
+        }
+        push_leg(&mut out, opening, "");
+        out.push('\n');
+        count += 1;
+    }
+
+    (out, count)
+}
+
+fn push_header(out: &mut String, date: Date, title: &str) {
+    out.push_str(&format!("{} * {}\n", date, title));
+}
+
+/// One posting line; an empty `amount` leaves the leg for the booker.
+fn push_leg(out: &mut String, account: &str, amount: &str) {
+    if amount.is_empty() {
+        out.push_str(&format!("\t{}\n", account));
+    } else {
+        out.push_str(&format!("\t{}\t{}\n", account, amount));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal(src: &str) -> Journal {
+        let mut journal = crate::testutil::load(src);
+        crate::pipeline::enrich(&mut journal, None, false, None, None).unwrap();
+        journal
+    }
+
+    const SRC: &str = "\
+        account assets\n    type asset\n\
+        account equity\n    type equity\n\
+        account income\n    type income\n\
+        account expenses\n    type expense\n\
+        account income:cap\n    capital gain\n\
+        account expenses:cap\n    capital loss\n\
+        2024-01-01 * opening\n\
+        \tassets:bank  5000.00 EUR\n\
+        \tequity:opening\n\
+        2024-02-01 * salary\n\
+        \tassets:bank  3000.00 EUR\n\
+        \tincome:salary\n\
+        2024-03-01 * rent\n\
+        \texpenses:rent  800.00 EUR\n\
+        \tassets:bank\n\
+        2024-04-01 * buy\n\
+        \tassets:btc  0.5 BTC @ 20000.00 EUR\n\
+        \tassets:bank\n";
+
+    #[test]
+    fn opening_entries_restore_balances_and_lots() {
+        let old = journal(SRC);
+        let date = Date::parse("2025-01-01").unwrap();
+        let (out, count) = render_entries(
+            &old,
+            balances(&old),
+            date,
+            "equity:retained",
+            "equity:opening",
+        );
+        // One closing entry (EUR), one lot entry (BTC at EUR cost), one
+        // plain opening entry (EUR).
+        assert_eq!(count, 3);
+        assert!(out.contains("\tassets:btc\tBTC0.5 {{EUR10000.00}} [2024-04-01]\n"));
+
+        // The opening entries alone reproduce the balance sheet.
+        let text = crate::commands::format::format_source(&out, true).unwrap();
+        let opening: String = text
+            .split("\n\n")
+            .filter(|e| e.contains("opening balances"))
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let new = journal(&format!("{}{}\n", &SRC[..SRC.find("2024-01-01").unwrap()], opening));
+        let b = balances(&new);
+        assert_eq!(b["assets:bank"]["EUR"], Decimal::from(-2800));
+        assert_eq!(b["assets:btc"]["BTC"], Decimal::new(5, 10));
+        assert_eq!(b["equity:retained"]["EUR"], Decimal::from(-2200));
+        assert!(!b.contains_key("income:salary"));
+        assert_eq!(new.lots.len(), 1);
+        assert_eq!(new.lots[0].date.to_string(), "2024-04-01");
+        assert_eq!(new.lots[0].cost_per_unit, Decimal::from(20000));
+    }
+}
@@ -1,11 +1,17 @@
 //! `codes` command — list every distinct transaction code used in
-//! the journal, one per line, sorted. Runs after the filter phase.
+//! the journal, one per line, sorted. Under `-O json|csv|tsv` each code
+//! is a row with the `file` / `line` of the first transaction using it.
+//! Runs after the filter phase.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
+use crate::commands::output::{Format, Table};
 use crate::loader::Journal;
 
-pub fn run(journal: &Journal) {
+pub fn run(journal: &Journal, format: Format) {
+    if format != Format::Text {
+        return table(journal).print(format);
+    }
     for code in journal
         .transactions
         .iter()
@@ -15,3 +21,21 @@ pub fn run(journal: &Journal) {
         println!("{}", code);
     }
 }
+
+fn table(journal: &Journal) -> Table {
+    let mut first = BTreeMap::new();
+    for lt in &journal.transactions {
+        if let Some(code) = lt.value.code.as_deref() {
+            first.entry(code).or_insert(lt);
+        }
+    }
+    let mut table = Table::new(&["code", "file", "line"]);
+    for (code, lt) in first {
+        table.push(vec![
+            Some(code.to_string()),
+            Some(lt.file.to_string()),
+            Some(lt.line.to_string()),
+        ]);
+    }
+    table
+}
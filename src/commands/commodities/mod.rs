@@ -1,34 +1,52 @@
 //! `commodities` command — list every commodity observed in postings,
 //! optionally with the earliest date on which it appeared. Runs after
 //! the filter phase.
+//!
+//! Under `-O json|csv|tsv` each commodity is a row with its first date and
+//! the `file` / `line` of the posting it first appeared on.
 
 use std::collections::BTreeMap;
+use std::sync::Arc;
 
+use crate::commands::output::{Format, Table};
 use crate::date::Date;
 use crate::loader::Journal;
 use crate::parser::posting::{Amount, Costs, Posting};
 
-pub fn run(journal: &Journal, show_date: bool) {
-    // commodity → earliest tx.date it occurred on
-    let mut first_seen: BTreeMap<String, Date> = BTreeMap::new();
+pub fn run(journal: &Journal, show_date: bool, format: Format) {
+    // commodity → earliest tx.date it occurred on, and where
+    let mut first_seen: BTreeMap<String, (Date, Arc<str>, usize)> = BTreeMap::new();
 
     for tx in &journal.transactions {
         for lp in &tx.value.postings {
             for commodity in commodities_in_posting(&lp.value) {
+                let seen = (tx.value.date, lp.file.clone(), lp.line);
                 first_seen
                     .entry(commodity)
                     .and_modify(|d| {
-                        if tx.value.date < *d {
-                            *d = tx.value.date;
+                        if tx.value.date < d.0 {
+                            *d = seen.clone();
                         }
                     })
-                    .or_insert(tx.value.date);
+                    .or_insert(seen);
             }
         }
     }
 
-    if show_date {
-        let mut entries: Vec<(&String, &Date)> = first_seen.iter().collect();
+    if format != Format::Text {
+        let mut table = Table::new(&["commodity", "first_seen", "file", "line"]);
+        for (commodity, (date, file, line)) in first_seen {
+            table.push(vec![
+                Some(commodity),
+                Some(date.to_string()),
+                Some(file.to_string()),
+                Some(line.to_string()),
+            ]);
+        }
+        table.print(format);
+    } else if show_date {
+        let mut entries: Vec<(&String, &Date)> =
+            first_seen.iter().map(|(c, seen)| (c, &seen.0)).collect();
         entries.sort_by(|a, b| a.1.cmp(b.1).then_with(|| a.0.cmp(b.0)));
         let width = entries
             .iter()
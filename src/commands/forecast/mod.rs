@@ -0,0 +1,127 @@
+//! `forecast` command — list the upcoming occurrences of the recurring
+//! rules (`~ EVERY from DATE`), or print them as ledger entries.
+//!
+//! The occurrences are the ones `--forecast UNTIL` would add to a report:
+//! from the day after the last recorded transaction up to UNTIL (see
+//! [`forecaster`](crate::forecaster)). By default they are booked and
+//! shown as a register. With `--emit` they are written out unbooked —
+//! a bare leg stays bare — and aligned via `format::format_source`, so
+//! they can be appended to the journal as-is, e.g.
+//! `acc forecast 2027-01 --emit >> FILE`; once recorded, the next
+//! forecast starts after them. Emitted entries carry no `forecast` tag:
+//! they are meant to become real ones. `-O json|csv|tsv` writes the
+//! register as structured rows (see `register`).
+
+use std::collections::HashMap;
+
+use colored::Colorize;
+
+use crate::commands::output::Format;
+use crate::commands::util::{format_amount, render_marked_account};
+use crate::date::Date;
+use crate::error::Error;
+use crate::loader::Journal;
+use crate::parser::located::Located;
+use crate::parser::posting::Costs;
+use crate::parser::transaction::Transaction;
+
+pub fn run(mut journal: Journal, until: Date, emit: bool, format: Format) -> Result<(), Error> {
+    let from = crate::forecaster::window_start(&journal.transactions);
+    let generated = crate::forecaster::forecast(&journal.recurring, from, until, !emit);
+    if generated.is_empty() {
+        eprintln!("{} nothing to forecast before {}", "!".yellow(), until);
+        return Ok(());
+    }
+    let count = generated.len();
+
+    if emit {
+        let out = render_entries(&generated, &journal.precisions);
+        print!("{}", crate::commands::format::format_source(&out, true)?);
+    } else {
+        journal.transactions =
+            crate::booker::book(generated).map_err(|e| Error::from(e.to_string()))?;
+        crate::commands::register::run(&journal, false, false, format);
+    }
+
+    let label = if count == 1 {
+        "transaction"
+    } else {
+        "transactions"
+    };
+    eprintln!("{} forecast {} {} from {}", "✓".green(), count, label, from);
+    Ok(())
+}
+
+/// Ledger text for the unbooked occurrences: header, then each posting's
+/// account and, where written, its amount and `@` / `@@` cost.
+fn render_entries(
+    transactions: &[Located<Transaction>],
+    precisions: &HashMap<String, usize>,
+) -> String {
+    let mut out = String::new();
+    for lt in transactions {
+        let tx = &lt.value;
+        out.push_str(&format!("{} {}\n", tx.date, tx.description));
+        for lp in &tx.postings {
+            let p = &lp.value;
+            out.push('\t');
+            out.push_str(&render_marked_account(p));
+            if let Some(a) = &p.amount {
+                out.push_str(&format!(
+                    "\t{}",
+                    format_amount(&a.commodity, &a.value, precisions)
+                ));
+            }
+            match &p.costs {
+                Some(Costs::PerUnit(c)) => out.push_str(&format!(
+                    " @ {}",
+                    format_amount(&c.commodity, &c.value, precisions)
+                )),
+                Some(Costs::Total(c)) => out.push_str(&format!(
+                    " @@ {}",
+                    format_amount(&c.commodity, &c.value, precisions)
+                )),
+                None => {}
+            }
+            out.push('\n');
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn emits_entries_that_load_back() {
+        let src = "\
+            ~ quarterly from 2025-01-01 until 2025-12-31 insurance\n\
+            \texpenses:insurance  300 EUR\n\
+            \tassets:bank\n\
+            2025-04-01 insurance\n\
+            \texpenses:insurance  300 EUR\n\
+            \tassets:bank\n";
+        let tmp = crate::testutil::TempJournal::new(src);
+        let journal = crate::load(&[&tmp.path]).unwrap();
+
+        let from = crate::forecaster::window_start(&journal.transactions);
+        let until = Date::parse("2026-01-01").unwrap();
+        let generated = crate::forecaster::forecast(&journal.recurring, from, until, false);
+        let text = crate::commands::format::format_source(
+            &render_entries(&generated, &journal.precisions),
+            true,
+        )
+        .unwrap();
+        assert_eq!(text.matches("insurance\n").count(), 2);
+        assert!(text.starts_with("2025-07-01 insurance\n"));
+
+        // Appended to the journal, the emitted entries leave nothing to forecast.
+        std::fs::write(&tmp.path, format!("{}\n{}", src, text)).unwrap();
+        let journal = crate::load(&[&tmp.path]).unwrap();
+        assert_eq!(journal.transactions.len(), 3);
+        let from = crate::forecaster::window_start(&journal.transactions);
+        assert!(crate::forecaster::forecast(&journal.recurring, from, until, false).is_empty());
+    }
+}
@@ -20,7 +20,8 @@
 //! Inputs can mix files and directories; directories are walked
 //! recursively for journal files (`.ledger` only), matching the same
 //! collector pattern the main pipeline uses for `-f DIR`. Files named
-//! explicitly are kept regardless of extension.
+//! explicitly are kept regardless of extension. Files pulled in through
+//! `include` are formatted along with the file that includes them.
 
 use std::fs;
 use std::io;
@@ -28,7 +29,7 @@ use std::path::{Path, PathBuf};
 
 use colored::Colorize;
 
-use super::util::{push_spaces, render_account};
+use super::util::{push_spaces, render_marked_account};
 use crate::decimal::Decimal;
 use crate::error::Error;
 use crate::parser::{
@@ -71,6 +72,9 @@ pub fn run(paths: &[String], sort: bool, infer: bool, fill: bool) -> Result<(),
     // wouldn't load, and a single error aborts the whole run so there
     // are never half-formatted batches.
     crate::load(&files).map_err(|e| Error::from(e.to_string()))?;
+    // Format the whole tree the load just validated: every file reached
+    // through `include` is part of the journal and gets aligned too.
+    let files = crate::loader::journal_files(&files).map_err(|e| Error::from(e.to_string()))?;
 
     let total = files.len();
     for path in &files {
@@ -146,7 +150,8 @@ fn validate_source(source: &str) -> Result<(), Error> {
         .map_err(|e| Error::from(format!("parse stdin: {}", e)))?;
     let resolved =
         crate::resolver::resolve(entries).map_err(|e| Error::from(e.to_string()))?;
-    crate::booker::book(resolved.transactions).map_err(|e| Error::from(e.to_string()))?;
+    crate::booker::book_with_splits(resolved.transactions, &resolved.splits, &resolved.bookings)
+        .map_err(|e| Error::from(e.to_string()))?;
     Ok(())
 }
 
@@ -241,17 +246,17 @@ fn render(entries: &[Located<Entry>], source: &str, sort: bool, infer: bool, fil
                     &mut out,
                 );
             }
-            Entry::Price(_) => {
-                // Prices have no multi-line structure to align, so the
-                // source line is emitted verbatim. Preserves the exact
-                // rate as the user wrote it (no trailing-zero drift,
-                // no precision rounding).
+            Entry::Price(_) | Entry::Split(_) => {
+                // Prices and splits have no multi-line structure to
+                // align, so the source line is emitted verbatim.
+                // Preserves the exact rate as the user wrote it (no
+                // trailing-zero drift, no precision rounding).
                 if let Some(line) = source_lines.get(entry.line.saturating_sub(1)) {
                     out.push_str(line);
                     out.push('\n');
                 }
             }
-            Entry::Commodity { symbol, aliases, parities, precision } => {
+            Entry::Commodity { symbol, aliases, parities, precision, max_age, infer_prices } => {
                 out.push_str(&format!("commodity {}\n", symbol));
                 for a in aliases {
                     out.push_str(INDENT);
@@ -265,6 +270,14 @@ fn render(entries: &[Located<Entry>], source: &str, sort: bool, infer: bool, fil
                     out.push_str(INDENT);
                     out.push_str(&format!("precision {}\n", p));
                 }
+                if let Some(days) = max_age {
+                    out.push_str(INDENT);
+                    out.push_str(&format!("max-age {}\n", days));
+                }
+                if *infer_prices {
+                    out.push_str(INDENT);
+                    out.push_str("infer-prices\n");
+                }
             }
             Entry::Account(name) => {
                 out.push_str(&format!("account {}\n", name));
@@ -275,17 +288,23 @@ fn render(entries: &[Located<Entry>], source: &str, sort: bool, infer: bool, fil
                 out.push_str(role);
                 out.push('\n');
             }
+            Entry::Include(path) => {
+                out.push_str(&format!("include {}\n", path));
+            }
             Entry::Comment(text) => {
                 out.push_str(text);
                 if !text.ends_with('\n') {
                     out.push('\n');
                 }
             }
-            // Auto-rule / template / periodic blocks span multiple lines (header
+            // Auto-rule / template / periodic / recurring blocks span multiple lines (header
             // + indented children); their body here needs no re-derivation —
             // simpler to emit the source lines verbatim than rebuild the syntax.
             // The block ends at the next line that isn't indented.
-            Entry::AutoRule(_) | Entry::AutoTemplate { .. } | Entry::Periodic { .. } => {
+            Entry::AutoRule(_)
+            | Entry::AutoTemplate { .. }
+            | Entry::Periodic { .. }
+            | Entry::Recurring(_) => {
                 let start = entry.line.saturating_sub(1);
                 let mut end = start + 1;
                 while end < source_lines.len() {
@@ -403,6 +422,9 @@ fn render_transaction(
     out: &mut String,
 ) {
     out.push_str(&tx.date.to_string());
+    if let Some(aux) = tx.aux_date {
+        out.push_str(&format!("={}", aux));
+    }
     match tx.state {
         State::Cleared => out.push_str(" *"),
         State::Pending => out.push_str(" !"),
@@ -462,7 +484,7 @@ fn render_posting(
     fill: Option<&str>,
     out: &mut String,
 ) {
-    let account = render_account(p);
+    let account = render_marked_account(p);
     let parts = source_line
         .map(extract_posting_parts)
         .unwrap_or_default();
@@ -532,7 +554,7 @@ fn column_widths(entries: &[Located<Entry>], source_lines: &[&str]) -> (usize, u
     for entry in entries {
         if let Entry::Transaction(tx) = &entry.value {
             for lp in &tx.postings {
-                let a = render_account(&lp.value);
+                let a = render_marked_account(&lp.value);
                 account_max = account_max.max(a.chars().count());
                 if let Some(src) = source_lines.get(lp.line.saturating_sub(1)).copied() {
                     let parts = extract_posting_parts(src);
@@ -565,6 +587,12 @@ struct PostingParts {
 ///   which introduce cost, assertion, or lot annotations).
 fn extract_posting_parts(source_line: &str) -> PostingParts {
     let body = source_line.trim_start();
+    // A posting's own `* ` / `! ` state marker precedes the account; drop
+    // it so a tab after the marker isn't taken for the amount separator.
+    let body = match body.as_bytes() {
+        [b'*' | b'!', b' ' | b'\t', ..] => body[2..].trim_start(),
+        _ => body,
+    };
     let (body_main, comment) = strip_inline_comment(body);
 
     let Some((_acc, rest)) = split_body(body_main) else {
@@ -669,4 +697,13 @@ mod tests {
         let p = extract_posting_parts("\trud:11:a  XMR4.314  ; note");
         assert_eq!(p.tail, "  ; note");
     }
+
+    #[test]
+    fn posting_state_marker_round_trips() {
+        let src = "2024-01-01 Coffee\n\t*\texpenses:food\t5 EUR\n\tassets:cash\n";
+        let out = format_source(src, false).unwrap();
+        assert!(out.contains("\t* expenses:food  "), "{}", out);
+        assert!(out.contains("5 EUR\n"), "{}", out);
+        assert_eq!(format_source(&out, false).unwrap(), out);
+    }
 }
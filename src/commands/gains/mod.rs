@@ -0,0 +1,320 @@
+//! `gains` command — one row per lot a disposal closed, with proceeds,
+//! cost basis, realized gain and holding-period term (the shape of an
+//! IRS Form 8949 line).
+//!
+//! Layout as a table, rows in disposal order, then the totals per
+//! commodity and per disposal year:
+//!
+//! ```text
+//! COMMODITY  QUANTITY  ACQUIRED    DISPOSED    PROCEEDS  COST BASIS      GAIN  TERM
+//! BTC          BTC0.5  2024-01-01  2024-06-01  EUR25000    EUR15000  EUR10000  short
+//! ---------------------------------------------------------------------------------
+//! BTC          BTC0.5                          EUR25000    EUR15000  EUR10000
+//! 2024                                         EUR25000    EUR15000  EUR10000
+//! ```
+//!
+//! `-O json|csv|tsv` writes the rows alone — one record per closed lot,
+//! so the file imports as is — with exact numbers and the amount currency
+//! in its own column (see [`output`](crate::commands::output)).
+//!
+//! The rows are the lotter's own disposals, so they exist only when the
+//! `capital gain` / `capital loss` accounts are declared. Under `-X` the
+//! amounts are the holding-period market move in the target; natively
+//! they are the booked trade amounts in the counter-commodity.
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, BufWriter, Write};
+
+use colored::Colorize;
+
+use super::output::{exact, Format, Table};
+use super::util::{format_amount, write_spaces};
+use crate::date::Date;
+use crate::decimal::Decimal;
+use crate::filter::PatternMatcher;
+use crate::loader::Journal;
+use crate::lotter::RealizedLot;
+
+const GAP: usize = 2;
+
+/// `patterns` scope the disposals by account; `in_period` selects them
+/// by disposal date (the `-b` / `-e` / `-p` range).
+pub fn run(journal: &Journal, patterns: &[String], in_period: impl Fn(Date) -> bool, format: Format) {
+    if journal.capital_gain.is_none() || journal.capital_loss.is_none() {
+        eprintln!("gains: lot tracking needs `capital gain` and `capital loss` accounts declared");
+        return;
+    }
+    let matcher = PatternMatcher::from_parts(patterns);
+    let rows: Vec<&RealizedLot> = journal
+        .realized
+        .iter()
+        .filter(|r| matcher.matches(&r.account) && in_period(r.disposed))
+        .collect();
+    if format != Format::Text {
+        return table(&rows).print(format);
+    }
+
+    // One locked, buffered writer for the whole report — see `print`.
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let _ = write_table(&mut out, &rows, &journal.precisions);
+    let _ = out.flush();
+}
+
+/// Summed proceeds, cost basis and gain of a group of rows. `qty` only
+/// adds up within one commodity, so the per-year totals leave it out.
+#[derive(Debug, PartialEq)]
+struct Total {
+    qty: Option<Decimal>,
+    proceeds: Decimal,
+    cost_basis: Decimal,
+    gain: Decimal,
+}
+
+impl Total {
+    fn add(&mut self, r: &RealizedLot) {
+        self.qty = self.qty.map(|q| q + r.qty);
+        self.proceeds += r.proceeds;
+        self.cost_basis += r.cost_basis;
+        self.gain += r.gain;
+    }
+}
+
+/// Totals keyed by (commodity, currency) and by (year, currency): gains
+/// in different currencies (native mode, mixed counter-commodities)
+/// can't be summed, so each currency totals on its own.
+type Totals = (BTreeMap<(String, String), Total>, BTreeMap<(u16, String), Total>);
+
+fn totals(rows: &[&RealizedLot]) -> Totals {
+    let zero = |qty: bool| Total {
+        qty: qty.then(Decimal::zero),
+        proceeds: Decimal::zero(),
+        cost_basis: Decimal::zero(),
+        gain: Decimal::zero(),
+    };
+    let mut by_commodity = BTreeMap::new();
+    let mut by_year = BTreeMap::new();
+    for r in rows {
+        by_commodity
+            .entry((r.commodity.clone(), r.currency.clone()))
+            .or_insert_with(|| zero(true))
+            .add(r);
+        by_year
+            .entry((r.disposed.year(), r.currency.clone()))
+            .or_insert_with(|| zero(false))
+            .add(r);
+    }
+    (by_commodity, by_year)
+}
+
+fn term(r: &RealizedLot) -> &'static str {
+    if r.long_term { "long" } else { "short" }
+}
+
+fn write_table<W: Write>(
+    out: &mut W,
+    rows: &[&RealizedLot],
+    precisions: &HashMap<String, usize>,
+) -> io::Result<()> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+    let header = ["COMMODITY", "QUANTITY", "ACQUIRED", "DISPOSED", "PROCEEDS", "COST BASIS", "GAIN", "TERM"];
+    let money = |currency: &str, v: &Decimal| format_amount(currency, v, precisions);
+    let mut lines: Vec<(Vec<String>, Option<Decimal>)> = rows
+        .iter()
+        .map(|r| {
+            let cells = vec![
+                r.commodity.clone(),
+                format_amount(&r.commodity, &r.qty, precisions),
+                r.acquired.to_string(),
+                r.disposed.to_string(),
+                money(&r.currency, &r.proceeds),
+                money(&r.currency, &r.cost_basis),
+                money(&r.currency, &r.gain),
+                term(r).to_string(),
+            ];
+            (cells, Some(r.gain))
+        })
+        .collect();
+    let body = lines.len();
+
+    let (by_commodity, by_year) = totals(rows);
+    let total_line = |label: String, qty: String, currency: &str, t: &Total| {
+        let cells = vec![
+            label,
+            qty,
+            String::new(),
+            String::new(),
+            money(currency, &t.proceeds),
+            money(currency, &t.cost_basis),
+            money(currency, &t.gain),
+            String::new(),
+        ];
+        (cells, Some(t.gain))
+    };
+    for ((commodity, currency), t) in &by_commodity {
+        let qty = t.qty.map(|q| format_amount(commodity, &q, precisions)).unwrap_or_default();
+        lines.push(total_line(commodity.clone(), qty, currency, t));
+    }
+    for ((year, currency), t) in &by_year {
+        lines.push(total_line(year.to_string(), String::new(), currency, t));
+    }
+
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            lines
+                .iter()
+                .map(|(c, _)| c[i].chars().count())
+                .chain(std::iter::once(header[i].len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    print_line(out, &header, &widths, None)?;
+    for (i, (cells, gain)) in lines.iter().enumerate() {
+        if i == body {
+            let width = widths.iter().sum::<usize>() + GAP * (widths.len() - 1);
+            writeln!(out, "{}", "-".repeat(width))?;
+        }
+        print_line(out, cells, &widths, *gain)?;
+    }
+    Ok(())
+}
+
+/// Write one row: the commodity and the term left-aligned, the dates
+/// too, every amount right-aligned. A negative `gain` paints the gain
+/// column red.
+fn print_line<W: Write>(
+    out: &mut W,
+    cells: &[String],
+    widths: &[usize],
+    gain: Option<Decimal>,
+) -> io::Result<()> {
+    const GAIN_COLUMN: usize = 6;
+    let last = cells.len() - 1;
+    // Totals leave the trailing columns empty; stop before them.
+    let shown = cells.iter().rposition(|c| !c.is_empty()).map_or(0, |i| i + 1);
+    for (i, (cell, width)) in cells.iter().zip(widths).enumerate().take(shown) {
+        let pad = width.saturating_sub(cell.chars().count());
+        if i > 0 {
+            write_spaces(out, GAP)?;
+        }
+        let left = matches!(i, 0 | 2 | 3) || i == last;
+        if !left {
+            write_spaces(out, pad)?;
+        }
+        if i == GAIN_COLUMN && gain.is_some_and(|g| g.is_negative()) {
+            write!(out, "{}", cell.red())?;
+        } else {
+            write!(out, "{}", cell)?;
+        }
+        // Trailing padding only between columns, never at line end.
+        if left && i + 1 != shown {
+            write_spaces(out, pad)?;
+        }
+    }
+    writeln!(out)
+}
+
+/// The disposals as structured rows: one per closed lot, no totals.
+fn table(rows: &[&RealizedLot]) -> Table {
+    let mut table = Table::new(&[
+        "account",
+        "commodity",
+        "quantity",
+        "acquired",
+        "disposed",
+        "proceeds",
+        "cost_basis",
+        "gain",
+        "currency",
+        "term",
+    ]);
+    for r in rows {
+        table.push(vec![
+            Some(r.account.clone()),
+            Some(r.commodity.clone()),
+            Some(exact(&r.qty)),
+            Some(r.acquired.to_string()),
+            Some(r.disposed.to_string()),
+            Some(exact(&r.proceeds)),
+            Some(exact(&r.cost_basis)),
+            Some(exact(&r.gain)),
+            Some(r.currency.clone()),
+            Some(term(r).to_string()),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal(src: &str, target: Option<&str>) -> Journal {
+        let mut journal = crate::testutil::load(src);
+        crate::pipeline::enrich(&mut journal, target, false, None, None).unwrap();
+        journal
+    }
+
+    const SRC: &str = "\
+        account income:cap\n    capital gain\n\
+        account expenses:cap\n    capital loss\n\
+        2023-01-01 * buy\n\
+        \tassets:btc       1 BTC @ 100 EUR\n\
+        \tassets:cash   -100 EUR\n\
+        2024-03-01 * buy\n\
+        \tassets:btc       1 BTC @ 300 EUR\n\
+        \tassets:cash   -300 EUR\n\
+        2024-06-01 * sell\n\
+        \tassets:btc    -1.5 BTC @ 200 EUR\n\
+        \tassets:cash    300 EUR\n\
+        2025-02-01 * sell\n\
+        \tassets:btc    -0.5 BTC @ 400 EUR\n\
+        \tassets:cash    200 EUR\n";
+
+    fn render(j: &Journal, format: Format) -> String {
+        let rows: Vec<&RealizedLot> = j.realized.iter().collect();
+        let mut out = Vec::new();
+        if format == Format::Text {
+            write_table(&mut out, &rows, &j.precisions)
+        } else {
+            table(&rows).write(&mut out, format)
+        }
+        .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn one_row_per_closed_lot_with_term() {
+        let j = journal(SRC, None);
+        let csv = render(&j, Format::Csv);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 4);
+        // The 2024-06-01 sale closes all of lot 1 (long) and half of lot 2.
+        assert_eq!(lines[1], "assets:btc,BTC,1,2023-01-01,2024-06-01,200,100,100,EUR,long");
+        assert_eq!(lines[2], "assets:btc,BTC,0.5,2024-03-01,2024-06-01,100,150,-50,EUR,short");
+        assert_eq!(lines[3], "assets:btc,BTC,0.5,2024-03-01,2025-02-01,200,150,50,EUR,short");
+
+        let rows: Vec<&RealizedLot> = j.realized.iter().collect();
+        let (by_commodity, by_year) = totals(&rows);
+        let btc = &by_commodity[&("BTC".to_string(), "EUR".to_string())];
+        assert_eq!(btc.qty, Some(Decimal::parse("2").unwrap()));
+        assert_eq!(btc.gain, Decimal::parse("100").unwrap());
+        assert_eq!(by_year[&(2024, "EUR".to_string())].gain, Decimal::parse("50").unwrap());
+        assert_eq!(by_year[&(2025, "EUR".to_string())].gain, Decimal::parse("50").unwrap());
+    }
+
+    #[test]
+    fn table_carries_the_totals() {
+        let j = journal(SRC, None);
+        let table = render(&j, Format::Text);
+        assert!(table.starts_with("COMMODITY"));
+        let totals: Vec<&str> = table.lines().skip_while(|l| !l.starts_with("---")).skip(1).collect();
+        assert_eq!(totals.len(), 3);
+        assert!(totals[0].starts_with("BTC") && totals[0].trim_end().ends_with("EUR100"));
+        assert!(totals[1].starts_with("2024"));
+    }
+}
@@ -0,0 +1,87 @@
+//! `is` command — income statement: income and expenses over the report
+//! period, one indented tree per section, and the net income.
+//!
+//! Accounts are picked by their declared `type` (see
+//! [`AccountTypes`](crate::resolver::AccountTypes)). Income is booked as
+//! credits, so its signs are flipped for reading: income and expenses
+//! both show positive, and net income is income less expenses — positive
+//! for a profit.
+//!
+//! Under `-O json|csv|tsv` the statement is one row per section, account
+//! node and commodity (see `balance`); net income is left out.
+
+use crate::commands::account::Account;
+use crate::commands::balance::{calculate_width, print_section, print_total, statement_table, total_width};
+use crate::commands::output::Format;
+use crate::decimal::Decimal;
+use crate::loader::Journal;
+use crate::resolver::AccountType;
+
+pub fn run(journal: &Journal, show_empty: bool, format: Format) {
+    if journal.account_types.is_empty() {
+        eprintln!("is: no account types declared — add `type asset|liability|equity|income|expense` under `account NAME`");
+        return;
+    }
+    let (income, expenses) = build(journal);
+    if format != Format::Text {
+        let sections = [("income", &income), ("expenses", &expenses)];
+        return statement_table(journal, &sections, show_empty).print(format);
+    }
+    let mut net = income.total();
+    for (commodity, value) in expenses.total() {
+        *net.entry(commodity).or_insert_with(Decimal::zero) -= value;
+    }
+    let width = calculate_width(&income, &journal.precisions)
+        .max(calculate_width(&expenses, &journal.precisions))
+        .max(total_width(&net, &journal.precisions));
+
+    print_section(journal, "Income", &income, show_empty, width);
+    print_section(journal, "Expenses", &expenses, show_empty, width);
+    println!("{}", "=".repeat(width));
+    print_total(&net, width, &journal.precisions);
+}
+
+/// The income tree, sign-flipped, and the expense tree.
+fn build(journal: &Journal) -> (Account, Account) {
+    let mut income = Account::root();
+    let mut expenses = Account::root();
+    for lt in &journal.transactions {
+        for lp in &lt.value.postings {
+            let p = &lp.value;
+            let Some(amount) = &p.amount else { continue };
+            let root = match journal.account_types.get(&p.account) {
+                Some(AccountType::Income) => &mut income,
+                Some(AccountType::Expense) => &mut expenses,
+                _ => continue,
+            };
+            root.find_or_create(&p.account)
+                .add_amount(&amount.commodity, amount.value);
+        }
+    }
+    income.negate();
+    (income, expenses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flips_income_to_positive() {
+        let src = "\
+            account income\n    type income\n\
+            account expenses\n    type expense\n\
+            2024-02-01 * salary\n\
+            \tassets:bank  3000 EUR\n\
+            \tincome:salary\n\
+            2024-03-01 * rent\n\
+            \texpenses:rent  800 EUR\n\
+            \tassets:bank\n";
+        let journal = crate::testutil::load(src);
+
+        let (income, expenses) = build(&journal);
+        assert_eq!(income.total()["EUR"], Decimal::from(3000));
+        assert_eq!(expenses.total()["EUR"], Decimal::from(800));
+        assert!(income.find("assets").is_none() && expenses.find("assets").is_none());
+    }
+}
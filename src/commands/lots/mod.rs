@@ -0,0 +1,276 @@
+//! `lots` command — every lot still open as of a date, with its cost
+//! basis and holding period.
+//!
+//! Layout, one row per lot, grouped by account and commodity:
+//!
+//! ```text
+//! ACCOUNT          ACQUIRED    DAYS  QUANTITY  UNIT COST      COST   [VALUE      GAIN]
+//! assets:wallet    2024-01-01   182    BTC0.5  EUR30000  EUR15000   EUR25000  EUR10000
+//! ```
+//!
+//! The lots are the lotter's own state, so they exist only when the
+//! `capital gain` / `capital loss` accounts are declared. The caller cuts
+//! the journal at the as-of date *before* enrichment — lot state is
+//! causal, so that is exactly the position held on that day. Under `-X`
+//! each lot's cost is its market value on the acquisition date, and the
+//! `VALUE` / `GAIN` columns add today's market value and the unrealized
+//! gain; natively the cost is the booked trade cost and those columns
+//! are left out.
+//!
+//! Under `-O json|csv|tsv` it is the same rows, with the commodity and
+//! the cost commodity in columns of their own; `value` and `gain` are
+//! empty natively or when no rate is known.
+
+use std::io::{self, BufWriter, Write};
+
+use colored::Colorize;
+
+use super::output::{exact, Format, Table};
+use super::util::{format_amount, write_spaces};
+use crate::date::Date;
+use crate::decimal::Decimal;
+use crate::filter::PatternMatcher;
+use crate::loader::Journal;
+
+const GAP: usize = 2;
+
+/// `patterns` scope the lots by account; `target` is the resolved `-X`
+/// commodity; `as_of` dates the holding period and the market value.
+pub fn run(journal: &Journal, patterns: &[String], target: Option<&str>, as_of: Date, format: Format) {
+    if journal.capital_gain.is_none() || journal.capital_loss.is_none() {
+        eprintln!("lots: lot tracking needs `capital gain` and `capital loss` accounts declared");
+        return;
+    }
+    let rows = build_rows(journal, patterns, target, as_of);
+    if format != Format::Text {
+        return table(&rows).print(format);
+    }
+    if rows.is_empty() {
+        return;
+    }
+
+    let mut header = vec!["ACCOUNT", "ACQUIRED", "DAYS", "QUANTITY", "UNIT COST", "COST"];
+    if target.is_some() {
+        header.extend(["VALUE", "GAIN"]);
+    }
+    let cells: Vec<Vec<String>> = rows.iter().map(|r| r.cells(journal, target.is_some())).collect();
+    let widths: Vec<usize> = (0..header.len())
+        .map(|i| {
+            cells
+                .iter()
+                .map(|c| c[i].chars().count())
+                .chain(std::iter::once(header[i].len()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    // One locked, buffered writer for the whole report — see `print`.
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let header: Vec<String> = header.iter().map(|h| h.to_string()).collect();
+    let _ = print_line(&mut out, &header, &widths, None);
+    for (row, cells) in rows.iter().zip(&cells) {
+        let _ = print_line(&mut out, cells, &widths, row.gain);
+    }
+    let _ = out.flush();
+}
+
+/// One open lot, valued as of the report date.
+struct Row {
+    account: String,
+    commodity: String,
+    date: Date,
+    days: u32,
+    qty: Decimal,
+    cost_per_unit: Decimal,
+    cost_commodity: String,
+    cost: Decimal,
+    /// Market value and unrealized gain in the `-X` target; `None`
+    /// natively, or when the price DB has no rate for the commodity.
+    value: Option<Decimal>,
+    gain: Option<Decimal>,
+}
+
+impl Row {
+    fn cells(&self, journal: &Journal, valued: bool) -> Vec<String> {
+        let p = &journal.precisions;
+        let mut cells = vec![
+            self.account.clone(),
+            self.date.to_string(),
+            self.days.to_string(),
+            format_amount(&self.commodity, &self.qty, p),
+            format_amount(&self.cost_commodity, &self.cost_per_unit, p),
+            format_amount(&self.cost_commodity, &self.cost, p),
+        ];
+        if valued {
+            let show = |v: Option<Decimal>| {
+                v.map(|v| format_amount(&self.cost_commodity, &v, p)).unwrap_or_else(|| "-".to_string())
+            };
+            cells.push(show(self.value));
+            cells.push(show(self.gain));
+        }
+        cells
+    }
+}
+
+/// The lots as structured rows: one per lot.
+fn table(rows: &[Row]) -> Table {
+    let mut table = Table::new(&[
+        "account",
+        "commodity",
+        "acquired",
+        "days",
+        "quantity",
+        "unit_cost",
+        "cost",
+        "cost_commodity",
+        "value",
+        "gain",
+    ]);
+    for r in rows {
+        table.push(vec![
+            Some(r.account.clone()),
+            Some(r.commodity.clone()),
+            Some(r.date.to_string()),
+            Some(r.days.to_string()),
+            Some(exact(&r.qty)),
+            Some(exact(&r.cost_per_unit)),
+            Some(exact(&r.cost)),
+            Some(r.cost_commodity.clone()),
+            r.value.as_ref().map(exact),
+            r.gain.as_ref().map(exact),
+        ]);
+    }
+    table
+}
+
+fn build_rows(journal: &Journal, patterns: &[String], target: Option<&str>, as_of: Date) -> Vec<Row> {
+    let matcher = PatternMatcher::from_parts(patterns);
+    let as_of_str = as_of.to_string();
+    journal
+        .lots
+        .iter()
+        .filter(|lot| matcher.matches(&lot.account))
+        .map(|lot| {
+            let cost = lot.qty.mul_rounded(lot.cost_per_unit);
+            let value = target
+                .filter(|t| *t == lot.cost_commodity)
+                .and_then(|t| journal.prices.find(&lot.commodity, t, &as_of_str))
+                .map(|rate| lot.qty.mul_rounded(rate));
+            Row {
+                account: lot.account.clone(),
+                commodity: lot.commodity.clone(),
+                date: lot.date,
+                days: as_of.days().saturating_sub(lot.date.days()),
+                qty: lot.qty,
+                cost_per_unit: lot.cost_per_unit,
+                cost_commodity: lot.cost_commodity.clone(),
+                cost,
+                value,
+                gain: value.map(|v| v - cost),
+            }
+        })
+        .collect()
+}
+
+/// Write one row: the account left-aligned, every other column
+/// right-aligned. A negative `gain` paints the last column red.
+fn print_line<W: Write>(
+    out: &mut W,
+    cells: &[String],
+    widths: &[usize],
+    gain: Option<Decimal>,
+) -> io::Result<()> {
+    for (i, (cell, width)) in cells.iter().zip(widths).enumerate() {
+        let pad = width.saturating_sub(cell.chars().count());
+        if i == 0 {
+            write!(out, "{}", cell)?;
+            write_spaces(out, pad)?;
+            continue;
+        }
+        write_spaces(out, GAP + pad)?;
+        if i == cells.len() - 1 && gain.is_some_and(|g| g.is_negative()) {
+            write!(out, "{}", cell.red())?;
+        } else {
+            write!(out, "{}", cell)?;
+        }
+    }
+    writeln!(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal(src: &str, target: Option<&str>) -> Journal {
+        let mut journal = crate::testutil::load(src);
+        crate::pipeline::enrich(&mut journal, target, false, None, None).unwrap();
+        journal
+    }
+
+    const SRC: &str = "\
+        account income:cap\n    capital gain\n\
+        account expenses:cap\n    capital loss\n\
+        P 2024-01-01 BTC EUR 30000\n\
+        P 2024-03-01 BTC EUR 40000\n\
+        P 2024-07-01 BTC EUR 50000\n\
+        2024-01-01 * buy\n\
+        \tassets:btc       1 BTC\n\
+        \tassets:cash  -30000 EUR\n\
+        2024-03-01 * buy\n\
+        \tassets:btc       1 BTC\n\
+        \tassets:cash  -40000 EUR\n\
+        2024-06-01 * sell\n\
+        \tassets:btc    -1.5 BTC\n\
+        \tassets:cash   60000 EUR\n";
+
+    #[test]
+    fn lists_the_remaining_lot_with_market_value() {
+        let j = journal(SRC, Some("EUR"));
+        let as_of = Date::parse("2024-07-01").unwrap();
+        let rows = build_rows(&j, &[], Some("EUR"), as_of);
+        // FIFO: the first lot is gone, half of the second remains.
+        assert_eq!(rows.len(), 1);
+        let r = &rows[0];
+        assert_eq!(r.date.to_string(), "2024-03-01");
+        assert_eq!(r.days, 122);
+        assert_eq!(r.qty, Decimal::parse("0.5").unwrap());
+        assert_eq!(r.cost, Decimal::parse("20000").unwrap());
+        assert_eq!(r.value, Some(Decimal::parse("25000").unwrap()));
+        assert_eq!(r.gain, Some(Decimal::parse("5000").unwrap()));
+        assert!(build_rows(&j, &["cash".to_string()], Some("EUR"), as_of).is_empty());
+
+        let mut out = Vec::new();
+        table(&rows).write(&mut out, Format::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json[0]["days"], 122);
+        assert_eq!(json[0]["cost"], "20000");
+        assert_eq!(json[0]["gain"], "5000");
+    }
+
+    #[test]
+    fn lists_only_the_bought_positions() {
+        // The USD and EUR the sales bring in are what the trades were
+        // paid in, not positions of their own.
+        let src = "\
+            account income:cap\n    capital gain\n\
+            account expenses:cap\n    capital loss\n\
+            2024-01-01 * buy\n\
+            \tassets:broker   10 AAPL @ 100 USD\n\
+            \tassets:cash  -1000 USD\n\
+            2024-03-01 * sell\n\
+            \tassets:broker   -5 AAPL @ 120 USD\n\
+            \tassets:cash    600 USD\n\
+            2024-04-01 * buy\n\
+            \tassets:btc    0.2 BTC\n\
+            \tassets:bank  -10000 EUR\n\
+            2024-05-01 * sell\n\
+            \tassets:btc   -0.1 BTC\n\
+            \tassets:bank   6000 EUR\n";
+        let j = journal(src, None);
+        let rows = build_rows(&j, &[], None, Date::parse("2024-07-01").unwrap());
+        let held: Vec<(&str, &str)> = rows.iter().map(|r| (r.account.as_str(), r.commodity.as_str())).collect();
+        assert_eq!(held, vec![("assets:broker", "AAPL"), ("assets:btc", "BTC")]);
+    }
+}
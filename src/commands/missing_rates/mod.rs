@@ -0,0 +1,157 @@
+//! `missing-rates` command — the rates `-X` lacked, grouped so they can
+//! be fetched: one row per commodity, with the span of days it needed a
+//! rate to the target on.
+//!
+//! ```text
+//! GBP → €   2024-01-03 .. 2024-06-02    4 days     9 postings
+//! XMR → €   2024-06-15                  1 day      1 posting
+//! ```
+//!
+//! The days are the postings' own conversion dates — their transaction's
+//! or, under `--aux-date`, their effective date — and the commodity is
+//! the side of the weight ([`weight`](crate::rebalancer::weight)) that
+//! has no path to the target. The same postings are what
+//! `--strict-rates` fails on ([`check`]), one `file:line` each.
+//!
+//! Under `-O json|csv|tsv` each commodity is one row: commodity, target,
+//! first and last day, and the number of days and postings.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use colored::Colorize;
+
+use crate::commands::output::{Format, Table};
+use crate::commands::util::shorten_home;
+use crate::date::Date;
+use crate::error::Error;
+use crate::loader::Journal;
+use crate::rebalancer::{unconverted, Unconverted};
+
+/// The days a commodity needed a rate on, and how many postings did.
+struct Gap {
+    days: BTreeSet<Date>,
+    postings: usize,
+}
+
+/// List the missing `commodity → target` rates. Expects native amounts:
+/// the caller skips the rebalance.
+pub fn run(journal: &Journal, target: &str, aux: bool, format: Format) {
+    let gaps = group(&unconverted(&journal.transactions, target, &journal.prices, aux));
+    if format != Format::Text {
+        return table(&gaps, target).print(format);
+    }
+    if gaps.is_empty() {
+        println!("{} every posting converts to {}", "✓".green(), target);
+        return;
+    }
+    let pair = |c: &str| format!("{} → {}", c, target);
+    let pair_width = gaps.keys().map(|c| pair(c).chars().count()).max().unwrap_or(0);
+    for (commodity, gap) in &gaps {
+        let pair = pair(commodity);
+        let first = gap.days.first().expect("a gap has a day");
+        let last = gap.days.last().expect("a gap has a day");
+        let span = if first == last { first.to_string() } else { format!("{} .. {}", first, last) };
+        println!(
+            "{}{}   {:<24}  {:>3} {}  {:>4} {}",
+            pair.bold(),
+            " ".repeat(pair_width - pair.chars().count()),
+            span,
+            gap.days.len(),
+            if gap.days.len() == 1 { "day " } else { "days" },
+            gap.postings,
+            if gap.postings == 1 { "posting" } else { "postings" },
+        );
+    }
+}
+
+/// `--strict-rates`: fail listing every posting that would stay out of
+/// `target`, one `file:line` each. Expects native amounts, so it runs
+/// before the rebalance.
+pub fn check(journal: &Journal, target: &str, aux: bool) -> Result<(), Error> {
+    let missing = unconverted(&journal.transactions, target, &journal.prices, aux);
+    if missing.is_empty() {
+        return Ok(());
+    }
+    let mut msg = format!(
+        "{} posting(s) have no rate to {} (--strict-rates):",
+        missing.len().to_string().red().bold(),
+        target
+    );
+    for m in &missing {
+        msg.push_str(&format!(
+            "\n  {}{}  {} on {}",
+            format!("{}:", shorten_home(m.file)).bright_blue(),
+            m.line.to_string().blue(),
+            m.commodity,
+            m.date
+        ));
+    }
+    Err(Error::from(msg))
+}
+
+/// The unconverted postings per commodity.
+fn group(missing: &[Unconverted]) -> BTreeMap<String, Gap> {
+    let mut gaps: BTreeMap<String, Gap> = BTreeMap::new();
+    for m in missing {
+        let gap = gaps
+            .entry(m.commodity.to_string())
+            .or_insert_with(|| Gap { days: BTreeSet::new(), postings: 0 });
+        gap.days.insert(m.date);
+        gap.postings += 1;
+    }
+    gaps
+}
+
+fn table(gaps: &BTreeMap<String, Gap>, target: &str) -> Table {
+    let mut table = Table::new(&["commodity", "target", "first", "last", "days", "postings"]);
+    for (commodity, gap) in gaps {
+        table.push(vec![
+            Some(commodity.clone()),
+            Some(target.to_string()),
+            gap.days.first().map(Date::to_string),
+            gap.days.last().map(Date::to_string),
+            Some(gap.days.len().to_string()),
+            Some(gap.postings.to_string()),
+        ]);
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal(src: &str) -> Journal {
+        let mut journal = crate::testutil::load(src);
+        crate::pipeline::enrich(&mut journal, None, false, None, None).unwrap();
+        journal
+    }
+
+    #[test]
+    fn groups_missing_days_per_commodity() {
+        let j = journal(
+            "P 2024-01-01 USD EUR 0.90\n\
+             2024-01-03 * a\n\
+             \tassets:gbp  10 GBP\n\
+             \tassets:usd  -12 USD\n\
+             2024-02-10 * b\n\
+             \tassets:gbp  5 GBP\n\
+             \tassets:chf  -6 CHF\n",
+        );
+        let missing = unconverted(&j.transactions, "EUR", &j.prices, false);
+        let gaps = group(&missing);
+        let rows: Vec<(&str, Vec<String>, usize)> = gaps
+            .iter()
+            .map(|(c, g)| (c.as_str(), g.days.iter().map(Date::to_string).collect(), g.postings))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("CHF", vec!["2024-02-10".to_string()], 1),
+                ("GBP", vec!["2024-01-03".to_string(), "2024-02-10".to_string()], 2),
+            ]
+        );
+        let err = check(&j, "EUR", false).unwrap_err().to_string();
+        assert!(err.contains("2024-02-10"));
+    }
+}
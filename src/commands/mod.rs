@@ -4,14 +4,27 @@
 pub mod account;
 pub mod accounts;
 pub mod balance;
+pub mod bs;
+pub mod budget;
+pub mod close;
+pub mod cashflow;
 pub mod codes;
 pub mod commodities;
 pub mod diff;
+pub mod forecast;
 pub mod format;
+pub mod gains;
 pub mod import;
+pub mod is;
 pub mod lint;
+pub mod lots;
+pub mod missing_rates;
 pub mod navigate;
+pub mod networth;
+pub mod output;
 pub mod print;
+pub mod rate;
+pub mod reconcile;
 pub mod register;
 pub mod rename;
 pub mod sweep;
@@ -0,0 +1,329 @@
+//! `networth` command — the cumulative balance of a set of accounts,
+//! sampled at the end of every day, week, month, quarter or year.
+//!
+//! ```text
+//!                    EUR        USD
+//! 2024-01-31   EUR1,000.00    USD50.00
+//! 2024-02-29   EUR3,200.00    USD50.00
+//! 2024-03-31   EUR2,450.00    USD80.00
+//! ```
+//!
+//! Each row is the balance at the close of the period's last day: every
+//! selected posting up to then, earlier ones included, so `-b` only moves
+//! the first sample. The selection is the positional pattern when one is
+//! given, else every account declared `type asset` or `type liability`.
+//! `--chart` adds a sparkline per commodity under the table.
+//!
+//! Under `-X` the rebalancer has converted each posting at its own date,
+//! so a sample is the historical value of what was booked. `--market`
+//! instead marks the native balances to market at every sample date —
+//! the caller skips the rebalance, and each commodity is converted at the
+//! rate in force on that date ([`Index::find`]), as the revaluator does
+//! for the latest one. A commodity with no rate path stays in its own
+//! column.
+
+use std::collections::BTreeMap;
+use std::io::{self, BufWriter, Write};
+
+use colored::Colorize;
+
+use crate::commands::balance::{periods, Columns};
+use crate::commands::output::{exact, Format, Table};
+use crate::commands::util::{format_amount, shows_nonzero, write_spaces};
+use crate::date::Date;
+use crate::decimal::Decimal;
+use crate::indexer::Index;
+use crate::loader::Journal;
+use crate::resolver::AccountType;
+
+const GAP: usize = 3;
+
+/// Sparkline levels, lowest to highest.
+const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The balance at the close of `date`, per commodity.
+struct Sample {
+    date: Date,
+    balance: BTreeMap<String, Decimal>,
+}
+
+/// Print the series. `typed` selects the asset and liability accounts
+/// (no pattern given); `market` is the `-X` target under `--market`.
+pub fn run(
+    journal: &Journal,
+    columns: &Columns,
+    typed: bool,
+    market: Option<&str>,
+    chart: bool,
+    format: Format,
+) {
+    if typed && journal.account_types.is_empty() {
+        eprintln!("networth: no account types declared — add `type asset|liability` under `account NAME`, or name the accounts");
+        return;
+    }
+    let samples = sample(journal, columns, typed, market);
+    if format != Format::Text {
+        return table(&samples).print(format);
+    }
+    if samples.is_empty() {
+        eprintln!("networth: nothing matched — no posting in range");
+        return;
+    }
+    let mut commodities: Vec<&str> = samples
+        .iter()
+        .flat_map(|s| s.balance.iter())
+        .filter(|(c, v)| shows_nonzero(c, v, &journal.precisions))
+        .map(|(c, _)| c.as_str())
+        .collect::<std::collections::BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    // Every sample nets to zero: still one row each, a single `0` column
+    // under no heading.
+    let all_zero = commodities.is_empty();
+    if all_zero {
+        commodities.push("");
+    }
+
+    let p = &journal.precisions;
+    let cells: Vec<Vec<(String, bool)>> = samples
+        .iter()
+        .map(|s| {
+            commodities
+                .iter()
+                .map(|c| {
+                    let v = s.balance.get(*c).copied().unwrap_or_else(Decimal::zero);
+                    if shows_nonzero(c, &v, p) {
+                        (format_amount(c, &v, p), v.is_negative())
+                    } else {
+                        ("0".to_string(), false)
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let widths: Vec<usize> = commodities
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            cells
+                .iter()
+                .map(|row| row[i].0.chars().count())
+                .chain(std::iter::once(c.chars().count()))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let date_width = 10;
+
+    // One locked, buffered writer for the whole report.
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    if !all_zero {
+        let _ = write_spaces(&mut out, date_width);
+        for (c, width) in commodities.iter().zip(&widths) {
+            let _ = write_spaces(&mut out, GAP + width - c.chars().count());
+            let _ = write!(out, "{}", c.bold());
+        }
+        let _ = writeln!(out);
+    }
+    for (s, row) in samples.iter().zip(&cells) {
+        let _ = write!(out, "{}", s.date);
+        for ((cell, negative), width) in row.iter().zip(&widths) {
+            let _ = write_spaces(&mut out, GAP + width - cell.chars().count());
+            if *negative {
+                let _ = write!(out, "{}", cell.red());
+            } else {
+                let _ = write!(out, "{}", cell);
+            }
+        }
+        let _ = writeln!(out);
+    }
+    if chart {
+        let label_width = commodities.iter().map(|c| c.chars().count()).max().unwrap_or(0);
+        let _ = writeln!(out);
+        for c in &commodities {
+            let series: Vec<Decimal> = samples
+                .iter()
+                .map(|s| s.balance.get(*c).copied().unwrap_or_else(Decimal::zero))
+                .collect();
+            let _ = write!(out, "{}", c.blue());
+            let _ = write_spaces(&mut out, label_width - c.chars().count() + GAP);
+            let _ = writeln!(out, "{}", sparkline(&series));
+        }
+    }
+    let _ = out.flush();
+}
+
+/// One [`Sample`] per period in `columns`.
+fn sample(journal: &Journal, columns: &Columns, typed: bool, market: Option<&str>) -> Vec<Sample> {
+    let selected = |account: &str| {
+        !typed
+            || matches!(
+                journal.account_types.get(account),
+                Some(AccountType::Asset | AccountType::Liability)
+            )
+    };
+    let mut postings: Vec<(Date, &str, Decimal)> = journal
+        .transactions
+        .iter()
+        .flat_map(|lt| {
+            lt.value.postings.iter().filter_map(|lp| {
+                let p = &lp.value;
+                let amount = p.amount.as_ref()?;
+                selected(&p.account).then(|| {
+                    let date = lt.value.posting_date(p, columns.aux_date);
+                    (date, amount.commodity.as_str(), amount.value)
+                })
+            })
+        })
+        .collect();
+    postings.sort_by_key(|(date, _, _)| *date);
+
+    let mut native: BTreeMap<String, Decimal> = BTreeMap::new();
+    let mut next = postings.iter().peekable();
+    let mut samples = Vec::new();
+    for (_, end) in periods(journal, columns) {
+        while let Some((_, commodity, value)) = next.next_if(|(date, _, _)| *date < end) {
+            *native.entry(commodity.to_string()).or_insert_with(Decimal::zero) += *value;
+        }
+        let date = Date::from_days(end.days() - 1);
+        let balance = match market {
+            Some(target) => mark(&native, target, &journal.prices, date),
+            None => native.clone(),
+        };
+        samples.push(Sample { date, balance });
+    }
+    samples
+}
+
+/// `native` converted to `target` at the rates in force on `date`; a
+/// commodity with no rate path is kept as it is.
+fn mark(
+    native: &BTreeMap<String, Decimal>,
+    target: &str,
+    db: &Index,
+    date: Date,
+) -> BTreeMap<String, Decimal> {
+    let date = date.to_string();
+    let mut marked: BTreeMap<String, Decimal> = BTreeMap::new();
+    for (commodity, value) in native {
+        let (commodity, value) = match db.find(commodity, target, &date) {
+            Some(rate) => (target, value.mul_rounded(rate)),
+            None => (commodity.as_str(), *value),
+        };
+        *marked.entry(commodity.to_string()).or_insert_with(Decimal::zero) += value;
+    }
+    marked
+}
+
+/// One level per value, scaled between the series' lowest and highest.
+fn sparkline(series: &[Decimal]) -> String {
+    let values: Vec<f64> = series.iter().map(Decimal::to_f64).collect();
+    let lo = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let hi = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    values
+        .iter()
+        .map(|v| {
+            if hi <= lo {
+                return LEVELS[LEVELS.len() / 2];
+            }
+            let level = ((v - lo) / (hi - lo) * (LEVELS.len() - 1) as f64).round() as usize;
+            LEVELS[level.min(LEVELS.len() - 1)]
+        })
+        .collect()
+}
+
+/// The series as structured rows: one per sample date and commodity.
+fn table(samples: &[Sample]) -> Table {
+    let mut table = Table::new(&["date", "commodity", "amount"]);
+    for s in samples {
+        for (commodity, value) in &s.balance {
+            table.push(vec![
+                Some(s.date.to_string()),
+                Some(commodity.clone()),
+                Some(exact(value)),
+            ]);
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::balance::Interval;
+
+    fn journal(src: &str) -> Journal {
+        let mut journal = crate::testutil::load(src);
+        crate::pipeline::enrich(&mut journal, None, false, None, None).unwrap();
+        journal
+    }
+
+    const SRC: &str = "\
+        account assets\n    type asset\n\
+        account liabilities\n    type liability\n\
+        P 2024-01-01 USD EUR 0.90\n\
+        P 2024-02-15 USD EUR 0.95\n\
+        2024-01-10 * opening\n\
+        \tassets:bank  1000 EUR\n\
+        \tassets:wallet  100 USD\n\
+        \tequity:opening\n\
+        2024-02-05 * rent\n\
+        \texpenses:rent  300 EUR\n\
+        \tliabilities:card\n\
+        2024-03-20 * salary\n\
+        \tassets:bank  2000 EUR\n\
+        \tincome:salary\n";
+
+    fn columns(interval: Interval) -> Columns {
+        Columns { interval, begin: None, end: None, row_total: false, average: false, aux_date: false }
+    }
+
+    fn balances(samples: &[Sample], commodity: &str) -> Vec<(String, String)> {
+        samples
+            .iter()
+            .map(|s| {
+                let v = s.balance.get(commodity).copied().unwrap_or_else(Decimal::zero);
+                (s.date.to_string(), exact(&v))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn samples_typed_accounts_at_each_period_end() {
+        let j = journal(SRC);
+        let samples = sample(&j, &columns(Interval::Monthly), true, None);
+        assert_eq!(
+            balances(&samples, "EUR"),
+            [
+                ("2024-01-31".to_string(), "1000".to_string()),
+                ("2024-02-29".to_string(), "700".to_string()),
+                ("2024-03-31".to_string(), "2700".to_string()),
+            ]
+        );
+        assert_eq!(balances(&samples, "USD")[2].1, "100");
+    }
+
+    #[test]
+    fn market_values_each_sample_at_its_own_rate() {
+        let j = journal(SRC);
+        let samples = sample(&j, &columns(Interval::Monthly), true, Some("EUR"));
+        let eur: Vec<String> = balances(&samples, "EUR").into_iter().map(|(_, v)| v).collect();
+        assert_eq!(eur, ["1090", "795", "2795"]);
+        assert!(samples.iter().all(|s| !s.balance.contains_key("USD")));
+    }
+
+    #[test]
+    fn weekly_periods_start_on_monday() {
+        let monday = Interval::Weekly.start(Date::parse("2024-01-10").unwrap());
+        assert_eq!(monday.to_string(), "2024-01-08");
+        assert_eq!(Interval::Weekly.next(monday).to_string(), "2024-01-15");
+    }
+
+    #[test]
+    fn sparkline_spans_lowest_to_highest() {
+        let series: Vec<Decimal> = [0, 50, 100].into_iter().map(Decimal::from).collect();
+        assert_eq!(sparkline(&series), "▁▅█");
+        assert_eq!(sparkline(&[Decimal::from(5), Decimal::from(5)]), "▅▅");
+    }
+}
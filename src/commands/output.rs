@@ -0,0 +1,194 @@
+//! Structured report output — `-O/--output-format json|csv|tsv`.
+//!
+//! A report that supports it builds a [`Table`]: fixed column names and
+//! rows of plain string cells — never coloured, padded or truncated, so
+//! the shape stays the same however wide the terminal or the values are.
+//! Amounts are exact decimals ([`exact`]) with the commodity in a column
+//! of its own; a cell with nothing to show is empty (`null` in JSON).
+//!
+//! - **json**: an array of objects, one per row, keyed by column name.
+//!   Amounts stay strings, to keep every digit; counts — a source `line`,
+//!   `days`, `postings` — are numbers.
+//! - **csv**: a header line, then one record per row; a field holding a
+//!   comma, quote or line break is quoted.
+//! - **tsv**: the same with tabs; a tab or line break inside a field
+//!   becomes a space, since TSV has no quoting.
+
+use std::io::{self, BufWriter, Write};
+
+use crate::decimal::Decimal;
+use crate::parser::transaction::State;
+
+/// How a report is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// The report's own aligned, coloured layout.
+    Text,
+    Json,
+    Csv,
+    Tsv,
+}
+
+/// Columns written as JSON numbers: whole counts, never amounts.
+const COUNTS: &[&str] = &["line", "days", "postings"];
+
+/// Rows of string cells under fixed column names.
+pub struct Table {
+    columns: &'static [&'static str],
+    rows: Vec<Vec<Option<String>>>,
+}
+
+impl Table {
+    pub fn new(columns: &'static [&'static str]) -> Self {
+        Self {
+            columns,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Append a row; one cell per column, in column order.
+    pub fn push(&mut self, row: Vec<Option<String>>) {
+        debug_assert_eq!(row.len(), self.columns.len());
+        self.rows.push(row);
+    }
+
+    /// Write the table to stdout in `format` (anything but
+    /// [`Format::Text`]).
+    pub fn print(&self, format: Format) {
+        // One locked, buffered writer for the whole report — see `print`.
+        let stdout = io::stdout();
+        let mut out = BufWriter::new(stdout.lock());
+        let _ = self.write(&mut out, format);
+        let _ = out.flush();
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W, format: Format) -> io::Result<()> {
+        match format {
+            Format::Json => self.write_json(out),
+            Format::Csv => self.write_delimited(out, ',', csv_field),
+            Format::Tsv => self.write_delimited(out, '\t', tsv_field),
+            Format::Text => Ok(()),
+        }
+    }
+
+    fn write_json<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        use serde_json::{Map, Value};
+        let rows: Vec<Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let object: Map<String, Value> = self
+                    .columns
+                    .iter()
+                    .zip(row)
+                    .map(|(column, cell)| {
+                        let value = match cell {
+                            None => Value::Null,
+                            Some(c) if COUNTS.contains(column) => {
+                                c.parse::<u64>().map_or_else(|_| Value::String(c.clone()), Value::from)
+                            }
+                            Some(c) => Value::String(c.clone()),
+                        };
+                        (column.to_string(), value)
+                    })
+                    .collect();
+                Value::Object(object)
+            })
+            .collect();
+        serde_json::to_writer_pretty(&mut *out, &rows)?;
+        writeln!(out)
+    }
+
+    fn write_delimited<W: Write>(
+        &self,
+        out: &mut W,
+        delimiter: char,
+        field: fn(&str) -> String,
+    ) -> io::Result<()> {
+        let sep = delimiter.to_string();
+        writeln!(out, "{}", self.columns.join(&sep))?;
+        for row in &self.rows {
+            let fields: Vec<String> = row
+                .iter()
+                .map(|cell| cell.as_deref().map(field).unwrap_or_default())
+                .collect();
+            writeln!(out, "{}", fields.join(&sep))?;
+        }
+        Ok(())
+    }
+}
+
+/// A decimal at full precision, without trailing zeros: `12.5`, `-3`,
+/// `0.00012345`.
+pub fn exact(value: &Decimal) -> String {
+    let s = value.to_string();
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        s
+    }
+}
+
+/// A clear state by the name the `status` query keyword takes.
+pub fn state_name(state: State) -> &'static str {
+    match state {
+        State::Cleared => "cleared",
+        State::Pending => "pending",
+        State::Uncleared => "uncleared",
+    }
+}
+
+/// Quote a CSV field when it holds a delimiter, quote or line break.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// A TSV field: tabs and line breaks become spaces.
+fn tsv_field(s: &str) -> String {
+    s.replace(['\t', '\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(table: &Table, format: Format) -> String {
+        let mut out = Vec::new();
+        table.write(&mut out, format).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn writes_each_format() {
+        let mut table = Table::new(&["account", "amount", "note", "line"]);
+        table.push(vec![
+            Some("assets:cash".to_string()),
+            Some(exact(&Decimal::parse("-12.50").unwrap())),
+            None,
+            Some("7".to_string()),
+        ]);
+        table.push(vec![
+            Some("expenses:food".to_string()),
+            Some(exact(&Decimal::from(3))),
+            Some("a, \"b\"\tc".to_string()),
+            Some("12".to_string()),
+        ]);
+        assert_eq!(
+            render(&table, Format::Csv),
+            "account,amount,note,line\nassets:cash,-12.5,,7\nexpenses:food,3,\"a, \"\"b\"\"\tc\",12\n"
+        );
+        assert_eq!(
+            render(&table, Format::Tsv),
+            "account\tamount\tnote\tline\nassets:cash\t-12.5\t\t7\nexpenses:food\t3\ta, \"b\" c\t12\n"
+        );
+        let json: serde_json::Value = serde_json::from_str(&render(&table, Format::Json)).unwrap();
+        assert_eq!(json[0]["amount"], "-12.5");
+        assert!(json[0]["note"].is_null());
+        assert_eq!(json[0]["line"], 7);
+        assert_eq!(json[1]["account"], "expenses:food");
+    }
+}
@@ -15,20 +15,28 @@
 //! global max-widths: the account column is left-aligned within
 //! `account_max`, the amount column right-aligned within `amount_max`.
 //!
+//! Under `-O json|csv|tsv` each posting is one row carrying its
+//! transaction's date, state, code and description, the amount and any
+//! `@` / `@@` cost, and the posting's source `file` / `line`.
+//!
 //! Runs after the filter phase — the journal is already scoped.
 
 use std::io::{self, BufWriter, Write};
 
 use colored::Colorize;
 
-use super::util::{format_amount, render_account, write_spaces};
+use super::output::{exact, state_name, Format, Table};
+use super::util::{format_amount, render_marked_account, write_spaces};
 use crate::loader::Journal;
 use crate::parser::posting::{Costs, Posting};
 use crate::parser::transaction::{State, Transaction};
 
 const GAP: usize = 4;
 
-pub fn run(journal: &Journal) {
+pub fn run(journal: &Journal, format: Format) {
+    if format != Format::Text {
+        return table(journal).print(format);
+    }
     let account_max = max_account_width(journal);
     let amount_max = max_amount_width(journal);
 
@@ -52,6 +60,59 @@ pub fn run(journal: &Journal) {
     let _ = out.flush();
 }
 
+fn table(journal: &Journal) -> Table {
+    let mut table = Table::new(&[
+        "date",
+        "aux_date",
+        "state",
+        "code",
+        "description",
+        "account",
+        "commodity",
+        "amount",
+        "cost_type",
+        "cost_commodity",
+        "cost",
+        "lot_cost_type",
+        "lot_cost_commodity",
+        "lot_cost",
+        "lot_date",
+        "file",
+        "line",
+    ]);
+    for lt in &journal.transactions {
+        let tx = &lt.value;
+        for lp in &tx.postings {
+            let p = &lp.value;
+            let (cost_type, cost) = match &p.costs {
+                Some(Costs::PerUnit(a)) => (Some("@"), Some(a)),
+                Some(Costs::Total(a)) => (Some("@@"), Some(a)),
+                None => (None, None),
+            };
+            table.push(vec![
+                Some(tx.date.to_string()),
+                tx.aux_date.map(|d| d.to_string()),
+                Some(state_name(tx.posting_state(p)).to_string()),
+                tx.code.clone(),
+                Some(tx.description.clone()),
+                Some(p.account.clone()),
+                p.amount.as_ref().map(|a| a.commodity.clone()),
+                p.amount.as_ref().map(|a| exact(&a.value)),
+                cost_type.map(String::from),
+                cost.map(|a| a.commodity.clone()),
+                cost.map(|a| exact(&a.value)),
+                p.lot_cost.as_ref().map(|l| if l.total { "{{}}" } else { "{}" }.to_string()),
+                p.lot_cost.as_ref().map(|l| l.amount.commodity.clone()),
+                p.lot_cost.as_ref().map(|l| exact(&l.amount.value)),
+                p.lot_date.map(|d| d.to_string()),
+                Some(lp.file.to_string()),
+                Some(lp.line.to_string()),
+            ]);
+        }
+    }
+    table
+}
+
 fn print_header<W: Write>(out: &mut W, tx: &Transaction) -> io::Result<()> {
     // State marker mirrors ledger: cleared `* `, pending `! `, and no
     // marker at all when the state is absent — just `date description`,
@@ -66,7 +127,8 @@ fn print_header<W: Write>(out: &mut W, tx: &Transaction) -> io::Result<()> {
         .as_deref()
         .map(|c| format!("({}) ", c).yellow().to_string())
         .unwrap_or_default();
-    writeln!(out, "{}{}{}{}", tx.date, marker, code, tx.description.bold())?;
+    let aux = tx.aux_date.map(|d| format!("={}", d)).unwrap_or_default();
+    writeln!(out, "{}{}{}{}{}", tx.date, aux, marker, code, tx.description.bold())?;
     for comment in &tx.comments {
         write_spaces(out, GAP)?;
         writeln!(out, "{}", format!("; {}", comment.value.text).dimmed())?;
@@ -81,7 +143,7 @@ fn print_posting<W: Write>(
     amount_max: usize,
     precisions: &std::collections::HashMap<String, usize>,
 ) -> io::Result<()> {
-    let display = render_account(p);
+    let display = render_marked_account(p);
     let display_width = display.chars().count();
 
     write_spaces(out, GAP)?;
@@ -144,7 +206,7 @@ fn max_account_width(journal: &Journal) -> usize {
         .transactions
         .iter()
         .flat_map(|tx| tx.value.postings.iter())
-        .map(|lp| render_account(&lp.value).chars().count())
+        .map(|lp| render_marked_account(&lp.value).chars().count())
         .max()
         .unwrap_or(0)
 }
@@ -164,3 +226,27 @@ fn max_amount_width(journal: &Journal) -> usize {
         .max()
         .unwrap_or(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_carry_the_lot_annotation() {
+        let journal = crate::testutil::load(
+            "2024-06-01 * sell\n\
+             \tassets:btc   -1 BTC {100 EUR} [2024-01-01] @ 150 EUR\n\
+             \tassets:cash  150 EUR\n\
+             \tincome:gain  -50 EUR\n",
+        );
+        let mut out = Vec::new();
+        table(&journal).write(&mut out, Format::Json).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json[0]["lot_cost_type"], "{}");
+        assert_eq!(json[0]["lot_cost_commodity"], "EUR");
+        assert_eq!(json[0]["lot_cost"], "100");
+        assert_eq!(json[0]["lot_date"], "2024-01-01");
+        assert_eq!(json[0]["cost"], "150");
+        assert!(json[1]["lot_cost"].is_null() && json[1]["lot_date"].is_null());
+    }
+}
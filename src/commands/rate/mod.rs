@@ -0,0 +1,40 @@
+//! `rate` command — the exchange rate between two commodities on a day,
+//! and the path `-X` takes to it.
+//!
+//! ```text
+//! 2024-06-15  1 XMR = 120 €
+//!   XMR → $ 150 (P 2024-06-14), $ → € 1/1.25 (P 2024-06-10) = 120
+//! ```
+//!
+//! The second line is the note `--explain-rates` attaches to a converted
+//! posting ([`Index::explain`](crate::indexer::Index::explain)): each
+//! hop with the rate it applied and the date of the `P` entry behind it,
+//! an inverse hop as `1/QUOTED`. Symbols go through the commodity
+//! aliases, as `-X` does. A rate a commodity's `max-age` refuses is
+//! routed around and warned about, as in a report.
+
+use colored::Colorize;
+
+use crate::date::Date;
+use crate::error::Error;
+use crate::indexer::index::plain;
+use crate::loader::Journal;
+
+pub fn run(journal: &Journal, from: &str, to: &str, date: Date) -> Result<(), Error> {
+    let canonical = |s: &str| journal.aliases.get(s).cloned().unwrap_or_else(|| s.to_string());
+    let (from, to) = (canonical(from), canonical(to));
+    let day = date.to_string();
+    // `find` records any rate a `max-age` refused; `explain` walks its path.
+    let explanation = journal.prices.find(&from, &to, &day).and_then(|_| journal.prices.explain(&from, &to, &day));
+    for stale in journal.prices.stale() {
+        eprintln!("warning: {}", stale);
+    }
+    let Some(explanation) = explanation else {
+        return Err(Error::from(format!("no rate path from {} to {} on {}", from, to, date)));
+    };
+    println!("{}  1 {} = {} {}", date, from, plain(&explanation.rate).bold(), to);
+    if !explanation.hops.is_empty() {
+        println!("  {}", explanation.to_string().dimmed());
+    }
+    Ok(())
+}
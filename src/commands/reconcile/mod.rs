@@ -0,0 +1,683 @@
+//! `reconcile` command — interactive statement reconciliation for one
+//! account.
+//!
+//! ratatui-based, like `navigate`: lists the account's postings that are
+//! not yet cleared (a posting's own state marker, else its transaction's)
+//! in the commodity being reconciled, with a running cleared balance — the
+//! already-cleared total plus every posting marked so far — against the
+//! statement's ending balance. Key bindings:
+//!
+//! - `Space`: mark / unmark the posting and move down
+//! - `↑` / `↓`, `PgUp` / `PgDn`, `Ctrl+U` / `Ctrl+D`, `Home` / `End`: move
+//! - `0`–`9`, `.`, `-`: type the statement balance; `Backspace` edits it
+//! - `Enter`: finish — write the marks back; `Esc` / `Ctrl+C`: quit without
+//!   writing
+//!
+//! Finishing writes a `* ` state marker in front of the account on each
+//! marked posting's source line (replacing a `! ` one), with the same
+//! line-surgical rewrite as `rename`: each file is re-parsed so only
+//! lines that really are postings of the account are touched, the rest
+//! of the file stays byte-for-byte identical, and the write goes through
+//! a temp file + rename. The transaction's own state is left alone —
+//! only the bank leg is reconciled, not the entry's other postings.
+//!
+//! With `--assert DATE`, finishing also appends a balance assertion for
+//! the statement balance, dated DATE, to the file holding the account's
+//! last posting on or before it — once the account is reconciled and
+//! every posting up to DATE is cleared, since the assertion checks the
+//! account's whole balance. Both legs of the entry are on the account
+//! itself and zero, so it asserts without moving any money.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::stdout;
+use std::path::Path;
+use std::sync::Arc;
+
+use colored::Colorize;
+use crossterm::ExecutableCommand;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{
+    EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode,
+};
+use ratatui::prelude::{
+    Color, Constraint, CrosstermBackend, Direction, Frame, Layout, Line, Span, Style, Terminal,
+};
+use ratatui::widgets::{List, ListItem, Paragraph};
+
+use crate::commands::util::{format_amount, write_atomic};
+use crate::date::Date;
+use crate::decimal::Decimal;
+use crate::error::Error;
+use crate::loader::Journal;
+use crate::parser;
+use crate::parser::entry::Entry;
+use crate::parser::transaction::State;
+
+const SEL_BG: Color = Color::Rgb(40, 40, 55);
+
+/// Gap between columns, matching `reg`.
+const GAP: usize = 2;
+
+/// One posting still to reconcile, located in its source file.
+struct Item {
+    date: Date,
+    description: String,
+    amount: Decimal,
+    pending: bool,
+    file: Arc<str>,
+    line: usize,
+    marked: bool,
+}
+
+pub fn run(
+    journal: &Journal,
+    account: &str,
+    commodity: Option<&str>,
+    balance: Option<&str>,
+    assert: Option<Date>,
+) -> Result<(), Error> {
+    let commodity = account_commodity(journal, account, commodity)?;
+    let (cleared, items) = collect(journal, account, &commodity);
+    if items.is_empty() {
+        eprintln!("{} nothing to reconcile in {}", "!".yellow(), account);
+        return Ok(());
+    }
+    let statement = match balance {
+        Some(b) => {
+            Decimal::parse(b)
+                .map_err(|e| Error::from(format!("reconcile: --balance {}: {}", b, e)))?;
+            b.to_string()
+        }
+        None => String::new(),
+    };
+
+    let mut app = App {
+        items,
+        cleared,
+        commodity: &commodity,
+        precisions: &journal.precisions,
+        statement,
+        cursor: 0,
+        scroll_offset: 0,
+    };
+    if !interact(&mut app)? {
+        eprintln!("{} reconciliation cancelled, nothing written", "!".yellow());
+        return Ok(());
+    }
+
+    let marked: Vec<&Item> = app.items.iter().filter(|i| i.marked).collect();
+    let files = write_marks(account, &marked)?;
+    let label = if marked.len() == 1 {
+        "posting"
+    } else {
+        "postings"
+    };
+    eprintln!(
+        "{} cleared {} {} in {} file(s)",
+        "✓".green(),
+        marked.len(),
+        label,
+        files
+    );
+
+    if let Some(date) = assert {
+        append_assertion(journal, account, &app, date)?;
+    }
+    Ok(())
+}
+
+/// The commodity to reconcile: `--commodity` when given, else the
+/// account's only one. An account holding several needs the flag.
+fn account_commodity(
+    journal: &Journal,
+    account: &str,
+    commodity: Option<&str>,
+) -> Result<String, Error> {
+    if let Some(c) = commodity {
+        return Ok(c.to_string());
+    }
+    let held: BTreeMap<&str, ()> = journal
+        .transactions
+        .iter()
+        .flat_map(|lt| lt.value.postings.iter())
+        .filter(|lp| lp.value.account == account)
+        .filter_map(|lp| lp.value.amount.as_ref())
+        .map(|a| (a.commodity.as_str(), ()))
+        .collect();
+    let held: Vec<&str> = held.into_keys().collect();
+    match held.as_slice() {
+        [] => Err(Error::from(format!(
+            "reconcile: no postings to {}",
+            account
+        ))),
+        [one] => Ok(one.to_string()),
+        _ => Err(Error::from(format!(
+            "reconcile: {} holds several commodities ({}) — pick one with --commodity",
+            account,
+            held.join(", ")
+        ))),
+    }
+}
+
+/// The account's cleared balance in `commodity`, and its postings in that
+/// commodity that are not cleared yet, in journal (date) order.
+fn collect(journal: &Journal, account: &str, commodity: &str) -> (Decimal, Vec<Item>) {
+    let mut cleared = Decimal::zero();
+    let mut items = Vec::new();
+    for lt in &journal.transactions {
+        let tx = &lt.value;
+        for lp in &tx.postings {
+            let p = &lp.value;
+            if p.account != account {
+                continue;
+            }
+            let Some(a) = p.amount.as_ref().filter(|a| a.commodity == commodity) else {
+                continue;
+            };
+            match tx.posting_state(p) {
+                State::Cleared => cleared += a.value,
+                state => items.push(Item {
+                    date: tx.date,
+                    description: tx.description.clone(),
+                    amount: a.value,
+                    pending: state == State::Pending,
+                    file: lp.file.clone(),
+                    line: lp.line,
+                    marked: false,
+                }),
+            }
+        }
+    }
+    (cleared, items)
+}
+
+/// Run the screen until the user finishes (`true`) or quits (`false`).
+fn interact(app: &mut App) -> Result<bool, Error> {
+    enable_raw_mode().map_err(|e| Error::from(e.to_string()))?;
+    stdout()
+        .execute(EnterAlternateScreen)
+        .map_err(|e| Error::from(e.to_string()))?;
+    let result = event_loop(app);
+    disable_raw_mode().map_err(|e| Error::from(e.to_string()))?;
+    stdout()
+        .execute(LeaveAlternateScreen)
+        .map_err(|e| Error::from(e.to_string()))?;
+    result
+}
+
+fn event_loop(app: &mut App) -> Result<bool, Error> {
+    let mut terminal =
+        Terminal::new(CrosstermBackend::new(stdout())).map_err(|e| Error::from(e.to_string()))?;
+    let mut frame_height: u16 = 20;
+    loop {
+        terminal
+            .draw(|frame| {
+                frame_height = frame.area().height;
+                app.adjust_scroll(frame_height.saturating_sub(1) as usize);
+                draw(frame, app);
+            })
+            .map_err(|e| Error::from(e.to_string()))?;
+
+        let Ok(Event::Key(key)) = event::read() else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        let half_page = (frame_height / 2).max(1) as usize;
+        match key.code {
+            KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if ctrl => return Ok(false),
+            KeyCode::Enter => return Ok(true),
+            KeyCode::Up => app.up(),
+            KeyCode::Down => app.down(),
+            KeyCode::Home => app.cursor = 0,
+            KeyCode::End => app.cursor = app.items.len() - 1,
+            KeyCode::PageUp => app.cursor = app.cursor.saturating_sub(half_page * 2),
+            KeyCode::PageDown => app.page_down(half_page * 2),
+            KeyCode::Char('u') if ctrl => app.cursor = app.cursor.saturating_sub(half_page),
+            KeyCode::Char('d') if ctrl => app.page_down(half_page),
+            KeyCode::Char(' ') => {
+                app.toggle();
+                app.down();
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() || c == '.' || c == '-' => {
+                app.statement.push(c);
+            }
+            KeyCode::Backspace => {
+                app.statement.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+struct App<'a> {
+    items: Vec<Item>,
+    cleared: Decimal,
+    commodity: &'a str,
+    precisions: &'a HashMap<String, usize>,
+    statement: String,
+    cursor: usize,
+    scroll_offset: usize,
+}
+
+impl App<'_> {
+    fn toggle(&mut self) {
+        if let Some(item) = self.items.get_mut(self.cursor) {
+            item.marked = !item.marked;
+        }
+    }
+
+    fn up(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    fn down(&mut self) {
+        self.page_down(1);
+    }
+
+    fn page_down(&mut self, n: usize) {
+        self.cursor = (self.cursor + n).min(self.items.len().saturating_sub(1));
+    }
+
+    fn adjust_scroll(&mut self, height: usize) {
+        if height == 0 {
+            return;
+        }
+        if self.cursor < self.scroll_offset {
+            self.scroll_offset = self.cursor;
+        } else if self.cursor >= self.scroll_offset + height {
+            self.scroll_offset = self.cursor + 1 - height;
+        }
+    }
+
+    /// The cleared balance once the marked postings are cleared too.
+    fn cleared_balance(&self) -> Decimal {
+        self.items
+            .iter()
+            .filter(|i| i.marked)
+            .fold(self.cleared, |acc, i| acc + i.amount)
+    }
+
+    /// The statement balance typed so far, when it parses.
+    fn statement_balance(&self) -> Option<Decimal> {
+        Decimal::parse(&self.statement).ok()
+    }
+
+    /// True when the cleared balance matches the statement at the
+    /// commodity's display precision.
+    fn reconciled(&self) -> bool {
+        let prec = self.precisions.get(self.commodity).copied().unwrap_or(2);
+        self.statement_balance()
+            .is_some_and(|s| (s - self.cleared_balance()).is_display_zero(prec))
+    }
+
+    fn amount(&self, value: &Decimal) -> String {
+        format_amount(self.commodity, value, self.precisions)
+    }
+}
+
+fn draw(frame: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(frame.area());
+    let width = chunks[0].width as usize;
+
+    // The running cleared balance after each row: the cleared total plus
+    // every marked posting up to and including it.
+    let mut running = app.cleared;
+    let rows: Vec<(String, String)> = app
+        .items
+        .iter()
+        .map(|item| {
+            if item.marked {
+                running += item.amount;
+            }
+            (app.amount(&item.amount), app.amount(&running))
+        })
+        .collect();
+    let amount_w = rows
+        .iter()
+        .map(|(a, _)| a.chars().count())
+        .max()
+        .unwrap_or(0);
+    let running_w = rows
+        .iter()
+        .map(|(_, r)| r.chars().count())
+        .max()
+        .unwrap_or(0);
+    // " [x] DATE ! " then the description fills what the amounts leave.
+    let lead_w = 1 + 4 + 10 + 3;
+    let desc_w = width
+        .saturating_sub(lead_w + GAP + amount_w + GAP + running_w)
+        .max(1);
+
+    let height = chunks[0].height as usize;
+    let end = (app.scroll_offset + height).min(app.items.len());
+    let mut items: Vec<ListItem> = Vec::new();
+    for (i, item) in app.items[app.scroll_offset..end].iter().enumerate() {
+        let index = i + app.scroll_offset;
+        let bg = if index == app.cursor {
+            SEL_BG
+        } else {
+            Color::Reset
+        };
+        let style = Style::default().bg(bg);
+        let (amount, run) = &rows[index];
+        let mark = if item.marked { " [x] " } else { " [ ] " };
+        let state = if item.pending { " ! " } else { "   " };
+        let desc: String = item.description.chars().take(desc_w).collect();
+        let desc_pad = desc_w.saturating_sub(desc.chars().count());
+        let amount_color = if item.amount.is_negative() {
+            Color::Red
+        } else {
+            Color::Green
+        };
+        let mark_style = if item.marked {
+            style.fg(Color::Green)
+        } else {
+            style
+        };
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(mark.to_string(), mark_style),
+            Span::styled(item.date.to_string(), style.fg(Color::DarkGray)),
+            Span::styled(state.to_string(), style.fg(Color::Yellow)),
+            Span::styled(format!("{}{}", desc, " ".repeat(desc_pad + GAP)), style),
+            Span::styled(
+                format!("{:>w$}", amount, w = amount_w),
+                style.fg(amount_color),
+            ),
+            Span::styled(" ".repeat(GAP), style),
+            Span::styled(format!("{:>w$}", run, w = running_w), style.fg(Color::Blue)),
+        ])));
+    }
+    frame.render_widget(List::new(items), chunks[0]);
+
+    let cleared = app.amount(&app.cleared_balance());
+    let (statement, difference) = match app.statement_balance() {
+        Some(s) => (app.amount(&s), app.amount(&(s - app.cleared_balance()))),
+        None => (format!("{}_", app.statement), "—".to_string()),
+    };
+    let diff_color = if app.reconciled() {
+        Color::Green
+    } else {
+        Color::Red
+    };
+    let footer = Line::from(vec![
+        Span::raw(format!(
+            " Cleared: {}  Statement: {}  Difference: ",
+            cleared, statement
+        )),
+        Span::styled(difference, Style::default().fg(diff_color)),
+        Span::styled(
+            "  |  Space:mark  0-9:statement  Enter:write  Esc:quit",
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]);
+    frame.render_widget(Paragraph::new(footer), chunks[1]);
+}
+
+/// Write the `*` marker onto every marked posting, file by file. Returns
+/// how many files changed.
+fn write_marks(account: &str, marked: &[&Item]) -> Result<usize, Error> {
+    let mut by_file: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for item in marked {
+        by_file.entry(&item.file).or_default().push(item.line);
+    }
+    let mut files = 0;
+    for (file, lines) in by_file {
+        let path = Path::new(file);
+        let source = fs::read_to_string(path)
+            .map_err(|e| Error::from(format!("read {}: {}", path.display(), e)))?;
+        let entries = parser::parse(&source)
+            .map_err(|e| Error::from(format!("parse {}: {}", path.display(), e)))?;
+        // Only lines that are written postings in this file — a posting
+        // generated from a periodic rule points at the rule. The item is
+        // already a resolved posting of the account; the line may spell
+        // it through an alias, a `$role` or `$year`.
+        let postings: HashSet<usize> = entries
+            .iter()
+            .filter_map(|e| match &e.value {
+                Entry::Transaction(tx) => Some(tx),
+                _ => None,
+            })
+            .flat_map(|tx| tx.postings.iter())
+            .map(|lp| lp.line)
+            .collect();
+        let (hits, skipped): (Vec<usize>, Vec<usize>) =
+            lines.into_iter().partition(|l| postings.contains(l));
+        for line in skipped {
+            eprintln!(
+                "{} {}:{}: not a posting of {}",
+                "skip".yellow(),
+                file,
+                line,
+                account
+            );
+        }
+        if hits.is_empty() {
+            continue;
+        }
+        write_atomic(path, &mark_cleared(&source, &hits))?;
+        files += 1;
+    }
+    Ok(files)
+}
+
+/// Put a `* ` state marker in front of the account on each of `lines`
+/// (1-based), replacing a `! ` one. Splitting on `\n` and re-joining
+/// keeps every other byte of the file as it was.
+fn mark_cleared(source: &str, lines: &[usize]) -> String {
+    let mut out: Vec<String> = source.split('\n').map(String::from).collect();
+    for &n in lines {
+        let Some(line) = out.get_mut(n - 1) else {
+            continue;
+        };
+        let body = line.trim_start();
+        let indent = &line[..line.len() - body.len()];
+        let body = match body.as_bytes() {
+            [b'*', b' ' | b'\t', ..] => continue,
+            [b'!', b' ' | b'\t', ..] => body[2..].trim_start(),
+            _ => body,
+        };
+        *line = format!("{}* {}", indent, body);
+    }
+    out.join("\n")
+}
+
+/// Append the statement-balance assertion, when the account is fully
+/// reconciled up to `date`; otherwise say why it is left out.
+fn append_assertion(journal: &Journal, account: &str, app: &App, date: Date) -> Result<(), Error> {
+    let Some(statement) = app.statement_balance().filter(|_| app.reconciled()) else {
+        eprintln!("{} not reconciled, no assertion appended", "!".yellow());
+        return Ok(());
+    };
+    let prec = journal.precisions.get(app.commodity).copied().unwrap_or(2);
+    let mut through = Decimal::zero();
+    let mut file: Option<&Arc<str>> = None;
+    for lt in journal
+        .transactions
+        .iter()
+        .filter(|lt| lt.value.date <= date)
+    {
+        for lp in &lt.value.postings {
+            let p = &lp.value;
+            if p.account != account {
+                continue;
+            }
+            if let Some(a) = p.amount.as_ref().filter(|a| a.commodity == app.commodity) {
+                through += a.value;
+                file = Some(&lp.file);
+            }
+        }
+    }
+    if !(through - statement).is_display_zero(prec) {
+        eprintln!(
+            "{} {} on {} is {}, not the statement's {} — uncleared postings remain, no assertion appended",
+            "!".yellow(),
+            account,
+            date,
+            app.amount(&through),
+            app.amount(&statement)
+        );
+        return Ok(());
+    }
+    let Some(file) = file.or_else(|| app.items.first().map(|i| &i.file)) else {
+        return Ok(());
+    };
+    let path = Path::new(&**file);
+    let mut source = fs::read_to_string(path)
+        .map_err(|e| Error::from(format!("read {}: {}", path.display(), e)))?;
+    if !source.is_empty() && !source.ends_with('\n') {
+        source.push('\n');
+    }
+    source.push('\n');
+    source.push_str(&crate::commands::format::format_source(
+        &assertion_entry(
+            date,
+            account,
+            app.commodity,
+            &statement,
+            &journal.precisions,
+        ),
+        false,
+    )?);
+    write_atomic(path, &source)?;
+    eprintln!("{} balance assertion appended to {}", "✓".green(), file);
+    Ok(())
+}
+
+/// A balance assertion as ledger text: two zero legs on the account,
+/// the first asserting the statement balance.
+fn assertion_entry(
+    date: Date,
+    account: &str,
+    commodity: &str,
+    balance: &Decimal,
+    precisions: &HashMap<String, usize>,
+) -> String {
+    format!(
+        "{} * Statement balance\n\t{}\t{} = {}\n\t{}\n",
+        date,
+        account,
+        format_amount(commodity, &Decimal::zero(), precisions),
+        format_amount(commodity, balance, precisions),
+        account
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    const SRC: &str = "\
+        2024-03-01 * Salary\n\
+        \tassets:checking  3000.00 EUR\n\
+        \tincome:salary\n\
+        2024-03-05 Rent\n\
+        \tassets:checking  -1000.00 EUR\n\
+        \texpenses:rent\n\
+        2024-03-20 ! Groceries\n\
+        \t! assets:checking  -50.00 EUR  ; card\n\
+        \texpenses:food\n";
+
+    fn load(path: &Path) -> Journal {
+        crate::load(&[path]).unwrap()
+    }
+
+    #[test]
+    fn marks_round_trip_and_assertion_loads() {
+        let tmp = crate::testutil::TempJournal::new(SRC);
+        let path = &tmp.path;
+        let journal = load(path);
+        let (cleared, mut items) = collect(&journal, "assets:checking", "EUR");
+        assert_eq!(cleared, Decimal::from(3000));
+        assert_eq!(items.len(), 2);
+        assert!(items[1].pending);
+
+        for item in &mut items {
+            item.marked = true;
+        }
+        let marked: Vec<&Item> = items.iter().collect();
+        assert_eq!(write_marks("assets:checking", &marked).unwrap(), 1);
+        let text = fs::read_to_string(path).unwrap();
+        assert!(text.contains("\t* assets:checking  -1000.00 EUR\n"));
+        assert!(text.contains("\t* assets:checking  -50.00 EUR  ; card\n"));
+        // Only the bank leg is cleared, not the entry's other postings.
+        assert!(text.contains("2024-03-05 Rent\n\t* assets:checking"));
+        assert!(text.contains("\n\texpenses:rent\n"));
+
+        let mut journal = load(path);
+        let (cleared, items) = collect(&journal, "assets:checking", "EUR");
+        assert_eq!(cleared, Decimal::from(1950));
+        assert!(items.is_empty());
+
+        let entry = assertion_entry(
+            Date::parse("2024-03-31").unwrap(),
+            "assets:checking",
+            "EUR",
+            &Decimal::from(1950),
+            &journal.precisions,
+        );
+        fs::write(&path, format!("{}\n{}", text, entry)).unwrap();
+        journal = load(path);
+        assert_eq!(journal.transactions.len(), 4);
+        fs::write(
+            path,
+            format!("{}\n{}", text, entry.replace("1950", "1900")),
+        )
+        .unwrap();
+        assert!(crate::load(&[path]).is_err());
+    }
+
+    #[test]
+    fn marks_a_posting_written_through_a_role() {
+        let tmp = crate::testutil::TempJournal::new(
+            "account assets:checking\n    bank main\n\
+             2024-03-05 Rent\n\t$bank:main  -1000.00 EUR\n\texpenses:rent\n",
+        );
+        let journal = load(&tmp.path);
+        let (_, mut items) = collect(&journal, "assets:checking", "EUR");
+        assert_eq!(items.len(), 1);
+        items[0].marked = true;
+        assert_eq!(write_marks("assets:checking", &[&items[0]]).unwrap(), 1);
+        let text = fs::read_to_string(&tmp.path).unwrap();
+        assert!(text.contains("\t* $bank:main  -1000.00 EUR\n"));
+    }
+
+    #[test]
+    fn draw_shows_running_cleared_balance_and_difference() {
+        let journal = crate::testutil::load(SRC);
+        let (cleared, items) = collect(&journal, "assets:checking", "EUR");
+        let mut app = App {
+            items,
+            cleared,
+            commodity: "EUR",
+            precisions: &journal.precisions,
+            statement: "2000".to_string(),
+            cursor: 0,
+            scroll_offset: 0,
+        };
+        app.toggle();
+        assert_eq!(app.cleared_balance(), Decimal::from(2000));
+        assert!(app.reconciled());
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 5)).unwrap();
+        terminal.draw(|f| draw(f, &app)).unwrap();
+        let buf = terminal.backend().buffer();
+        let line = |y: u16| -> String {
+            (0..buf.area().width)
+                .map(|x| buf[(x, y)].symbol())
+                .collect()
+        };
+        assert!(line(0).starts_with(" [x] 2024-03-05   Rent"));
+        assert!(line(0).trim_end().ends_with("EUR2000.00"));
+        assert!(line(1).trim_end().ends_with("EUR2000.00"));
+        assert!(line(4).contains("Difference: EUR0.00"), "{}", line(4));
+    }
+}
@@ -12,6 +12,13 @@
 //! is rendered per-commodity; rows with multiple non-zero commodities
 //! add continuation lines for the extra commodities.
 //!
+//! With `explain` (`--explain-rates`) each converted posting is followed
+//! by its `rate:` note — the path and `P` entries its `-X` rate came from.
+//!
+//! Under `-O json|csv|tsv` each posting is one row: its date, state,
+//! code, description, account and register label, amount and the running
+//! total in that commodity, and its source `file` / `line`.
+//!
 //! Runs after the filter phase — the journal is already scoped.
 
 use std::collections::BTreeMap;
@@ -19,16 +26,24 @@ use std::io::{self, BufWriter, Write};
 
 use colored::Colorize;
 
+use super::output::{exact, state_name, Format, Table};
 use super::util::{format_amount, paint_label, render_account, shows_nonzero, write_spaces};
 use crate::decimal::Decimal;
 use crate::loader::{Journal, LabelView};
 use crate::parser::transaction::{State, Transaction};
+use crate::rebalancer::RATE_NOTE;
 
 const GAP: usize = 2;
 
-pub fn run(journal: &Journal) {
+/// `aux` (`--aux-date`) dates each row by the transaction's effective
+/// date instead of its primary one; `explain` prints the rebalancer's
+/// rate notes under their postings.
+pub fn run(journal: &Journal, aux: bool, explain: bool, format: Format) {
+    if format != Format::Text {
+        return table(journal, aux).print(format);
+    }
     let precisions = &journal.precisions;
-    let rows = build_rows(journal);
+    let rows = build_rows(journal, aux, explain);
     let widths = compute_widths(&rows, precisions, terminal_cols());
 
     // One locked, buffered writer for the whole register — see `print`.
@@ -72,11 +87,55 @@ pub fn run(journal: &Journal) {
                     }
                 }
             }
+            for note in &entry.notes {
+                let _ = write_spaces(&mut out, widths.title + GAP);
+                let _ = writeln!(out, "{}", note.dimmed());
+            }
         }
     }
     let _ = out.flush();
 }
 
+fn table(journal: &Journal, aux: bool) -> Table {
+    let mut table = Table::new(&[
+        "date",
+        "state",
+        "code",
+        "description",
+        "account",
+        "label",
+        "commodity",
+        "amount",
+        "total",
+        "file",
+        "line",
+    ]);
+    let mut running: BTreeMap<&str, Decimal> = BTreeMap::new();
+    for lt in &journal.transactions {
+        let tx = &lt.value;
+        for lp in &tx.postings {
+            let p = &lp.value;
+            let Some(amount) = &p.amount else { continue };
+            let total = running.entry(&amount.commodity).or_insert_with(Decimal::zero);
+            *total += amount.value;
+            table.push(vec![
+                Some(tx.report_date(aux).to_string()),
+                Some(state_name(tx.posting_state(p)).to_string()),
+                tx.code.clone(),
+                Some(tx.description.clone()),
+                Some(p.account.clone()),
+                journal.label_for(&p.account, LabelView::Register).map(String::from),
+                Some(amount.commodity.clone()),
+                Some(exact(&amount.value)),
+                Some(exact(total)),
+                Some(lp.file.to_string()),
+                Some(lp.line.to_string()),
+            ]);
+        }
+    }
+    table
+}
+
 struct Row {
     title: String,
     entries: Vec<Entry>,
@@ -92,6 +151,8 @@ struct Entry {
     amount: String,
     amount_negative: bool,
     total: BTreeMap<String, Decimal>,
+    /// `rate:` notes, under `explain`.
+    notes: Vec<String>,
 }
 
 struct Widths {
@@ -104,12 +165,12 @@ struct Widths {
 /// Walk the journal, accumulating a per-commodity running total.
 /// Each posting produces one `Entry` capturing the running total at
 /// the moment it was applied.
-fn build_rows(journal: &Journal) -> Vec<Row> {
+fn build_rows(journal: &Journal, aux: bool, explain: bool) -> Vec<Row> {
     let mut rows = Vec::new();
     let mut running: BTreeMap<String, Decimal> = BTreeMap::new();
 
     for tx in &journal.transactions {
-        let title = format_title(&tx.value);
+        let title = format_title(&tx.value, aux);
         let mut entries = Vec::new();
 
         for lp in &tx.value.postings {
@@ -125,12 +186,21 @@ fn build_rows(journal: &Journal) -> Vec<Row> {
 
             let amount_str = format_amount(&amount.commodity, &amount.value, &journal.precisions);
 
+            let notes = p
+                .comments
+                .iter()
+                .map(|c| &c.value.text)
+                .filter(|text| explain && text.starts_with(RATE_NOTE))
+                .cloned()
+                .collect();
+
             entries.push(Entry {
                 account,
                 account_width,
                 amount: amount_str,
                 amount_negative: amount.value.is_negative(),
                 total: running.clone(),
+                notes,
             });
         }
 
@@ -218,13 +288,13 @@ fn non_zero_commodities(
         .collect()
 }
 
-fn format_title(tx: &Transaction) -> String {
+fn format_title(tx: &Transaction, aux: bool) -> String {
     let marker = match tx.state {
         State::Cleared => " * ",
         State::Uncleared => " ",
         State::Pending => " ! ",
     };
-    format!("{}{}{}", tx.date, marker, tx.description)
+    format!("{}{}{}", tx.report_date(aux), marker, tx.description)
 }
 
 
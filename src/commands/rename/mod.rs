@@ -19,14 +19,19 @@
 //! are left untouched — and the rest of the file stays byte-for-byte
 //! identical. A file that fails to parse is reported on stderr and
 //! skipped, never edited.
+//!
+//! `include` directives are followed: the files they name are renamed in
+//! as well, so one entry file stands for its whole tree.
 
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use colored::Colorize;
 
-use super::util::{render_account, shorten_home};
+use super::util::{render_account, shorten_home, write_atomic};
 use crate::Error;
+use crate::loader::include;
 use crate::parser;
 use crate::parser::entry::Entry;
 
@@ -50,12 +55,18 @@ pub fn run(paths: &[PathBuf], old: &str, new: &str, execute: bool) -> Result<(),
     let mut files = paths.to_vec();
     files.sort();
     files.dedup();
+    let mut seen: HashSet<PathBuf> = files.iter().map(|p| canonical(p)).collect();
 
     let mut postings = 0usize;
     let mut changed_files = 0usize;
 
-    for path in &files {
-        let source = match fs::read_to_string(path) {
+    // Indexed rather than iterated: files reached through `include` are
+    // appended while walking, and visited in turn.
+    let mut next = 0;
+    while next < files.len() {
+        let path = files[next].clone();
+        next += 1;
+        let source = match fs::read_to_string(&path) {
             Ok(s) => s,
             Err(e) => {
                 eprintln!("{} {}: {}", "skip".yellow(), path.display(), e);
@@ -70,6 +81,19 @@ pub fn run(paths: &[PathBuf], old: &str, new: &str, execute: bool) -> Result<(),
             }
         };
 
+        // An included file is part of the same journal, so its postings are
+        // renamed too. The `seen` set visits each file once, which also
+        // keeps an include cycle from looping.
+        for e in &entries {
+            let Entry::Include(pattern) = &e.value else { continue };
+            match include::expand(&path, pattern) {
+                Ok(targets) => {
+                    files.extend(targets.into_iter().filter(|t| seen.insert(canonical(t))));
+                }
+                Err(msg) => eprintln!("{} {}:{}: {}", "skip".yellow(), path.display(), e.line, msg),
+            }
+        }
+
         let hits = collect_hits(&entries, old, new);
         if hits.is_empty() {
             continue;
@@ -77,7 +101,7 @@ pub fn run(paths: &[PathBuf], old: &str, new: &str, execute: bool) -> Result<(),
 
         if execute {
             let rewritten = apply(&source, &hits);
-            write_atomic(path, &rewritten)?;
+            write_atomic(&path, &rewritten)?;
         }
 
         let shown = shorten_home(&path.to_string_lossy());
@@ -138,6 +162,12 @@ fn rename_account(account: &str, old: &str, new: &str) -> Option<String> {
     }
 }
 
+/// A file's identity for the visited set — the canonical path when it
+/// resolves, else the path as given.
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
 /// Rewrite the matched posting lines in `source`, leaving every other
 /// byte untouched. Splitting on `\n` and re-joining reconstructs the
 /// file exactly (including a trailing newline and any `\r`), so only the
@@ -154,16 +184,6 @@ fn apply(source: &str, hits: &[Hit]) -> String {
     lines.join("\n")
 }
 
-/// Write `contents` to `path` via a temp file + rename, so a crash
-/// mid-write never leaves a half-written journal.
-fn write_atomic(path: &Path, contents: &str) -> Result<(), Error> {
-    let tmp = path.with_extension("rename-tmp");
-    fs::write(&tmp, contents)
-        .map_err(|e| Error::from(format!("write {}: {}", tmp.display(), e)))?;
-    fs::rename(&tmp, path)
-        .map_err(|e| Error::from(format!("rename {} -> {}: {}", tmp.display(), path.display(), e)))
-}
-
 fn print_summary(postings: usize, files: usize, execute: bool) {
     if postings == 0 {
         println!("{} No matching accounts found.", "!".yellow());
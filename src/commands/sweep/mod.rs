@@ -63,6 +63,7 @@ pub fn run(
         crate::filter::SignFilter::Any,
         None,
         None,
+        false,
     );
 
     let (out, count) =
@@ -217,6 +218,7 @@ fn cancel(postings: Vec<(String, Decimal)>) -> Vec<(String, Decimal)> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parser::metadata::Metadata;
     use crate::parser::posting::{Amount, Posting};
     use crate::parser::transaction::State;
     use std::sync::Arc;
@@ -239,6 +241,9 @@ mod tests {
                 is_virtual: false,
                 balanced: true,
                 comments: Vec::new(),
+                metadata: Metadata::default(),
+                aux_date: None,
+                state: None,
             },
         }
     }
@@ -249,11 +254,13 @@ mod tests {
             line: 1,
             value: Transaction {
                 date: crate::date::Date::parse(date).unwrap(),
+                aux_date: None,
                 state: State::Cleared,
                 code: None,
                 description: "x".to_string(),
                 postings,
                 comments: Vec::new(),
+                metadata: Metadata::default(),
             },
         }
     }
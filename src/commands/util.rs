@@ -4,13 +4,19 @@
 //! commander that needs padding calls this helper so layouts stay
 //! consistent. `format_amount` is the canonical amount renderer:
 //! commodity-first, per-commodity precision, "-0.00" suppressed.
+//! `write_atomic` is how the commands that edit journals in place
+//! (`rename`, `reconcile`) write them back.
 
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 
 use colored::Colorize;
 
 use crate::decimal::Decimal;
+use crate::error::Error;
 use crate::parser::posting::Posting;
+use crate::parser::transaction::State;
 
 /// Paint a display label (from `account NAME / label …`) for report
 /// output. Centralised so the label colour lives in exactly one place —
@@ -46,6 +52,17 @@ pub(crate) fn render_account(p: &Posting) -> String {
     }
 }
 
+/// [`render_account`] behind the posting's own clear-state marker (`* `
+/// or `! `), as written in the source. `print` and `format` round-trip a
+/// journal, so they keep it; `register` shows the bare account.
+pub(crate) fn render_marked_account(p: &Posting) -> String {
+    match p.state {
+        Some(State::Cleared) => format!("* {}", render_account(p)),
+        Some(State::Pending) => format!("! {}", render_account(p)),
+        Some(State::Uncleared) | None => render_account(p),
+    }
+}
+
 /// Write `n` space characters to a `Write` sink. Commands stream their
 /// output through a `BufWriter`, so padding goes through the same writer.
 pub(crate) fn write_spaces<W: std::io::Write>(out: &mut W, n: usize) -> std::io::Result<()> {
@@ -100,3 +117,13 @@ pub(crate) fn format_amount(
     };
     format!("{}{}", commodity, formatted)
 }
+
+/// Write `contents` to `path` via a temp file + rename, so a crash
+/// mid-write never leaves a half-written journal.
+pub(crate) fn write_atomic(path: &Path, contents: &str) -> Result<(), Error> {
+    let tmp = path.with_extension("acc-tmp");
+    fs::write(&tmp, contents)
+        .map_err(|e| Error::from(format!("write {}: {}", tmp.display(), e)))?;
+    fs::rename(&tmp, path)
+        .map_err(|e| Error::from(format!("rename {} -> {}: {}", tmp.display(), path.display(), e)))
+}
@@ -85,6 +85,17 @@ impl Date {
         Date(civil_to_days(y as i64 + 1, 1, 1) as u32)
     }
 
+    /// The same day `n` months later, clamped to the end of a shorter
+    /// month (`2025-01-31` plus one month is `2025-02-28`).
+    pub fn add_months(self, n: u32) -> Self {
+        let (y, m, d) = days_to_date(self.0 as u64);
+        let months = y as i64 * 12 + m as i64 - 1 + n as i64;
+        let (ny, nm) = (months / 12, months % 12 + 1);
+        let first = civil_to_days(ny, nm, 1);
+        let next = if nm == 12 { civil_to_days(ny + 1, 1, 1) } else { civil_to_days(ny, nm + 1, 1) };
+        Date((first + d - 1).min(next - 1) as u32)
+    }
+
     /// Number of days between `self` and `other` (signed).
     pub fn days_until(self, other: Date) -> i64 {
         other.0 as i64 - self.0 as i64
@@ -242,6 +253,15 @@ mod tests {
         assert_eq!(d.to_string(), "2024-06-15");
     }
 
+    #[test]
+    fn add_months_clamps_to_month_end() {
+        let d = Date::parse("2024-01-31").unwrap();
+        assert_eq!(d.add_months(1).to_string(), "2024-02-29");
+        assert_eq!(d.add_months(3).to_string(), "2024-04-30");
+        assert_eq!(d.add_months(12).to_string(), "2025-01-31");
+        assert_eq!(Date::parse("2024-11-15").unwrap().add_months(2).to_string(), "2025-01-15");
+    }
+
     #[test]
     fn parse_rejects_garbage() {
         assert!(Date::parse("not-a-date").is_err());
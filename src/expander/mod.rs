@@ -21,6 +21,7 @@
 
 use crate::parser::entry::{AutoAmount, AutoRule};
 use crate::parser::located::Located;
+use crate::parser::metadata::Metadata;
 use crate::parser::posting::{Amount, Posting};
 use crate::parser::transaction::Transaction;
 
@@ -154,9 +155,12 @@ fn inject_rule(
                 lot_cost: None,
                 lot_date: None,
                 balance_assertion: None,
+                state: None,
                 is_virtual,
                 balanced,
                 comments: Vec::new(),
+                metadata: Metadata::default(),
+                aux_date: None,
             }
         })
         .collect()
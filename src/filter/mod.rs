@@ -16,6 +16,10 @@
 //! #XYZ               transaction-code equals "XYZ" (case-insensitive)
 //! code <XYZ>         same as #XYZ
 //! com <EUR>          posting commodity equals "EUR" (case-insensitive)
+//! tag <NAME>         posting or its transaction carries tag / key NAME
+//! tag <NAME=VALUE>   ... with that value (case-insensitive, exact)
+//! status <STATE>     posting's clear state (its own `* account` marker,
+//!                    else the transaction's): cleared, pending, uncleared
 //! not <pat>          negate the following single pattern
 //! and / or           combinators. Default between bare tokens is OR.
 //! ```
@@ -31,8 +35,9 @@
 
 use crate::loader::Journal;
 use crate::parser::located::Located;
+use crate::parser::metadata::Metadata;
 use crate::parser::posting::Posting;
-use crate::parser::transaction::Transaction;
+use crate::parser::transaction::{State, Transaction};
 
 /// Posting-level sign filter, driven by `--pos` / `--neg`. Applied as a
 /// secondary projection *after* transaction selection: it narrows which
@@ -78,6 +83,33 @@ impl SignFilter {
     }
 }
 
+/// The clear states selected by `-C` / `--pending` / `-U`; none set
+/// means no constraint (an empty list).
+pub fn states_from_flags(cleared: bool, pending: bool, uncleared: bool) -> Vec<State> {
+    [
+        (cleared, State::Cleared),
+        (pending, State::Pending),
+        (uncleared, State::Uncleared),
+    ]
+    .into_iter()
+    .filter_map(|(on, state)| on.then_some(state))
+    .collect()
+}
+
+/// Keep only the postings whose clear state — their own `* account`
+/// marker, else their transaction's — is one of `states`, then drop the
+/// transactions left empty. A posting cleared on its own inside a pending
+/// entry survives `-C`, so partially cleared entries report correctly.
+pub fn retain_states(transactions: &mut Vec<Located<Transaction>>, states: &[State]) {
+    for lt in transactions.iter_mut() {
+        let tx = &mut lt.value;
+        let state = tx.state;
+        tx.postings
+            .retain(|lp| states.contains(&lp.value.state.unwrap_or(state)));
+    }
+    transactions.retain(|lt| !lt.value.postings.is_empty());
+}
+
 /// Comparison operator for the amount filter.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum CmpOp {
@@ -163,6 +195,11 @@ impl AmountFilter {
 /// wants — show the complete entry whenever it matches — as opposed
 /// to `reg` / `bal`, which show only the matched postings.
 ///
+/// `aux_date` (`--aux-date`) dates the range check by the effective
+/// date instead: each posting is kept or dropped by its own
+/// [`Transaction::posting_date`], so a posting settled in another period
+/// than its siblings leaves the transaction on its own.
+///
 /// The non-transaction fields of `Journal` (prices, role accounts,
 /// precisions) pass through unchanged — they are either global
 /// metadata or derived before the filter runs.
@@ -176,6 +213,7 @@ pub fn filter(
     sign: SignFilter,
     display: Option<&str>,
     amount: Option<&AmountFilter>,
+    aux_date: bool,
 ) -> Journal {
     // Only `transactions` is transformed; every other field passes
     // through unchanged, so `..journal` carries them — including any
@@ -190,6 +228,7 @@ pub fn filter(
         sign,
         display,
         amount,
+        aux_date,
     );
     Journal {
         transactions,
@@ -209,6 +248,7 @@ fn filter_transactions(
     sign: SignFilter,
     display: Option<&str>,
     amount: Option<&AmountFilter>,
+    aux_date: bool,
 ) -> Vec<Located<Transaction>> {
     let matcher = (!patterns.is_empty()).then(|| PatternMatcher::from_parts(patterns));
     let display_matcher = display.map(PatternMatcher::new);
@@ -219,14 +259,22 @@ fn filter_transactions(
     transactions
         .into_iter()
         .filter_map(|mut lt| {
-            if let Some(b) = begin_d
-                && lt.value.date < b {
-                    return None;
-                }
-            if let Some(e) = end_d
-                && lt.value.date >= e {
+            let in_range = |d: crate::date::Date| {
+                begin_d.is_none_or(|b| d >= b) && end_d.is_none_or(|e| d < e)
+            };
+            if aux_date {
+                // Effective dates can differ per posting: range-check each
+                // (a posting's own `[=DATE]`, else the transaction's).
+                let tx_date = lt.value.report_date(true);
+                lt.value
+                    .postings
+                    .retain(|lp| in_range(lp.value.aux_date.unwrap_or(tx_date)));
+                if lt.value.postings.is_empty() {
                     return None;
                 }
+            } else if !in_range(lt.value.date) {
+                return None;
+            }
             // Lowercased tx-wide fields for pattern matching; only needed
             // when a positional pattern is present.
             let (desc_lower, code_lower) = if matcher.is_some() {
@@ -247,7 +295,7 @@ fn filter_transactions(
                     .value
                     .postings
                     .iter()
-                    .any(|lp| m.matches_full(&lp.value, &desc_lower, &code_lower));
+                    .any(|lp| m.matches_full(&lp.value, &desc_lower, &code_lower, lt.value.state, &lt.value.metadata));
                 if !any {
                     return None;
                 }
@@ -262,7 +310,9 @@ fn filter_transactions(
             let matched = |lp: &Located<Posting>| {
                 matcher
                     .as_ref()
-                    .is_none_or(|m| m.matches_full(&lp.value, &desc_lower, &code_lower))
+                    .is_none_or(|m| {
+                        m.matches_full(&lp.value, &desc_lower, &code_lower, lt.value.state, &lt.value.metadata)
+                    })
                     && sign.keeps(&lp.value)
                     && amount.is_none_or(|af| af.keeps(&lp.value))
             };
@@ -340,17 +390,19 @@ impl PatternMatcher {
     }
 
     /// Full posting match — used during the filter pass where all
-    /// context (posting + transaction description + code) is
-    /// available.
+    /// context (posting + transaction description, code, state and
+    /// metadata) is available.
     pub(crate) fn matches_full(
         &self,
         posting: &Posting,
         desc_lower: &str,
         code_lower: &str,
+        tx_state: State,
+        tx_meta: &Metadata,
     ) -> bool {
         match &self.query {
             None => true,
-            Some(q) => q.eval(posting, desc_lower, code_lower),
+            Some(q) => q.eval(posting, desc_lower, code_lower, tx_state, tx_meta),
         }
     }
 
@@ -370,6 +422,14 @@ impl PatternMatcher {
 #[derive(Debug, Clone)]
 enum Query {
     Match(Dim, Pattern),
+    /// `tag NAME[=VALUE]`: the name is matched case-insensitively against
+    /// the effective metadata (posting over transaction); the value, when
+    /// given, must equal it, also case-insensitively.
+    Tag(String, Option<String>),
+    /// `status STATE`: the posting's own clear state, else its
+    /// transaction's. Holds the accepted states; an unknown keyword
+    /// leaves it empty, matching nothing.
+    Status(Vec<State>),
     Not(Box<Query>),
     And(Box<Query>, Box<Query>),
     Or(Box<Query>, Box<Query>),
@@ -409,7 +469,7 @@ impl Pattern {
 }
 
 impl Query {
-    fn eval(&self, p: &Posting, desc_lower: &str, code_lower: &str) -> bool {
+    fn eval(&self, p: &Posting, desc_lower: &str, code_lower: &str, tx_state: State, tx_meta: &Metadata) -> bool {
         match self {
             Query::Match(dim, pat) => match dim {
                 Dim::Account => pat.test(&p.account.to_lowercase()),
@@ -419,12 +479,19 @@ impl Query {
                 // posting carries an amount.
                 Dim::Commodity => p.amount.as_ref().is_some_and(|a| pat.test(&a.commodity.to_lowercase())),
             },
-            Query::Not(q) => !q.eval(p, desc_lower, code_lower),
+            Query::Tag(name, value) => match Metadata::lookup(&p.metadata, tx_meta, name) {
+                None => false,
+                Some(v) => value.as_ref().is_none_or(|want| v.eq_ignore_ascii_case(want)),
+            },
+            Query::Status(states) => states.contains(&p.state.unwrap_or(tx_state)),
+            Query::Not(q) => !q.eval(p, desc_lower, code_lower, tx_state, tx_meta),
             Query::And(a, b) => {
-                a.eval(p, desc_lower, code_lower) && b.eval(p, desc_lower, code_lower)
+                a.eval(p, desc_lower, code_lower, tx_state, tx_meta)
+                    && b.eval(p, desc_lower, code_lower, tx_state, tx_meta)
             }
             Query::Or(a, b) => {
-                a.eval(p, desc_lower, code_lower) || b.eval(p, desc_lower, code_lower)
+                a.eval(p, desc_lower, code_lower, tx_state, tx_meta)
+                    || b.eval(p, desc_lower, code_lower, tx_state, tx_meta)
             }
         }
     }
@@ -434,7 +501,7 @@ impl Query {
     fn eval_account_only(&self, account_lower: &str) -> bool {
         match self {
             Query::Match(Dim::Account, pat) => pat.test(account_lower),
-            Query::Match(_, _) => true,
+            Query::Match(_, _) | Query::Tag(_, _) | Query::Status(_) => true,
             Query::Not(q) => !q.eval_account_only(account_lower),
             Query::And(a, b) => {
                 a.eval_account_only(account_lower) && b.eval_account_only(account_lower)
@@ -459,6 +526,8 @@ impl Query {
 /// primary  := "desc" VALUE
 ///           | "code" VALUE
 ///           | "com"  VALUE
+///           | "tag"  NAME ["=" VALUE]
+///           | "status" ("cleared" | "pending" | "uncleared")
 ///           | "@" REST                  # description
 ///           | "#" REST                  # code
 ///           | "^" REST "$"              # account exact
@@ -573,11 +642,37 @@ impl<'a> Parser<'a> {
                     },
                 ))
             }
+            "tag" => {
+                let val = self.advance()?;
+                let (name, value) = match val.split_once('=') {
+                    Some((n, v)) => (n, Some(v.to_string())),
+                    None => (val, None),
+                };
+                Some(Query::Tag(name.to_string(), value))
+            }
+            "status" => {
+                let val = self.advance()?;
+                Some(Query::Status(parse_status(val)))
+            }
             _ => Some(parse_bare_token(tok)),
         }
     }
 }
 
+/// The states a `status` value selects: `cleared` / `*`, `pending` / `!`,
+/// `uncleared`. An unknown value is reported and selects none.
+fn parse_status(val: &str) -> Vec<State> {
+    match val.to_ascii_lowercase().as_str() {
+        "cleared" | "*" => vec![State::Cleared],
+        "pending" | "!" => vec![State::Pending],
+        "uncleared" => vec![State::Uncleared],
+        _ => {
+            eprintln!("warning: unknown status '{}' (expected cleared, pending or uncleared)", val);
+            Vec::new()
+        }
+    }
+}
+
 /// Parse a bare (non-keyword) token into a `Query::Match` node.
 fn parse_bare_token(tok: &str) -> Query {
     if let Some(rest) = tok.strip_prefix('@') {
@@ -619,7 +714,6 @@ mod tests {
     use crate::decimal::Decimal;
     use crate::parser::comment::Comment;
     use crate::parser::posting::Amount;
-    use crate::parser::transaction::State;
     use std::sync::Arc;
 
     fn posting(account: &str, commodity: &str, value: i64) -> Located<Posting> {
@@ -640,6 +734,9 @@ mod tests {
                 is_virtual: false,
                 balanced: true,
                 comments: Vec::<Located<Comment>>::new(),
+                metadata: Metadata::default(),
+                aux_date: None,
+                state: None,
             },
         }
     }
@@ -659,23 +756,25 @@ mod tests {
             line: 1,
             value: Transaction {
                 date: crate::date::Date::parse(date).unwrap(),
+                aux_date: None,
                 state: State::Cleared,
                 code: code.map(String::from),
                 description: description.to_string(),
                 postings,
                 comments: Vec::new(),
+                metadata: Metadata::default(),
             },
         }
     }
 
     fn run(patterns: &[&str], txs: Vec<Located<Transaction>>) -> Vec<Located<Transaction>> {
         let pats: Vec<String> = patterns.iter().map(|s| s.to_string()).collect();
-        filter_transactions(txs, &pats, None, None, false, false, SignFilter::Any, None, None)
+        filter_transactions(txs, &pats, None, None, false, false, SignFilter::Any, None, None, false)
     }
 
     fn run_amount(expr: &str, txs: Vec<Located<Transaction>>) -> Vec<Located<Transaction>> {
         let af = AmountFilter::parse(expr).unwrap();
-        filter_transactions(txs, &[], None, None, false, false, SignFilter::Any, None, Some(&af))
+        filter_transactions(txs, &[], None, None, false, false, SignFilter::Any, None, Some(&af), false)
     }
 
     fn run_sign(
@@ -685,7 +784,7 @@ mod tests {
         txs: Vec<Located<Transaction>>,
     ) -> Vec<Located<Transaction>> {
         let pats: Vec<String> = patterns.iter().map(|s| s.to_string()).collect();
-        filter_transactions(txs, &pats, None, None, false, whole_transactions, sign, None, None)
+        filter_transactions(txs, &pats, None, None, false, whole_transactions, sign, None, None, false)
     }
 
     fn run_display(
@@ -706,6 +805,7 @@ mod tests {
             SignFilter::Any,
             Some(display),
             None,
+            false,
         )
     }
 
@@ -1015,6 +1115,60 @@ mod tests {
         assert_eq!(out.len(), 1);
     }
 
+    #[test]
+    fn tag_matches_posting_over_transaction_metadata() {
+        let mk = || {
+            let mut t = tx(
+                "2025-01-01",
+                "a",
+                vec![posting("expenses:x", "EUR", -5), posting("assets:cc", "EUR", 5)],
+            );
+            t.value.metadata.add_comment(":work: project: alpha");
+            t.value.postings[0].value.metadata.add_comment("project: beta");
+            vec![t]
+        };
+        // Bare name: every posting inherits the transaction tag.
+        assert_eq!(run(&["tag", "work"], mk())[0].value.postings.len(), 2);
+        // The posting-level value overrides the transaction-level one.
+        let out = run(&["tag", "project=alpha"], mk());
+        assert_eq!(accounts(&out[0]), vec!["assets:cc"]);
+        let out = run(&["tag", "PROJECT=Beta"], mk());
+        assert_eq!(accounts(&out[0]), vec!["expenses:x"]);
+        assert!(run(&["tag", "project=gamma"], mk()).is_empty());
+        assert!(run(&["tag", "missing"], mk()).is_empty());
+    }
+
+    #[test]
+    fn status_uses_posting_state_over_transaction_state() {
+        let mk = || {
+            let mut pending = tx(
+                "2025-01-01",
+                "a",
+                vec![posting("expenses:x", "EUR", -5), posting("assets:cc", "EUR", 5)],
+            );
+            pending.value.state = State::Pending;
+            pending.value.postings[1].value.state = Some(State::Cleared);
+            let cleared = tx("2025-01-02", "b", vec![posting("assets:cc", "EUR", 1)]);
+            vec![pending, cleared]
+        };
+        let out = run(&["status", "cleared"], mk());
+        assert_eq!(out.len(), 2);
+        assert_eq!(accounts(&out[0]), vec!["assets:cc"]);
+        let out = run(&["status", "pending"], mk());
+        assert_eq!(accounts(&out[0]), vec!["expenses:x"]);
+        // Composes with the other keywords.
+        let out = run(&["^assets", "and", "not", "status", "cleared"], mk());
+        assert!(out.is_empty());
+        assert!(run(&["status", "bogus"], mk()).is_empty());
+
+        // The `-C` / `--pending` / `-U` flags select the same postings.
+        let mut txs = mk();
+        retain_states(&mut txs, &states_from_flags(false, true, true));
+        assert_eq!(txs.len(), 1);
+        assert_eq!(accounts(&txs[0]), vec!["expenses:x"]);
+        assert!(states_from_flags(false, false, false).is_empty());
+    }
+
     #[test]
     fn date_range_preserved() {
         let txs = vec![
@@ -1040,11 +1194,40 @@ mod tests {
             SignFilter::Any,
             None,
             None,
+            false,
         );
         assert_eq!(out.len(), 1);
         assert_eq!(out[0].value.date.to_string(), "2025-02-01");
     }
 
+    #[test]
+    fn aux_date_range_checks_each_posting() {
+        let mut t = tx(
+            "2025-01-30",
+            "card",
+            vec![posting("expenses:x", "EUR", 5), posting("liabilities:card", "EUR", -5)],
+        );
+        t.value.aux_date = crate::date::Date::parse("2025-01-31").ok();
+        t.value.postings[1].value.aux_date = crate::date::Date::parse("2025-02-03").ok();
+        let run_feb = |aux: bool| {
+            filter_transactions(
+                vec![t.clone()],
+                &[],
+                Some("2025-02-01"),
+                None,
+                false,
+                false,
+                SignFilter::Any,
+                None,
+                None,
+                aux,
+            )
+        };
+        assert!(run_feb(false).is_empty());
+        let out = run_feb(true);
+        assert_eq!(accounts(&out[0]), vec!["liabilities:card"]);
+    }
+
     #[test]
     fn matches_account_only_accepts_when_only_non_account_dims_constrain() {
         let m = PatternMatcher::from_parts(&["com".into(), "EUR".into()]);
@@ -1379,6 +1562,7 @@ mod tests {
             SignFilter::Any,
             None,
             Some(&af),
+            false,
         )
     }
 
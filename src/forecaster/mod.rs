@@ -0,0 +1,136 @@
+//! Forecaster phase — project recurring rules into the future.
+//!
+//! Runs under `--forecast UNTIL`, between the loader and the enrichment
+//! phases (see [`pipeline::forecast`](crate::pipeline::forecast)). Each
+//! `~ EVERY from DATE` rule ([`Recurring`]) yields one transaction per
+//! occurrence in the forecast window, dated on the occurrence and carrying
+//! the rule's postings as written: a bare leg stays bare for the booker to
+//! fill, and `$year` / `$month` / `$day` in an account are filled from the
+//! occurrence date, as in a periodic block.
+//!
+//! The window starts the day after the latest recorded transaction, so an
+//! occurrence already written by hand is not projected a second time, and
+//! ends before UNTIL. Every generated transaction is marked with the
+//! [`TAG`] tag (and a `; :forecast:` comment, so `print` shows it):
+//! `tag forecast` selects them, `not tag forecast` leaves them out.
+
+use crate::date::Date;
+use crate::parser::located::Located;
+use crate::parser::metadata::Metadata;
+use crate::parser::transaction::{State, Transaction};
+use crate::parser::{Comment, Recurring};
+
+/// The tag every generated transaction carries.
+pub const TAG: &str = "forecast";
+
+/// The first day of the forecast window: the day after the latest
+/// recorded transaction, or the epoch for an empty journal.
+pub fn window_start(transactions: &[Located<Transaction>]) -> Date {
+    transactions
+        .iter()
+        .map(|lt| lt.value.date)
+        .max()
+        .map_or(Date::from_days(0), Date::next_day)
+}
+
+/// The occurrences of every rule in `[from, until)`, unbooked and in date
+/// order. `tagged` adds the [`TAG`] marker; `acc forecast --emit` leaves
+/// it off, since the entries it prints are meant to become real ones.
+pub fn forecast(
+    rules: &[Located<Recurring>],
+    from: Date,
+    until: Date,
+    tagged: bool,
+) -> Vec<Located<Transaction>> {
+    let mut out = Vec::new();
+    for rule in rules {
+        for date in rule.value.occurrences(from, until) {
+            out.push(Located {
+                file: rule.file.clone(),
+                line: rule.line,
+                value: occurrence(rule, date, tagged),
+            });
+        }
+    }
+    out.sort_by_key(|lt| lt.value.date);
+    out
+}
+
+fn occurrence(rule: &Located<Recurring>, date: Date, tagged: bool) -> Transaction {
+    let date_str = date.to_string();
+    let postings = rule
+        .value
+        .postings
+        .iter()
+        .map(|lp| {
+            let mut lp = lp.clone();
+            crate::resolver::substitute_date_vars(&mut lp.value.account, &date_str);
+            lp
+        })
+        .collect();
+    let mut tx = Transaction {
+        date,
+        aux_date: None,
+        state: State::Uncleared,
+        code: None,
+        description: rule.value.description.clone(),
+        postings,
+        comments: Vec::new(),
+        metadata: Metadata::default(),
+    };
+    if tagged {
+        let text = format!(":{}:", TAG);
+        tx.metadata.add_comment(&text);
+        tx.comments.push(Located {
+            file: rule.file.clone(),
+            line: rule.line,
+            value: Comment { text },
+        });
+    }
+    tx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projects_tagged_occurrences_after_the_last_transaction() {
+        let src = "\
+            ~ monthly from 2025-01-15 rent\n\
+            \texpenses:rent:$month  1000 EUR\n\
+            \tassets:bank\n\
+            2025-02-15 rent\n\
+            \texpenses:rent:02  1000 EUR\n\
+            \tassets:bank\n";
+        let journal = crate::testutil::load(src);
+
+        let from = window_start(&journal.transactions);
+        let txs = forecast(
+            &journal.recurring,
+            from,
+            Date::parse("2025-05-01").unwrap(),
+            true,
+        );
+        let got: Vec<(String, &str)> = txs
+            .iter()
+            .map(|lt| {
+                (
+                    lt.value.date.to_string(),
+                    lt.value.postings[0].value.account.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            got,
+            [
+                ("2025-03-15".to_string(), "expenses:rent:03"),
+                ("2025-04-15".to_string(), "expenses:rent:04")
+            ]
+        );
+        assert_eq!(txs[0].value.metadata.get(TAG), Some(""));
+        assert!(txs[0].value.postings[1].value.amount.is_none());
+        let booked = crate::booker::book(txs).unwrap();
+        assert!(booked[0].value.postings[1].value.amount.is_some());
+    }
+}
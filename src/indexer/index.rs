@@ -6,15 +6,36 @@
 //! are compared case-sensitively — `USD` and `usd` are distinct.
 //! Dates are stored as `u32` days-since-epoch so comparison is an
 //! integer op.
+//!
+//! `split` / `convert` directives are kept alongside: a rate dated on
+//! one side of a split is rescaled to the other, and a converted
+//! commodity is looked up through its successor (or predecessor), so a
+//! position's value runs continuously across the redenomination.
+//!
+//! A maximum rate age — `max-age N` under a `commodity`, else
+//! `--max-rate-age` — makes an edge whose `P` entry lies more than N days
+//! from the lookup day unusable, so the BFS routes around it. A rate a
+//! lookup had to route around, or failed on, is recorded for the report's
+//! warning ([`Index::stale`]).
 
 use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
-use std::sync::Arc;
+use std::fmt;
+use std::sync::{Arc, Mutex};
 
+use crate::date::Date;
 use crate::decimal::Decimal;
+use crate::parser::entry::Split;
 
 #[derive(Debug, Default)]
 pub struct Index {
     prices: HashMap<Arc<str>, HashMap<Arc<str>, BTreeMap<u32, Decimal>>>,
+    splits: Vec<Split>,
+    /// `max-age N` per commodity, in days.
+    max_ages: HashMap<String, u32>,
+    /// `--max-rate-age`: the limit for commodities without their own.
+    max_age: Option<u32>,
+    /// The rates a limit refused, per quoted pair: the oldest seen.
+    stale: Mutex<BTreeMap<(String, String), Stale>>,
 }
 
 impl Index {
@@ -53,6 +74,33 @@ impl Index {
             .insert(day, rate);
     }
 
+    /// Register a `split` / `convert` directive; see the module docs.
+    pub(super) fn add_split(&mut self, split: Split) {
+        self.splits.push(split);
+    }
+
+    /// The registered `split` / `convert` directives, in date order.
+    pub fn splits(&self) -> &[Split] {
+        &self.splits
+    }
+
+    /// Set the `max-age N` limits declared under `commodity` directives.
+    pub(crate) fn set_max_ages(&mut self, max_ages: HashMap<String, u32>) {
+        self.max_ages = max_ages;
+    }
+
+    /// `--max-rate-age DAYS`: the limit for every commodity without a
+    /// `max-age` of its own.
+    pub fn set_max_age(&mut self, days: Option<u32>) {
+        self.max_age = days;
+    }
+
+    /// The rates a maximum age kept lookups from using, one per `P` pair
+    /// (its oldest), by pair.
+    pub fn stale(&self) -> Vec<Stale> {
+        self.stale.lock().unwrap().values().cloned().collect()
+    }
+
     /// Rate for `from → to` at or before `date`. Uses BFS over the
     /// commodity graph so multi-hop paths (e.g. USD → CHF → EUR) work
     /// when direct edges are missing. Every stored edge is reversible
@@ -62,7 +110,127 @@ impl Index {
             return Some(Decimal::from(1));
         }
         let day = crate::date::Date::parse(date).ok()?.days();
-        if let Some(rate) = self.edge_rate(from, to, day) {
+        if self.splits.is_empty() {
+            return self.find_on(from, to, day);
+        }
+        // A converted commodity is priced through the symbol current on
+        // `day`: 1 `from` = `from_units` × `from'`, likewise for `to`.
+        let (from, from_units) = self.current_symbol(from, day);
+        let (to, to_units) = self.current_symbol(to, day);
+        let rate = self.find_on(from, to, day)?;
+        Some(from_units.mul_rounded(rate).div_rounded(to_units))
+    }
+
+    /// Mean of the `from → to` rate in force on each day of `[start,
+    /// end)` — the period-average rate, each day weighing the same and
+    /// looked up as [`find`](Self::find) would. `None` if any day has no
+    /// rate path, or the span is empty.
+    pub fn average(&self, from: &str, to: &str, start: Date, end: Date) -> Option<Decimal> {
+        if from == to {
+            return Some(Decimal::from(1));
+        }
+        let days = end.days().checked_sub(start.days()).filter(|n| *n > 0)?;
+        let mut sum = Decimal::zero();
+        for day in start.days()..end.days() {
+            sum += self.find(from, to, &Date::from_days(day).to_string())?;
+        }
+        Some(sum.div_rounded(Decimal::from(days as i64)))
+    }
+
+    /// How [`find`](Self::find) arrives at the `from → to` rate on `date`:
+    /// the same path, hop by hop, with the `P` entry each hop used. The
+    /// BFS mirrors `find_on` — same neighbour order, same rounding — so
+    /// the explained rate is the one `find` returns.
+    pub fn explain(&self, from: &str, to: &str, date: &str) -> Option<Explanation> {
+        let date = Date::parse(date).ok()?;
+        let day = date.days();
+        let mut explanation = Explanation {
+            from: from.to_string(),
+            to: to.to_string(),
+            date,
+            rate: Decimal::from(1),
+            from_as: None,
+            to_as: None,
+            hops: Vec::new(),
+        };
+        if from == to {
+            return Some(explanation);
+        }
+        let (priced_from, from_units) = self.current_symbol(from, day);
+        let (priced_to, to_units) = self.current_symbol(to, day);
+        let hops = self.route(priced_from, priced_to, day, true, &mut false)?;
+        let mut rate = Decimal::from(1);
+        for hop in &hops {
+            rate = rate.mul_rounded(hop.rate);
+        }
+        if !self.splits.is_empty() {
+            rate = from_units.mul_rounded(rate).div_rounded(to_units);
+        }
+        explanation.rate = rate;
+        explanation.hops = hops;
+        explanation.from_as = (priced_from != from).then(|| (priced_from.to_string(), from_units));
+        explanation.to_as = (priced_to != to).then(|| (priced_to.to_string(), to_units));
+        Some(explanation)
+    }
+
+    /// The hops of `find_on`'s path from `from` to `to` on `day` — or,
+    /// unless `fresh`, of the path it would take with no maximum age.
+    /// `refused` is set when a rate was passed over for its age.
+    fn route(&self, from: &str, to: &str, day: u32, fresh: bool, refused: &mut bool) -> Option<Vec<Hop>> {
+        if from == to {
+            return Some(Vec::new());
+        }
+        if let Some(edge) = self.edge(from, to, day, fresh, refused) {
+            return Some(vec![edge.hop(from, to)]);
+        }
+        let mut visited: HashSet<&str> = HashSet::new();
+        let mut parent: HashMap<&str, (&str, Edge)> = HashMap::new();
+        let mut queue: VecDeque<&str> = VecDeque::new();
+        visited.insert(from);
+        queue.push_back(from);
+        while let Some(current) = queue.pop_front() {
+            for next in self.neighbors(current) {
+                if visited.contains(next) {
+                    continue;
+                }
+                let Some(edge) = self.edge(current, next, day, fresh, refused) else {
+                    continue;
+                };
+                parent.insert(next, (current, edge));
+                if next == to {
+                    let mut hops = Vec::new();
+                    let mut at = to;
+                    while let Some((prev, edge)) = parent.get(at) {
+                        hops.push(edge.hop(prev, at));
+                        at = prev;
+                    }
+                    hops.reverse();
+                    return Some(hops);
+                }
+                visited.insert(next);
+                queue.push_back(next);
+            }
+        }
+        None
+    }
+
+    /// `find` on the symbols current on `day`. Only when a rate is passed
+    /// over for its age does it walk the unlimited path again, to record
+    /// what the lookup routed around.
+    fn find_on(&self, from: &str, to: &str, day: u32) -> Option<Decimal> {
+        let mut refused = false;
+        let rate = self.find_fresh(from, to, day, &mut refused);
+        if refused {
+            self.note_stale(from, to, day);
+        }
+        rate
+    }
+
+    fn find_fresh(&self, from: &str, to: &str, day: u32, refused: &mut bool) -> Option<Decimal> {
+        if from == to {
+            return Some(Decimal::from(1));
+        }
+        if let Some(rate) = self.edge_rate(from, to, day, refused) {
             return Some(rate);
         }
         let mut visited: HashSet<&str> = HashSet::new();
@@ -74,7 +242,7 @@ impl Index {
                 if visited.contains(next) {
                     continue;
                 }
-                let Some(edge) = self.edge_rate(current, next, day) else {
+                let Some(edge) = self.edge_rate(current, next, day, refused) else {
                     continue;
                 };
                 // mul_rounded because inverse-rate edges served by
@@ -116,26 +284,279 @@ impl Index {
         forward.into_iter().chain(reverse)
     }
 
-    fn edge_rate(&self, from: &str, to: &str, day: u32) -> Option<Decimal> {
+    fn edge_rate(&self, from: &str, to: &str, day: u32, refused: &mut bool) -> Option<Decimal> {
+        self.edge(from, to, day, true, refused).map(|e| e.rate)
+    }
+
+    /// The `from → to` edge in force on `day`: a stored `P` rate, else the
+    /// reciprocal of the stored `to → from` one. With `fresh`, a rate
+    /// older than the pair's maximum age is passed over and `refused` set.
+    fn edge(&self, from: &str, to: &str, day: u32, fresh: bool, refused: &mut bool) -> Option<Edge> {
         if let Some(dates) = self.prices.get(from).and_then(|m| m.get(to)) {
-            return latest_rate(dates, day);
+            let (key, quoted) = latest_rate(dates, day)?;
+            if !fresh || self.within_age(from, to, key, day) {
+                let rate = self.rescale(quoted, from, to, key, day);
+                return Some(Edge { rate, quoted, key, inverse: false, rescaled: rate != quoted });
+            }
+            *refused = true;
         }
         let reverse = self.prices.get(to).and_then(|m| m.get(from))?;
-        let rate = latest_rate(reverse, day)?;
-        Some(Decimal::from(1).div_rounded(rate))
+        let (key, quoted) = latest_rate(reverse, day)?;
+        if fresh && !self.within_age(from, to, key, day) {
+            *refused = true;
+            return None;
+        }
+        let rescaled = self.rescale(quoted, to, from, key, day);
+        Some(Edge {
+            rate: Decimal::from(1).div_rounded(rescaled),
+            quoted,
+            key,
+            inverse: true,
+            rescaled: rescaled != quoted,
+        })
+    }
+
+    /// The maximum age of an `a`–`b` rate: a side's own `max-age` (the
+    /// stricter, when both declare one), else `--max-rate-age`.
+    fn max_age_of(&self, a: &str, b: &str) -> Option<u32> {
+        match (self.max_ages.get(a), self.max_ages.get(b)) {
+            (Some(x), Some(y)) => Some(*x.min(y)),
+            (Some(x), None) | (None, Some(x)) => Some(*x),
+            (None, None) => self.max_age,
+        }
+    }
+
+    /// Whether a rate quoted on day `key` may value day `day`. The age is
+    /// the distance either way, so the earliest-entry fallback for a day
+    /// before any rate counts too.
+    fn within_age(&self, a: &str, b: &str, key: u32, day: u32) -> bool {
+        self.max_age_of(a, b).is_none_or(|limit| key.abs_diff(day) <= limit)
+    }
+
+    /// Record each stale hop on the path the lookup would take without a
+    /// maximum age — the rates it routed around or failed on; per pair,
+    /// the oldest refusal is kept.
+    fn note_stale(&self, from: &str, to: &str, day: u32) {
+        let Some(hops) = self.route(from, to, day, false, &mut false) else { return };
+        for hop in hops {
+            let key = hop.date.days();
+            let Some(limit) = self.max_age_of(&hop.from, &hop.to) else { continue };
+            let age = key.abs_diff(day);
+            if age <= limit {
+                continue;
+            }
+            let (base, quote) = if hop.inverse { (hop.to, hop.from) } else { (hop.from, hop.to) };
+            let stale = Stale { base, quote, date: hop.date, on: Date::from_days(day), age, max_age: limit };
+            let mut seen = self.stale.lock().unwrap();
+            let entry = seen.entry((stale.base.clone(), stale.quote.clone())).or_insert_with(|| stale.clone());
+            if stale.age > entry.age {
+                *entry = stale;
+            }
+        }
+    }
+
+    /// A `base → quote` rate quoted on day `key`, restated for `day`:
+    /// every split of either side between the two dates changes how many
+    /// units a unit then is now.
+    fn rescale(&self, rate: Decimal, base: &str, quote: &str, key: u32, day: u32) -> Decimal {
+        if self.splits.is_empty() {
+            return rate;
+        }
+        let base = self.units_then(base, key, day);
+        let quote = self.units_then(quote, key, day);
+        rate.mul_rounded(base).div_rounded(quote)
+    }
+
+    /// Units of `symbol` on day `key` that one unit on `day` stood for,
+    /// across its same-symbol splits: a 4:1 split between them makes a
+    /// share now a quarter share then.
+    fn units_then(&self, symbol: &str, key: u32, day: u32) -> Decimal {
+        let mut units = Decimal::from(1);
+        for split in self.splits.iter().filter(|s| s.from == symbol && s.to == symbol) {
+            let d = split.date.days();
+            if key < d && d <= day {
+                units = units.div_rounded(split.ratio());
+            } else if day < d && d <= key {
+                units = units.mul_rounded(split.ratio());
+            }
+        }
+        units
+    }
+
+    /// The symbol `symbol` is priced as on `day`, and how many of its
+    /// units one `symbol` is: a converted commodity on or after its
+    /// `convert` date is its successor (× ratio); a successor before that
+    /// date is its predecessor (÷ ratio). Chained conversions follow
+    /// through; the walk is bounded by the number of directives.
+    fn current_symbol<'a>(&'a self, symbol: &'a str, day: u32) -> (&'a str, Decimal) {
+        let mut current = symbol;
+        let mut units = Decimal::from(1);
+        for _ in 0..self.splits.len() {
+            let converts = self.splits.iter().filter(|s| s.from != s.to);
+            let step = converts
+                .clone()
+                .find(|s| s.from == current && s.date.days() <= day)
+                .map(|s| (s.to.as_str(), units.mul_rounded(s.ratio())))
+                .or_else(|| {
+                    converts
+                        .filter(|s| s.to == current && day < s.date.days())
+                        .map(|s| (s.from.as_str(), units.div_rounded(s.ratio())))
+                        .next()
+                });
+            match step {
+                Some((next, u)) => (current, units) = (next, u),
+                None => break,
+            }
+        }
+        (current, units)
     }
 }
 
-/// Latest rate with `date_key ≤ day`, or the earliest-known rate as a
-/// fallback if the requested day is before any stored entry.
-fn latest_rate(dates: &BTreeMap<u32, Decimal>, day: u32) -> Option<Decimal> {
+/// One graph edge as served on a day; see [`Index::edge`].
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    rate: Decimal,
+    quoted: Decimal,
+    key: u32,
+    inverse: bool,
+    rescaled: bool,
+}
+
+impl Edge {
+    fn hop(&self, from: &str, to: &str) -> Hop {
+        Hop {
+            from: from.to_string(),
+            to: to.to_string(),
+            rate: self.rate,
+            quoted: self.quoted,
+            date: Date::from_days(self.key),
+            inverse: self.inverse,
+            rescaled: self.rescaled,
+        }
+    }
+}
+
+/// One step of a conversion path, as [`Index::explain`] walked it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hop {
+    pub from: String,
+    pub to: String,
+    /// The rate applied: units of `to` per `from`.
+    pub rate: Decimal,
+    /// The rate as the `P` entry states it — `to → from` for an inverse hop.
+    pub quoted: Decimal,
+    /// Date of that `P` entry.
+    pub date: Date,
+    /// The entry is quoted the other way; `rate` is its reciprocal.
+    pub inverse: bool,
+    /// A `split` between the entry's date and the lookup day restated it.
+    pub rescaled: bool,
+}
+
+/// The path behind one [`Index::find`] result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub from: String,
+    pub to: String,
+    pub date: Date,
+    /// The rate `find` returns.
+    pub rate: Decimal,
+    /// The symbol `from` was priced as after a `convert`, and how many of
+    /// its units one `from` is.
+    pub from_as: Option<(String, Decimal)>,
+    /// Likewise for `to`.
+    pub to_as: Option<(String, Decimal)>,
+    /// Empty when `from` and `to` are the same commodity.
+    pub hops: Vec<Hop>,
+}
+
+impl fmt::Display for Hop {
+    /// `XMR → $ 150.2 (P 2024-06-14)`; an inverse hop shows the quoted
+    /// rate as `1/1.087`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} → {} ", self.from, self.to)?;
+        if self.rescaled {
+            let quoted = if self.inverse { format!("1/{}", plain(&self.quoted)) } else { plain(&self.quoted) };
+            write!(f, "{} (P {}, {} split-adjusted)", plain(&self.rate), self.date, quoted)
+        } else if self.inverse {
+            write!(f, "1/{} (P {})", plain(&self.quoted), self.date)
+        } else {
+            write!(f, "{} (P {})", plain(&self.quoted), self.date)
+        }
+    }
+}
+
+impl fmt::Display for Explanation {
+    /// The hops joined by `, `, then `= RATE` unless a single quoted rate
+    /// already states it.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((symbol, units)) = &self.from_as {
+            write!(f, "1 {} = {} {}, ", self.from, plain(units), symbol)?;
+        }
+        for (i, hop) in self.hops.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", hop)?;
+        }
+        if let Some((symbol, units)) = &self.to_as {
+            write!(f, ", 1 {} = {} {}", self.to, plain(units), symbol)?;
+        }
+        let stated = match self.hops.as_slice() {
+            [hop] => !hop.inverse,
+            _ => false,
+        };
+        if !stated || self.from_as.is_some() || self.to_as.is_some() {
+            write!(f, " = {}", plain(&self.rate))?;
+        }
+        Ok(())
+    }
+}
+
+/// A rate a maximum age refused: the `P` pair, its entry's date, the day
+/// it would have valued and how far apart they are.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stale {
+    pub base: String,
+    pub quote: String,
+    pub date: Date,
+    pub on: Date,
+    /// Days between `date` and `on`.
+    pub age: u32,
+    pub max_age: u32,
+}
+
+impl fmt::Display for Stale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let when = if self.date <= self.on { "before" } else { "after" };
+        write!(
+            f,
+            "{} → {} rate of {} is {} days {} {} (max age {}); not used",
+            self.base, self.quote, self.date, self.age, when, self.on, self.max_age
+        )
+    }
+}
+
+/// `value` without trailing zeros, at most 10 decimal places.
+pub(crate) fn plain(value: &Decimal) -> String {
+    let s = value.format_decimal(10);
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        s
+    }
+}
+
+/// Latest `(day, rate)` with `day ≤ day`, or the earliest-known entry as
+/// a fallback if the requested day is before any stored entry.
+fn latest_rate(dates: &BTreeMap<u32, Decimal>, day: u32) -> Option<(u32, Decimal)> {
     if dates.is_empty() {
         return None;
     }
-    if let Some((_, rate)) = dates.range(..=day).next_back() {
-        return Some(*rate);
+    if let Some((key, rate)) = dates.range(..=day).next_back() {
+        return Some((*key, *rate));
     }
-    dates.iter().next().map(|(_, rate)| *rate)
+    dates.iter().next().map(|(key, rate)| (*key, *rate))
 }
 
 #[cfg(test)]
@@ -173,4 +594,88 @@ mod tests {
         // (3.0) — proves the tie-break is the sort, not HashMap luck.
         assert!(expected.unwrap() < Decimal::from(3));
     }
+
+    #[test]
+    fn explain_walks_the_path_find_takes() {
+        // XMR → $ is quoted directly; € → $ only the other way round.
+        let day = |s: &str| Date::parse(s).unwrap();
+        let mut idx = Index::new();
+        let (xmr, usd, eur): (Arc<str>, Arc<str>, Arc<str>) =
+            (Arc::from("XMR"), Arc::from("$"), Arc::from("€"));
+        idx.add(xmr, usd.clone(), day("2024-06-14").days(), Decimal::parse("150").unwrap());
+        idx.add(eur, usd, day("2024-06-10").days(), Decimal::parse("1.25").unwrap());
+        let e = idx.explain("XMR", "€", "2024-06-15").unwrap();
+        assert_eq!(Some(e.rate), idx.find("XMR", "€", "2024-06-15"));
+        assert_eq!(e.rate, Decimal::from(120));
+        let path: Vec<(&str, &str, bool)> =
+            e.hops.iter().map(|h| (h.from.as_str(), h.to.as_str(), h.inverse)).collect();
+        assert_eq!(path, [("XMR", "$", false), ("$", "€", true)]);
+        assert_eq!(
+            e.to_string(),
+            "XMR → $ 150 (P 2024-06-14), $ → € 1/1.25 (P 2024-06-10) = 120"
+        );
+        assert!(idx.explain("XMR", "GBP", "2024-06-15").is_none());
+    }
+
+    #[test]
+    fn max_age_routes_around_a_stale_rate_and_records_it() {
+        // The direct XMR → $ rate is two years old; a fresh path runs
+        // through BTC.
+        let day = |s: &str| Date::parse(s).unwrap().days();
+        let mut idx = Index::new();
+        let (xmr, btc, usd): (Arc<str>, Arc<str>, Arc<str>) =
+            (Arc::from("XMR"), Arc::from("BTC"), Arc::from("$"));
+        idx.add(xmr.clone(), usd.clone(), day("2022-06-14"), Decimal::from(150));
+        idx.add(xmr, btc.clone(), day("2024-06-10"), Decimal::parse("0.002").unwrap());
+        idx.add(btc, usd, day("2024-06-10"), Decimal::from(60000));
+        assert_eq!(idx.find("XMR", "$", "2024-06-15"), Some(Decimal::from(150)));
+        assert!(idx.stale().is_empty());
+
+        idx.set_max_ages(HashMap::from([("XMR".to_string(), 30)]));
+        // A lookup that never reaches the old rate records nothing.
+        assert_eq!(idx.find("BTC", "$", "2024-06-15"), Some(Decimal::from(60000)));
+        assert!(idx.stale().is_empty());
+        assert_eq!(idx.find("XMR", "$", "2024-06-15"), Some(Decimal::from(120)));
+        let stale = idx.stale();
+        assert_eq!(stale.len(), 1);
+        assert_eq!((stale[0].base.as_str(), stale[0].quote.as_str(), stale[0].age), ("XMR", "$", 732));
+
+        // `--max-rate-age` covers the commodities without their own limit.
+        idx.set_max_age(Some(2));
+        assert_eq!(idx.find("XMR", "$", "2024-06-15"), None);
+    }
+
+    #[test]
+    fn fresh_direct_rate_records_no_stale_alternative() {
+        // € → XMR runs over the fresh € → $ and XMR → $ rates. The BFS
+        // looks at the two-year-old BTC → $ rate on the way, but the
+        // lookup never needs it: nothing to warn about.
+        let day = |s: &str| Date::parse(s).unwrap().days();
+        let mut idx = Index::new();
+        let (eur, xmr, btc, usd): (Arc<str>, Arc<str>, Arc<str>, Arc<str>) =
+            (Arc::from("€"), Arc::from("XMR"), Arc::from("BTC"), Arc::from("$"));
+        idx.add(eur, usd.clone(), day("2024-06-10"), Decimal::parse("1.25").unwrap());
+        idx.add(xmr, usd.clone(), day("2024-06-14"), Decimal::from(150));
+        idx.add(btc, usd, day("2022-06-10"), Decimal::from(20000));
+        idx.set_max_ages(HashMap::from([("BTC".to_string(), 30)]));
+        assert_eq!(idx.find("XMR", "$", "2024-06-15"), Some(Decimal::from(150)));
+        assert!(idx.find("€", "XMR", "2024-06-15").is_some());
+        assert!(idx.stale().is_empty());
+        assert_eq!(idx.find("BTC", "€", "2024-06-15"), None);
+        assert_eq!(idx.stale().len(), 1);
+    }
+
+    #[test]
+    fn average_weighs_each_day_of_the_span() {
+        // 0.90 for the first 10 days of a 30-day span, 1.20 for the rest:
+        // (10 × 0.90 + 20 × 1.20) / 30 = 1.10.
+        let day = |s: &str| Date::parse(s).unwrap();
+        let mut idx = Index::new();
+        let (usd, eur): (Arc<str>, Arc<str>) = (Arc::from("USD"), Arc::from("EUR"));
+        idx.add(usd.clone(), eur.clone(), day("2024-04-01").days(), Decimal::parse("0.90").unwrap());
+        idx.add(usd, eur, day("2024-04-11").days(), Decimal::parse("1.20").unwrap());
+        let avg = idx.average("USD", "EUR", day("2024-04-01"), day("2024-05-01"));
+        assert_eq!(avg, Some(Decimal::parse("1.10").unwrap()));
+        assert_eq!(idx.average("USD", "EUR", day("2024-04-01"), day("2024-04-01")), None);
+    }
 }
@@ -12,9 +12,9 @@
 
 pub mod index;
 
-pub use index::Index;
+pub use index::{Explanation, Hop, Index};
 
-use crate::parser::entry::Price;
+use crate::parser::entry::{Price, Split};
 use crate::parser::located::Located;
 
 /// Build an `Index` from resolved price directives. The input is
@@ -23,11 +23,20 @@ use crate::parser::located::Located;
 /// resulting lookup behaviour depends on stored values, not insertion
 /// order.
 pub fn index(prices: Vec<Located<Price>>) -> Index {
+    index_with_splits(prices, Vec::new())
+}
+
+/// [`index`], plus the `split` / `convert` directives, which rescale
+/// lookups across their dates.
+pub fn index_with_splits(prices: Vec<Located<Price>>, splits: Vec<Located<Split>>) -> Index {
     let mut db = Index::new();
     for located in prices {
         let Price { date, base, quote, rate, .. } = located.value;
         db.add(base, quote, date.days(), rate);
     }
+    for located in splits {
+        db.add_split(located.value);
+    }
     db
 }
 
@@ -40,7 +49,7 @@ mod tests {
     fn build(src: &str) -> Index {
         let entries = parser::parse(src).unwrap();
         let resolved = crate::resolver::resolve(entries).unwrap();
-        index(resolved.prices)
+        index_with_splits(resolved.prices, resolved.splits)
     }
 
     #[test]
@@ -126,4 +135,29 @@ mod tests {
         assert_eq!(db.find("USD", "EUR", "2024-06-16"), Some(Decimal::parse("0.92").unwrap()));
     }
 
+    #[test]
+    fn split_rescales_rates_across_its_date() {
+        let db = build("P 2024-06-01 AAPL USD 200\n2024-06-10 split AAPL 4:1\nP 2024-07-01 AAPL USD 52\n");
+        // Before the split a share is a whole pre-split share…
+        assert_eq!(db.find("AAPL", "USD", "2024-06-05"), Some(Decimal::from(200)));
+        // …after it, a quarter of one, until a post-split quote lands.
+        assert_eq!(db.find("AAPL", "USD", "2024-06-10"), Some(Decimal::from(50)));
+        assert_eq!(db.find("USD", "AAPL", "2024-06-10"), Some(Decimal::parse("0.02").unwrap()));
+        assert_eq!(db.find("AAPL", "USD", "2024-07-01"), Some(Decimal::from(52)));
+        // A pre-split day falling back to the post-split quote scales up.
+        assert_eq!(db.find("AAPL", "USD", "2024-06-09"), Some(Decimal::from(200)));
+        let late = build("2024-06-10 split AAPL 4:1\nP 2024-07-01 AAPL USD 52\n");
+        assert_eq!(late.find("AAPL", "USD", "2024-06-01"), Some(Decimal::from(208)));
+    }
+
+    #[test]
+    fn convert_prices_each_side_through_the_other() {
+        let db = build("P 2024-06-01 OLD USD 2\n2024-06-10 convert OLD NEW 1000\nP 2024-07-01 NEW USD 0.003\n");
+        // The old token after the date is 1000 of the new one…
+        assert_eq!(db.find("OLD", "USD", "2024-07-02"), Some(Decimal::from(3)));
+        // …and the new one before it is a thousandth of the old.
+        assert_eq!(db.find("NEW", "USD", "2024-06-05"), Some(Decimal::parse("0.002").unwrap()));
+        assert_eq!(db.find("USD", "NEW", "2024-06-05"), Some(Decimal::from(500)));
+        assert_eq!(db.find("OLD", "NEW", "2024-06-05"), Some(Decimal::from(1000)));
+    }
 }
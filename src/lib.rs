@@ -2,11 +2,14 @@
 //!
 //! ```text
 //! load:    parser → resolver → booker → indexer ─────────────────────► Journal
+//! project: forecaster → booker                                          (--forecast)
 //! enrich:  expander → realizer → lotter → translator → revaluator      (journal-global)
 //! report:  filter → rebalancer → sorter → commands                     (CLI-driven)
 //! ```
 //!
-//! [`load`] builds a validated `Journal`. [`pipeline::enrich`] runs the
+//! [`load`] builds a validated `Journal`. Under `--forecast`,
+//! [`pipeline::forecast`] adds the projected occurrences of recurring
+//! rules before anything else sees it. [`pipeline::enrich`] runs the
 //! journal-global phases that must see every transaction (realizer and
 //! translator only under `-X`; the revaluator only under `-X` with
 //! `--unrealized`; lotter needs capital accounts). The report phases are
@@ -19,6 +22,7 @@ pub mod decimal;
 pub mod error;
 pub mod expander;
 pub mod filter;
+pub mod forecaster;
 pub mod indexer;
 pub mod loader;
 pub mod lotter;
@@ -32,6 +36,8 @@ pub mod sorter;
 pub mod translator;
 
 pub(crate) mod i256;
+#[cfg(test)]
+pub(crate) mod testutil;
 
 pub use error::Error;
 pub use loader::{load, load_selective, Journal, LoadError};
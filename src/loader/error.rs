@@ -11,6 +11,11 @@ use crate::error;
 pub enum LoadError {
     Io { path: String, source: std::io::Error },
     Parse { path: String, source: ParseError },
+    /// A failure inside a file pulled in by `include`. `path` / `line`
+    /// locate the `include` directive, `source` is the error in the
+    /// included file — itself another `Include` when the file was reached
+    /// through several levels, so the whole chain is kept.
+    Include { path: String, line: usize, source: Box<LoadError> },
     Resolve(ResolveError),
     Book(BookError),
 }
@@ -22,6 +27,12 @@ impl fmt::Display for LoadError {
             LoadError::Parse { path, source } => {
                 error::render_at_line(f, path, source.line, &source.message)
             }
+            // Innermost error first, then one "included from" excerpt per
+            // level, outward to the file named on the command line.
+            LoadError::Include { path, line, source } => {
+                write!(f, "{}\n\n", source)?;
+                error::render_range(f, path, *line, *line, "included from here")
+            }
             LoadError::Resolve(e) => write!(f, "{}", e),
             LoadError::Book(e) => write!(f, "{}", e),
         }
@@ -0,0 +1,146 @@
+//! `include` path resolution.
+//!
+//! An `include PATH` directive names a file relative to the file that
+//! contains it — never relative to the working directory — so a journal
+//! tree loads the same no matter where `acc` is started from. An absolute
+//! path is taken as-is.
+//!
+//! The last path components may carry the wildcards `*` (any run of
+//! characters) and `?` (exactly one character), in any component:
+//! `include 2024/*.ledger`, `include */bank-??.ledger`. Wildcards never
+//! cross a `/`, and a leading `.` must be matched explicitly, so hidden
+//! files stay out of `*`. Matches come back sorted, which keeps the load
+//! order — and with it every report — deterministic.
+
+use std::path::{Component, Path, PathBuf};
+
+/// The files `include PATTERN` in `from` pulls in.
+///
+/// A plain path resolves to exactly that one file; whether it exists is
+/// left to the read, so a missing file fails with the usual I/O error. A
+/// glob must match at least one file. The including file itself is never
+/// a glob match — `include *.ledger` next to `main.ledger` means "my
+/// siblings", not "myself again".
+pub fn expand(from: &Path, pattern: &str) -> Result<Vec<PathBuf>, String> {
+    let base = from.parent().unwrap_or(Path::new(""));
+    let target = base.join(pattern);
+    if !has_wildcard(pattern) {
+        return Ok(vec![target]);
+    }
+
+    let mut candidates: Vec<PathBuf> = vec![PathBuf::new()];
+    for component in target.components() {
+        let part = component.as_os_str().to_string_lossy();
+        match component {
+            Component::Normal(_) if has_wildcard(&part) => {
+                let mut next = Vec::new();
+                for dir in &candidates {
+                    next.extend(matching_children(dir, &part));
+                }
+                candidates = next;
+            }
+            _ => {
+                for c in &mut candidates {
+                    c.push(component.as_os_str());
+                }
+            }
+        }
+    }
+
+    let own = from.canonicalize().ok();
+    let files: Vec<PathBuf> = candidates
+        .into_iter()
+        .filter(|p| p.is_file())
+        .filter(|p| own.is_none() || p.canonicalize().ok() != own)
+        .collect();
+    if files.is_empty() {
+        return Err(format!("include: no files match `{}`", pattern));
+    }
+    Ok(files)
+}
+
+fn has_wildcard(s: &str) -> bool {
+    s.contains(['*', '?'])
+}
+
+/// Entries of `dir` whose name matches the single-component `pattern`,
+/// sorted by name. An unreadable directory has no matches.
+fn matching_children(dir: &Path, pattern: &str) -> Vec<PathBuf> {
+    let read_from = if dir.as_os_str().is_empty() { Path::new(".") } else { dir };
+    let Ok(entries) = std::fs::read_dir(read_from) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| !name.starts_with('.') || pattern.starts_with('.'))
+        .filter(|name| wildcard_match(pattern, name))
+        .collect();
+    names.sort();
+    names.into_iter().map(|n| dir.join(n)).collect()
+}
+
+/// Match `name` against a pattern of literal characters, `*` and `?`.
+/// Iterative with single-star backtracking: on a mismatch, retry from the
+/// last `*` with it swallowing one more character.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let n: Vec<char> = name.chars().collect();
+    let (mut pi, mut ni) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ni < n.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == n[ni]) {
+            pi += 1;
+            ni += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ni));
+            pi += 1;
+        } else if let Some((sp, sn)) = star {
+            pi = sp + 1;
+            ni = sn + 1;
+            star = Some((sp, sn + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards_match_within_a_name() {
+        assert!(wildcard_match("*.ledger", "bank.ledger"));
+        assert!(wildcard_match("bank-??.ledger", "bank-03.ledger"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(wildcard_match("a*b*c", "aXXbYYc"));
+        assert!(!wildcard_match("*.ledger", "bank.ledger.tmp"));
+        assert!(!wildcard_match("bank-??.ledger", "bank-3.ledger"));
+    }
+
+    #[test]
+    fn plain_path_resolves_relative_to_the_including_file() {
+        let got = expand(Path::new("books/main.ledger"), "2024/jan.ledger").unwrap();
+        assert_eq!(got, vec![PathBuf::from("books/2024/jan.ledger")]);
+        let got = expand(Path::new("books/main.ledger"), "/etc/acc.ledger").unwrap();
+        assert_eq!(got, vec![PathBuf::from("/etc/acc.ledger")]);
+    }
+
+    #[test]
+    fn glob_expands_sorted_and_skips_the_includer() {
+        let dir = std::env::temp_dir().join(format!("acc-include-test-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        for name in ["main.ledger", "b.ledger", "a.ledger", ".hidden.ledger", "sub/c.ledger"] {
+            std::fs::write(dir.join(name), "").unwrap();
+        }
+        let main = dir.join("main.ledger");
+        let got = expand(&main, "*.ledger").unwrap();
+        assert_eq!(got, vec![dir.join("a.ledger"), dir.join("b.ledger")]);
+        let got = expand(&main, "s*/*.ledger").unwrap();
+        assert_eq!(got, vec![dir.join("sub/c.ledger")]);
+        assert!(expand(&main, "*.csv").is_err());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
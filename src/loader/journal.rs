@@ -15,6 +15,13 @@ use crate::parser::transaction::Transaction;
 pub struct Journal {
     pub transactions: Vec<Located<Transaction>>,
     pub prices: Index,
+    /// Budgeted amounts: the occurrences of `~~` budget blocks, never
+    /// booked. Read by `acc budget` only.
+    pub budget: Vec<Located<Transaction>>,
+    /// Recurring rules from `~ EVERY from …` blocks. Only
+    /// [`pipeline::forecast`](crate::pipeline::forecast) turns them into
+    /// transactions.
+    pub recurring: Vec<Located<crate::parser::Recurring>>,
     pub slippage_gain: Option<String>,
     pub slippage_loss: Option<String>,
     /// Account for positive Currency Translation Adjustments.
@@ -29,6 +36,16 @@ pub struct Journal {
     /// declared for the lot/capital-gains phase to run.
     pub capital_gain: Option<String>,
     pub capital_loss: Option<String>,
+    /// Optional short-/long-term split of the capital pair, declared via
+    /// `capital gain short` / `capital gain long [DAYS]` / `capital loss
+    /// short` / `capital loss long [DAYS]`. Each one undeclared falls back
+    /// to the plain pair; `long_term_days` defaults to
+    /// [`LONG_TERM_DAYS`](crate::lotter::LONG_TERM_DAYS).
+    pub capital_gain_short: Option<String>,
+    pub capital_gain_long: Option<String>,
+    pub capital_loss_short: Option<String>,
+    pub capital_loss_long: Option<String>,
+    pub long_term_days: Option<u32>,
     /// Unrealized mark-to-market accounts, declared via
     /// `account NAME / holding gain` / `holding loss`. Both must
     /// be declared for the `--unrealized` revaluator phase to run.
@@ -54,6 +71,22 @@ pub struct Journal {
     pub labels: crate::resolver::LabelSet,
     pub labels_balance: crate::resolver::LabelSet,
     pub labels_register: crate::resolver::LabelSet,
+    /// Lot booking methods from `account NAME / booking METHOD`, for
+    /// the lotter. See [`Booking::for_account`](crate::lotter::Booking::for_account).
+    pub bookings: crate::resolver::Bookings,
+    /// Account classes from `account NAME / type CLASS`, for the `bs` /
+    /// `is` statements. See [`AccountTypes::get`](crate::resolver::AccountTypes::get).
+    pub account_types: crate::resolver::AccountTypes,
+    /// Counter-party sections from `account NAME / cashflow SECTION`, for
+    /// `acc cashflow`.
+    pub cashflow_sections: crate::resolver::CashflowSections,
+    /// Lots still open after the lotter phase, by account, commodity and
+    /// acquisition date. Empty until [`pipeline::enrich`](crate::pipeline::enrich)
+    /// runs, and whenever the capital accounts aren't declared.
+    pub lots: Vec<crate::lotter::OpenLot>,
+    /// Every lot a disposal closed, in disposal order — the `gains`
+    /// report's rows. Filled alongside [`Self::lots`].
+    pub realized: Vec<crate::lotter::RealizedLot>,
 }
 
 /// Which view is asking for a label — selects the view-specific set that
@@ -1,8 +1,9 @@
 //! Loader phase.
 //!
-//! Orchestrates the full pipeline end-to-end: reads the input files,
-//! runs every earlier phase in order, and assembles a [`Journal`] for
-//! downstream report commands.
+//! Orchestrates the full pipeline end-to-end: reads the input files
+//! (following their `include` directives, see [`include`]), runs every
+//! earlier phase in order, and assembles a [`Journal`] for downstream
+//! report commands.
 //!
 //! ```text
 //! files ─► parser ─► resolver ─┬─► booker  ─┐
@@ -12,13 +13,14 @@
 //! ```
 
 pub mod error;
+pub mod include;
 pub mod journal;
 
 pub use error::LoadError;
 pub use journal::{Journal, LabelView};
 
 use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use crate::parser::entry::Entry;
@@ -77,8 +79,9 @@ where
 /// Resolve → book → index a parsed entry stream into a `Journal`.
 fn finish_load(entries: Vec<Located<Entry>>) -> Result<Journal, LoadError> {
     let resolved = resolver::resolve(entries)?;
-    let transactions = booker::book(resolved.transactions)?;
-    let prices = indexer::index(resolved.prices);
+    let transactions = booker::book_with_splits(resolved.transactions, &resolved.splits, &resolved.bookings)?;
+    let mut prices = indexer::index_with_splits(resolved.prices, resolved.splits.clone());
+    prices.set_max_ages(resolved.max_ages);
     let mut precisions = precisions_per_commodity(&transactions);
     // Explicit `precision N` under `commodity` directives wins over
     // whatever the posting amounts happened to contain. Users pin
@@ -91,12 +94,19 @@ fn finish_load(entries: Vec<Located<Entry>>) -> Result<Journal, LoadError> {
     Ok(Journal {
         transactions,
         prices,
+        budget: resolved.budget,
+        recurring: resolved.recurring,
         slippage_gain: resolved.slippage_gain,
         slippage_loss: resolved.slippage_loss,
         cta_gain: resolved.cta_gain,
         cta_loss: resolved.cta_loss,
         capital_gain: resolved.capital_gain,
         capital_loss: resolved.capital_loss,
+        capital_gain_short: resolved.capital_gain_short,
+        capital_gain_long: resolved.capital_gain_long,
+        capital_loss_short: resolved.capital_loss_short,
+        capital_loss_long: resolved.capital_loss_long,
+        long_term_days: resolved.long_term_days,
         holding_gain: resolved.holding_gain,
         holding_loss: resolved.holding_loss,
         precisions,
@@ -105,6 +115,11 @@ fn finish_load(entries: Vec<Located<Entry>>) -> Result<Journal, LoadError> {
         labels: resolved.labels,
         labels_balance: resolved.labels_balance,
         labels_register: resolved.labels_register,
+        bookings: resolved.bookings,
+        account_types: resolved.account_types,
+        cashflow_sections: resolved.cashflow_sections,
+        lots: Vec::new(),
+        realized: Vec::new(),
     })
 }
 
@@ -138,6 +153,21 @@ fn needed_commodities(
                     }
                 }
             }
+            // Periodic blocks become transactions (or budget amounts) too,
+            // and recurring ones under `--forecast`.
+            Entry::Periodic { postings, .. } | Entry::Recurring(crate::parser::Recurring { postings, .. }) => {
+                for lp in postings {
+                    if let Some(a) = &lp.value.amount {
+                        needed.insert(a.commodity.clone());
+                    }
+                }
+            }
+            // A redenomination values the old holding through the new
+            // symbol's prices (and back), so both sides are needed.
+            Entry::Split(split) => {
+                needed.insert(split.from.clone());
+                needed.insert(split.to.clone());
+            }
             Entry::Commodity { symbol, aliases, .. } => {
                 for a in aliases {
                     alias_pairs.push((symbol.clone(), a.clone()));
@@ -411,21 +441,123 @@ fn bump(map: &mut HashMap<String, usize>, a: &Amount) {
     }
 }
 
-/// Read every file and concatenate their parsed entries. Files are
-/// read and parsed in parallel via `rayon`; the final `Vec` still
-/// preserves the input file order (and source order within each
-/// file), because `par_iter().collect()` is order-preserving.
+/// Read every file and concatenate their parsed entries, with every
+/// `include` directive replaced by the entries of the file(s) it names.
+/// Files are read and parsed in parallel via `rayon`; the final `Vec`
+/// still preserves the input file order (and source order within each
+/// file, an included file sitting where its `include` was), because
+/// `par_iter().collect()` is order-preserving.
 fn read_and_parse<P>(files: &[P]) -> Result<Vec<Located<Entry>>, LoadError>
 where
     P: AsRef<Path> + Sync,
 {
+    Ok(read_tree(files)?.entries)
+}
+
+/// Every file the journal consists of: the given files plus everything
+/// they reach through `include`, each once, in load order. Lets the
+/// commands that edit files in place (`format`, `rename`) treat the tree
+/// rooted at one entry file as a single journal.
+pub fn journal_files<P>(files: &[P]) -> Result<Vec<PathBuf>, LoadError>
+where
+    P: AsRef<Path> + Sync,
+{
+    Ok(read_tree(files)?.files)
+}
+
+/// The include tree walked from a set of root files.
+#[derive(Default)]
+struct Tree {
+    entries: Vec<Located<Entry>>,
+    files: Vec<PathBuf>,
+    /// Canonical paths already loaded. A file reached twice — listed with
+    /// `-f` *and* included, or included from two places — loads once, so
+    /// its transactions are never doubled.
+    seen: HashSet<PathBuf>,
+}
+
+fn read_tree<P>(files: &[P]) -> Result<Tree, LoadError>
+where
+    P: AsRef<Path> + Sync,
+{
+    let mut tree = Tree::default();
+    let roots: Vec<&Path> = files.iter().map(|f| f.as_ref()).collect();
+    for (file, entries) in roots.iter().zip(read_and_parse_each(&roots)?) {
+        let key = canonical(file);
+        if !tree.seen.insert(key.clone()) {
+            continue;
+        }
+        tree.files.push(file.to_path_buf());
+        let mut chain = vec![(key, file.display().to_string())];
+        splice_includes(file, entries, &mut chain, &mut tree)?;
+    }
+    Ok(tree)
+}
+
+/// Move `entries` (parsed from `file`) into `tree`, loading the target of
+/// each `include` in its place, depth-first. `chain` is the stack of files
+/// currently being included — a target already on it is a cycle. An error
+/// inside an included file is wrapped in `LoadError::Include` at the
+/// directive that reached it, one wrap per level.
+fn splice_includes(
+    file: &Path,
+    entries: Vec<Located<Entry>>,
+    chain: &mut Vec<(PathBuf, String)>,
+    tree: &mut Tree,
+) -> Result<(), LoadError> {
+    for located in entries {
+        let Entry::Include(pattern) = &located.value else {
+            tree.entries.push(located);
+            continue;
+        };
+        let at_directive = |message: String| LoadError::Parse {
+            path: located.file.to_string(),
+            source: parser::ParseError::new(located.line, 1, message),
+        };
+        let targets = include::expand(file, pattern).map_err(at_directive)?;
+        for target in &targets {
+            let key = canonical(target);
+            if let Some(pos) = chain.iter().position(|(k, _)| *k == key) {
+                let cycle: Vec<&str> = chain[pos..]
+                    .iter()
+                    .map(|(_, shown)| shown.as_str())
+                    .chain([chain[pos].1.as_str()])
+                    .collect();
+                return Err(at_directive(format!("include cycle: {}", cycle.join(" → "))));
+            }
+        }
+        let fresh: Vec<&Path> = targets
+            .iter()
+            .filter(|t| tree.seen.insert(canonical(t)))
+            .map(PathBuf::as_path)
+            .collect();
+        let wrap = |e: LoadError| LoadError::Include {
+            path: located.file.to_string(),
+            line: located.line,
+            source: Box::new(e),
+        };
+        for (target, parsed) in fresh.iter().zip(read_and_parse_each(&fresh).map_err(wrap)?) {
+            tree.files.push(target.to_path_buf());
+            chain.push((canonical(target), target.display().to_string()));
+            splice_includes(target, parsed, chain, tree).map_err(wrap)?;
+            chain.pop();
+        }
+    }
+    Ok(())
+}
+
+/// The identity of a file for cycle and duplicate detection. Falls back to
+/// the path as given when it cannot be canonicalized (stdin's `-`, or a
+/// missing file whose read is about to fail anyway).
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Read and parse `files` in parallel, one entry list per file, in order.
+fn read_and_parse_each(files: &[&Path]) -> Result<Vec<Vec<Located<Entry>>>, LoadError> {
     use rayon::prelude::*;
 
-    let per_file: Result<Vec<Vec<Located<Entry>>>, LoadError> = files
-        .par_iter()
-        .map(|file| read_and_parse_one(file.as_ref()))
-        .collect();
-    Ok(per_file?.into_iter().flatten().collect())
+    files.par_iter().map(|file| read_and_parse_one(file)).collect()
 }
 
 fn read_and_parse_one(file: &Path) -> Result<Vec<Located<Entry>>, LoadError> {
@@ -592,6 +724,75 @@ mod tests {
         });
     }
 
+    #[test]
+    fn include_splices_the_file_in_place() {
+        // The included file is resolved next to the includer and its
+        // entries land where the directive was — the commodity alias is
+        // declared before the transaction that uses it.
+        let main = "include inc-decl.ledger\n2024-06-15 * Coffee\n    expenses:food  5 USD\n    assets:cash\n";
+        let decl = "commodity $\n    alias USD\n";
+        with_tmp("inc-decl", decl, |_| {
+            with_tmp("inc-main", main, |path| {
+                let journal = load(&[path]).unwrap();
+                let p = &journal.transactions[0].value.postings[0].value;
+                assert_eq!(p.amount.as_ref().unwrap().commodity, "$");
+                assert_eq!(journal_files(&[path]).unwrap().len(), 2);
+            });
+        });
+    }
+
+    #[test]
+    fn include_reached_twice_loads_once() {
+        let main = "include inc-once.ledger\ninclude inc-once.ledger\n";
+        let once = "2024-06-15 * Coffee\n    expenses:food  5 USD\n    assets:cash\n";
+        with_tmp("inc-once", once, |inc| {
+            with_tmp("inc-twice", main, |path| {
+                let journal = load(&[path, inc]).unwrap();
+                assert_eq!(journal.transactions.len(), 1);
+            });
+        });
+    }
+
+    #[test]
+    fn include_cycle_names_the_chain() {
+        with_tmp("cyc-a", "include cyc-b.ledger\n", |a| {
+            with_tmp("cyc-b", "include cyc-a.ledger\n", |_| {
+                let err = load(&[a]).unwrap_err();
+                let LoadError::Include { line, source, .. } = &err else {
+                    panic!("expected Include error, got {:?}", err);
+                };
+                assert_eq!(*line, 1);
+                let LoadError::Parse { source, .. } = source.as_ref() else {
+                    panic!("expected Parse error inside, got {:?}", source);
+                };
+                assert!(source.message.starts_with("include cycle:"));
+                assert!(source.message.contains("cyc-a.ledger → "));
+            });
+        });
+    }
+
+    #[test]
+    fn error_in_included_file_keeps_the_include_site() {
+        with_tmp("bad-inner", "2024-06-15 * X\n    a  5 USD\n    b  -3 USD\n", |_| {
+            with_tmp("bad-outer", "; top\ninclude bad-inner.ledger\n", |outer| {
+                let err = load(&[outer]).unwrap_err();
+                // Book errors surface after the whole tree is loaded and
+                // carry the inner file themselves; parse / I/O errors are
+                // wrapped at the include site.
+                assert!(matches!(err, LoadError::Book(_)));
+            });
+            with_tmp("io-outer", "include missing-file.ledger\n", |outer| {
+                let err = load(&[outer]).unwrap_err();
+                match err {
+                    LoadError::Include { line: 1, source, .. } => {
+                        assert!(matches!(*source, LoadError::Io { .. }));
+                    }
+                    other => panic!("expected Include error, got {:?}", other),
+                }
+            });
+        });
+    }
+
     #[test]
     fn errors_on_missing_file() {
         let err = load(&[Path::new("/this/does/not/exist.ledger")]).unwrap_err();
@@ -0,0 +1,136 @@
+//! Lot booking methods — which open lots a disposal closes.
+//!
+//! Declared per account with an `account NAME / booking METHOD`
+//! sub-directive. Like `type` and `cashflow`, a declaration covers the
+//! account and every account below it
+//! ([`AccountMap`](crate::resolver::AccountMap)); the deepest declared
+//! ancestor wins, and an account with no declaration books FIFO.
+//!
+//! - `fifo`     — oldest lot first (the default).
+//! - `lifo`     — newest lot first.
+//! - `hifo`     — highest per-unit cost first, oldest first on a tie.
+//! - `average`  — every lot carries the position's average cost; lots
+//!   still close oldest first, so each keeps its own acquisition date.
+//! - `specific` — the disposal leg names its lot with `{cost}` and/or
+//!   `[date]`; lots that match close first (oldest first), then any
+//!   remainder falls back to FIFO. A name no open lot matches is an
+//!   error. The `{cost}` only selects: the leg still balances at its
+//!   `@` price and the lotter books the gain, as for any other method.
+
+use std::fmt;
+
+use crate::date::Date;
+use crate::decimal::Decimal;
+use crate::indexer::index::plain;
+use crate::parser::posting::Posting;
+use crate::resolver::Bookings;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Booking {
+    #[default]
+    Fifo,
+    Lifo,
+    Hifo,
+    Average,
+    Specific,
+}
+
+impl Booking {
+    /// Parse a method name (case-insensitive). `None` for anything else.
+    pub fn parse(s: &str) -> Option<Booking> {
+        match s.to_ascii_lowercase().as_str() {
+            "fifo" => Some(Booking::Fifo),
+            "lifo" => Some(Booking::Lifo),
+            "hifo" => Some(Booking::Hifo),
+            "average" => Some(Booking::Average),
+            "specific" => Some(Booking::Specific),
+            _ => None,
+        }
+    }
+
+    /// The method that applies to `account`: its own declaration, else
+    /// the deepest declared parent's, else FIFO.
+    pub fn for_account(bookings: &Bookings, account: &str) -> Booking {
+        bookings.get(account).unwrap_or_default()
+    }
+}
+
+/// Whether `p`'s `{cost}` / `[date]` names the lot it closes rather than
+/// booking its cost basis: a disposal from an account booking `specific`.
+pub fn selects_lot(bookings: &Bookings, p: &Posting) -> bool {
+    p.amount.as_ref().is_some_and(|a| a.value.is_negative())
+        && Booking::for_account(bookings, &p.account) == Booking::Specific
+}
+
+impl fmt::Display for Booking {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Booking::Fifo => "fifo",
+            Booking::Lifo => "lifo",
+            Booking::Hifo => "hifo",
+            Booking::Average => "average",
+            Booking::Specific => "specific",
+        })
+    }
+}
+
+/// The lot a `specific` disposal names: its `{cost}` (per unit, in the
+/// cost commodity) and/or `[date]`. A field left `None` matches any lot.
+#[derive(Debug, Default)]
+pub(super) struct Selector {
+    pub cost: Option<(Decimal, String)>,
+    pub date: Option<Date>,
+}
+
+impl Selector {
+    pub(super) fn matches(&self, cost: Decimal, commodity: &str, date: Date) -> bool {
+        let cost_ok = match &self.cost {
+            // A cost written in another commodity than the lot's (e.g. the
+            // booked USD cost of a lot tracked at its EUR market value under
+            // `-X EUR`) can't be compared; it doesn't narrow the match.
+            Some((c, com)) => com != commodity || *c == cost,
+            None => true,
+        };
+        cost_ok && self.date.is_none_or(|d| d == date)
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.cost.is_none() && self.date.is_none()
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cost = self.cost.as_ref().map(|(c, com)| format!("{{{} {}}}", plain(c), com));
+        let date = self.date.map(|d| format!("[{}]", d));
+        let parts: Vec<String> = cost.into_iter().chain(date).collect();
+        f.write_str(&parts.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_method() {
+        for m in ["fifo", "lifo", "hifo", "average", "specific"] {
+            assert_eq!(Booking::parse(m).unwrap().to_string(), m);
+        }
+        assert_eq!(Booking::parse("HIFO"), Some(Booking::Hifo));
+        assert_eq!(Booking::parse("fofi"), None);
+    }
+
+    #[test]
+    fn deepest_declared_parent_wins() {
+        let bookings = Bookings {
+            declared: std::collections::HashMap::from([
+                ("assets:broker".to_string(), Booking::Average),
+                ("assets:broker:tax".to_string(), Booking::Hifo),
+            ]),
+        };
+        assert_eq!(Booking::for_account(&bookings, "assets:broker:aapl"), Booking::Average);
+        assert_eq!(Booking::for_account(&bookings, "assets:broker:tax:btc"), Booking::Hifo);
+        assert_eq!(Booking::for_account(&bookings, "assets:brokerage"), Booking::Fifo);
+    }
+}
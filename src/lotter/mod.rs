@@ -1,10 +1,12 @@
-//! Lotter phase — realized capital gains via FIFO lot tracking.
+//! Lotter phase — realized capital gains via lot tracking.
 //!
 //! Runs whenever the journal declares both a `capital gain` and a
 //! `capital loss` account. It walks transactions chronologically and
-//! tracks a per-(account, commodity) FIFO queue of lots: an exchange
-//! posting that *acquires* a commodity opens a lot, a posting that
-//! *disposes* of it closes lots front-to-back and realizes the gain.
+//! tracks the open lots per (account, commodity): an exchange posting
+//! that *acquires* a commodity opens a lot, a posting that *disposes* of
+//! it closes lots and realizes the gain. Which lots a disposal closes is
+//! the account's [booking method](booking) — FIFO unless the account
+//! declares another.
 //!
 //! ## With `-X TARGET`: the holding-period market move
 //!
@@ -31,8 +33,56 @@
 //! Natively there is no market to compare against, so the lotter falls
 //! back to the **booked** (trade) rate: a lot opens at its booked cost in
 //! the counter-commodity, and a disposal realizes `proceeds − cost` as a
-//! single `capital` posting. Mixed-currency native disposals (bought in
-//! EUR, sold in USD) can't be netted and are skipped; they need `-X`.
+//! single `capital` posting. The counter-commodity itself — what a trade
+//! is paid or received in — is that trade's money and opens no lot, so
+//! spending the proceeds of a sale later realizes nothing. Mixed-currency
+//! native disposals (bought in EUR, sold in USD) can't be netted and are
+//! skipped; they need `-X`.
+//!
+//! ## Transfers between own accounts
+//!
+//! A single-commodity transaction between two balance-sheet accounts
+//! (BTC from an exchange to a cold wallet) is no exchange and realizes
+//! nothing: the lots leave the sender — in its booking order — and join
+//! the receiver with their original cost and acquisition date. Both legs
+//! are split per lot with `{cost} [lot-date]`, so under `-X` they move at
+//! cost and the translator sees no drift on them; the gain is realized
+//! once, when the commodity leaves the books. This is the same shape the
+//! [translator](crate::translator) treats as pass-through.
+//!
+//! A balance-sheet account is one declared `type asset`, `liability` or
+//! `equity` — or, undeclared, one under an `assets`, `liabilities` or
+//! `equity` root. Whatever such a transaction sends anywhere else (BTC
+//! spent on `expenses:food`, a network fee) leaves the books: those lots
+//! are disposed of at the commodity's market value on the day (price DB;
+//! in the lot's cost commodity natively) and realize their gain like a
+//! sale. The receiving leg carries that value as its `@` price.
+//!
+//! ## Splits and conversions
+//!
+//! The swap the booker generates for a `split` / `convert` directive
+//! realizes nothing either: every lot of the old commodity becomes a lot
+//! of the new one, its quantity scaled by the ratio and its per-unit cost
+//! divided by it, same total cost and acquisition date. The swap legs are
+//! split per lot like a transfer's.
+//!
+//! ## Carried-forward lots
+//!
+//! A hand-written `{cost}` normally means the user books the gain, so the
+//! lotter opens no lot for it. The one exception is an acquisition that
+//! also names its `[lot-date]` — the shape `acc close` writes opening
+//! balances in: it re-opens the lot at that cost and acquisition date, so
+//! a journal split per year keeps its holding periods and cost basis.
+//!
+//! ## Holding-period terms
+//!
+//! With any of the optional `capital gain short` / `capital gain long` /
+//! `capital loss short` / `capital loss long` roles declared, a disposal's
+//! closed lots are split by holding period: a lot held more than the
+//! threshold (`capital gain long N`, default 365 days) is long-term, the
+//! rest short-term. Each term nets its lots and books to its own gain or
+//! loss account, so one disposal can carry several capital postings. A
+//! term whose role isn't declared falls back to the plain pair.
 //!
 //! ## Injection
 //!
@@ -41,17 +91,28 @@
 //! asset legs. So `print` is 1:1 copy-pasteable and survives a reload.
 
 use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
 
+use crate::booker::SWAP_TAG;
 use crate::date::Date;
 use crate::decimal::Decimal;
+use crate::error::Error;
 use crate::indexer::Index;
+use crate::parser::entry::Split;
 use crate::parser::located::Located;
+use crate::parser::metadata::Metadata;
 use crate::parser::posting::{Amount, Costs, LotCost, Posting};
 use crate::parser::transaction::Transaction;
+use crate::resolver::{AccountType, AccountTypes, Bookings};
+
+pub mod booking;
+
+pub use booking::Booking;
+use booking::{selects_lot, Selector};
 
 /// One open lot: a remaining quantity carrying its per-unit cost and the
-/// date it was acquired (FIFO order is insertion order; the date feeds
-/// the holding period and the title).
+/// date it was acquired (lots are kept in insertion order, which is also
+/// date order; the date feeds the holding period and the title).
 ///
 /// `cost_per_unit` is the lot's cost in `cost_commodity`. Under `-X` that
 /// is the commodity's *market value on the acquisition date*, in the
@@ -64,13 +125,67 @@ struct Lot {
     date: Date,
 }
 
-/// One lot closed by a disposal: how much was taken, at what cost, and
-/// when it was acquired — enough to render one split leg.
+/// A lot still open once every transaction has been walked — the
+/// position [`realize_capital`] leaves behind, for the `lots` report.
+/// `cost_per_unit` is in `cost_commodity`, on the same basis as the
+/// internal lot: market value at acquisition under `-X`, the booked
+/// rate natively.
+#[derive(Debug, Clone)]
+pub struct OpenLot {
+    pub account: String,
+    pub commodity: String,
+    pub qty: Decimal,
+    pub cost_per_unit: Decimal,
+    pub cost_commodity: String,
+    pub date: Date,
+}
+
+/// One lot closed by a disposal — a row of the `gains` report. The
+/// amounts are in `currency`, the disposal's gain commodity: the target
+/// under `-X`, the counter-commodity natively. `proceeds − cost_basis =
+/// gain`. Closing a short swaps the roles: the opening sale is the
+/// proceeds, the covering purchase the cost, and `acquired` is the date
+/// the short was opened. Every closed lot is listed, whatever its gain:
+/// a zero gain, a disposal booked by hand with a `{}` lot cost (listed,
+/// though the lotter books nothing for it), and a lot whose cost is in
+/// another currency than the proceeds — the proceeds converted to the
+/// cost currency, else the cost to the proceeds', when the price DB links
+/// the two.
+#[derive(Debug, Clone)]
+pub struct RealizedLot {
+    pub account: String,
+    pub commodity: String,
+    pub qty: Decimal,
+    pub acquired: Date,
+    pub disposed: Date,
+    pub proceeds: Decimal,
+    pub cost_basis: Decimal,
+    pub gain: Decimal,
+    pub currency: String,
+    /// Held more than the long-term threshold (see [`CapitalAccounts`]).
+    pub long_term: bool,
+}
+
+/// What [`realize_capital`] leaves behind: the lots still open, ordered
+/// by account, commodity and acquisition date, and every lot a disposal
+/// closed, ordered by disposal date.
+#[derive(Debug, Default)]
+pub struct Lots {
+    pub open: Vec<OpenLot>,
+    pub realized: Vec<RealizedLot>,
+}
+
+/// One lot closed by a disposal (or moved by a transfer): how much was
+/// taken, at what cost, and when it was acquired — enough to render one
+/// split leg.
+#[derive(Clone)]
 struct ClosedLot {
     qty: Decimal,
     cost_per_unit: Decimal,
     cost_commodity: String,
     date: Date,
+    /// This lot's share of the disposal's gain (zero for a transfer).
+    gain: Decimal,
 }
 
 /// A disposal that realized a capital gain/loss. Phase 1 collects these
@@ -84,6 +199,8 @@ struct ClosedLot {
 struct Disposal {
     tx_idx: usize,
     posting_idx: usize,
+    /// The disposal date — the end of each closed lot's holding period.
+    date: Date,
     /// Traded commodity (e.g. ETH) and the source posting's decimals.
     commodity: String,
     decimals: usize,
@@ -102,26 +219,98 @@ struct Disposal {
     lots: Vec<ClosedLot>,
 }
 
+/// One leg of a lot-carrying transfer: the lots it sends or receives, the
+/// lots a sending leg disposes of (they leave the books), and the quantity
+/// no lot covered (sent from an account with no lots). `price` is the
+/// per-unit market value of what leaves the books, in its commodity: the
+/// `@` on a sending leg's disposed lots and on a receiving leg's covered
+/// quantity.
+struct TransferLeg {
+    posting_idx: usize,
+    lots: Vec<ClosedLot>,
+    disposed: Vec<ClosedLot>,
+    uncovered: Decimal,
+    price: Option<(Decimal, String)>,
+}
+
 /// The accounts a realized gain/loss is booked to: `capital` for a gain
-/// (income), the loss account for a loss (expense).
+/// (income), the loss account for a loss (expense). The optional
+/// short-/long-term accounts split a disposal by holding period; each one
+/// left `None` falls back to the plain pair.
 pub struct CapitalAccounts<'a> {
     pub capital_gain: &'a str,
     pub capital_loss: &'a str,
+    pub short_gain: Option<&'a str>,
+    pub short_loss: Option<&'a str>,
+    pub long_gain: Option<&'a str>,
+    pub long_loss: Option<&'a str>,
+    /// A lot held more than this many days is long-term.
+    pub long_term_days: u32,
+}
+
+/// Default long-term threshold: held more than a year.
+pub const LONG_TERM_DAYS: u32 = 365;
+
+impl CapitalAccounts<'_> {
+    fn split_by_term(&self) -> bool {
+        self.short_gain.is_some()
+            || self.short_loss.is_some()
+            || self.long_gain.is_some()
+            || self.long_loss.is_some()
+    }
+
+    /// Whether a lot acquired on `acquired` and disposed of on `disposed`
+    /// was held long-term.
+    pub fn is_long_term(&self, acquired: Date, disposed: Date) -> bool {
+        disposed.days().saturating_sub(acquired.days()) > self.long_term_days
+    }
+
+    /// The account a net `gain` (in the lotter's sign: positive = gain)
+    /// books to, for the given term (`None` when terms aren't split).
+    fn account_for(&self, gain: Decimal, long: Option<bool>) -> &str {
+        let (short, long_, base) = if gain.is_negative() {
+            (self.short_loss, self.long_loss, self.capital_loss)
+        } else {
+            (self.short_gain, self.long_gain, self.capital_gain)
+        };
+        match long {
+            Some(true) => long_.unwrap_or(base),
+            Some(false) => short.unwrap_or(base),
+            None => base,
+        }
+    }
 }
 
-/// Track lots FIFO and inject one realized capital-gain/loss posting per
-/// disposal. See module docs for the valuation semantics.
+/// Track lots per each account's booking method (`bookings`, from
+/// `booking` sub-directives; FIFO where none applies), carry them across
+/// transfers between balance-sheet accounts (`types`), and inject one
+/// realized capital-gain/loss posting per disposal. See module docs for
+/// the valuation semantics. Returns the lots still open at the end and
+/// the lots each disposal closed, or an error naming the first `specific`
+/// disposal whose `{cost}` / `[date]` matches no open lot.
 pub fn realize_capital(
     txs: &mut Vec<Located<Transaction>>,
     accounts: &CapitalAccounts,
+    bookings: &Bookings,
+    types: &AccountTypes,
     target: Option<&str>,
     db: &Index,
     precisions: &HashMap<String, usize>,
-) {
-    let mut lots: HashMap<(String, String), VecDeque<Lot>> = HashMap::new();
+) -> Result<Lots, Error> {
+    let mut lots: HashMap<(String, String), Vec<Lot>> = HashMap::new();
     let mut disposals: Vec<Disposal> = Vec::new();
+    let mut realized: Vec<RealizedLot> = Vec::new();
+    let mut transfers: Vec<(usize, Vec<TransferLeg>, Vec<Disposal>)> = Vec::new();
+    let own = |account: &str| is_balance_sheet(types, account);
 
     for (idx, lt) in txs.iter().enumerate() {
+        // A redenomination swap rescales the lots in place of a trade.
+        if let Some(split) = db.splits().iter().find(|s| is_split_swap(s, &lt.value)) {
+            if target.is_none_or(|t| t != split.from && t != split.to) {
+                transfers.push((idx, split_lots(&lt.value, split, &mut lots), Vec::new()));
+            }
+            continue;
+        }
         // Native per-commodity sums over balance-contributing postings.
         // Drive both the "is this a trade?" test and the implied rate.
         let mut sums: HashMap<String, Decimal> = HashMap::new();
@@ -133,13 +322,38 @@ pub fn realize_capital(
                 *sums.entry(a.commodity.clone()).or_insert(Decimal::zero()) += a.value;
             }
         }
-        // Single-commodity transactions can't realize a capital gain —
-        // there is no exchange, hence no cost basis vs proceeds.
+        let date = lt.value.date.to_string();
+        // A single-commodity transaction is no exchange. One that moves
+        // the commodity between balance-sheet accounts carries its lots
+        // along; whatever it sends elsewhere leaves the books and is
+        // disposed of at its market value in the lot's cost commodity.
         if sums.len() < 2 {
+            if let Some((commodity, _)) = sums.iter().next()
+                && target != Some(commodity.as_str())
+            {
+                let value = |cost_commodity: &str| match target {
+                    Some(t) if t != cost_commodity => None,
+                    _ => db.find(commodity, cost_commodity, &date),
+                };
+                if let Some(legs) = move_lots(&lt.value, commodity, &mut lots, bookings, &own, &value)? {
+                    let disposals = transfer_disposals(idx, &lt.value, commodity, &legs);
+                    for d in &disposals {
+                        let account = &lt.value.postings[d.posting_idx].value.account;
+                        realized.extend(realized_lots(d, account, accounts));
+                    }
+                    transfers.push((idx, legs, disposals));
+                }
+            }
             continue;
         }
-        let date = lt.value.date.to_string();
 
+        // Natively, the commodity a trade is paid or received in is the
+        // money of that trade: like the target under `-X`, its legs
+        // neither open lots nor close them.
+        let counter = match target {
+            Some(_) => Vec::new(),
+            None => counter_commodities(&lt.value, &sums),
+        };
         for (p_idx, lp) in lt.value.postings.iter().enumerate() {
             if !contributes(&lp.value) {
                 continue;
@@ -151,6 +365,9 @@ pub fn realize_capital(
                 && a.commodity == t {
                     continue;
                 }
+            if counter.contains(&a.commodity) {
+                continue;
+            }
             // Per-unit value and its commodity. Under `-X` this is the
             // commodity's market value in the target on this date (price
             // DB); natively it is the booked trade rate in the counter-
@@ -164,48 +381,65 @@ pub fn realize_capital(
             let key = (lp.value.account.clone(), a.commodity.clone());
 
             // A `{}` lot-cost means the user books the gain by hand
-            // (ledger style) — acc consumes lots for FIFO consistency but
-            // injects nothing and opens no lot.
-            let manual = lp.value.lot_cost.is_some();
+            // (ledger style) — acc consumes lots to keep the position
+            // consistent but injects nothing and opens no lot. Under
+            // `specific` booking a disposal's `{}` only names its lot.
+            let manual = lp.value.lot_cost.is_some() && !selects_lot(bookings, &lp.value);
+            let booking = Booking::for_account(bookings, &lp.value.account);
+            let selector = if booking == Booking::Specific {
+                selector_for(&lp.value)
+            } else {
+                Selector::default()
+            };
 
             let queue = lots.entry(key.clone()).or_default();
+            if a.value.is_negative() && !selector_matches(queue, &selector) {
+                return Err(no_lot_error(lp, &a.commodity, &selector));
+            }
 
             let mut remaining = a.value; // signed: + acquires, − disposes
             let mut gain = Decimal::zero();
             let mut closed: Vec<ClosedLot> = Vec::new();
-            let mut mixed = false;
-
-            // Close opposite-sign lots front-to-back: a long lot (qty > 0)
-            // by a disposal (remaining < 0), a short lot (qty < 0) by an
-            // acquisition (remaining > 0). Same sign extends the position,
-            // so stop. Shorts are only ever opened for a position traded
-            // against the target money — see the open condition below.
-            while !remaining.is_zero() {
-                let Some(front) = queue.front() else { break };
-                if front.qty.is_negative() == remaining.is_negative() {
+            let mut foreign: Vec<RealizedLot> = Vec::new();
+
+            // Close opposite-sign lots in the booking method's order: a
+            // long lot (qty > 0) by a disposal (remaining < 0), a short lot
+            // (qty < 0) by an acquisition (remaining > 0). Same sign extends
+            // the position, so it is left alone. Shorts are only ever opened
+            // for a position traded against the target money — see the open
+            // condition below.
+            for i in closing_order(queue, booking, &selector) {
+                if remaining.is_zero() {
                     break;
                 }
+                let front = &mut queue[i];
+                if front.qty.is_negative() == remaining.is_negative() {
+                    continue;
+                }
                 let short = front.qty.is_negative();
                 let take = remaining.abs().min(front.qty.abs());
-                let front = queue.front_mut().unwrap();
                 // Cost vs proceeds in different commodities (mixed currency,
-                // native mode) can't be netted — skip the gain but still
-                // consume the lot for FIFO consistency. Under `-X` both are
-                // the target, so this always matches.
+                // native mode) can't be netted — book no gain but still
+                // consume the lot to keep the position consistent, and list
+                // it at a converted value. Under `-X` both are the target,
+                // so this always matches.
                 if front.cost_commodity == value_commodity {
                     // (close − open) for a long lot, (open − close) for a
                     // short — `unit_value − cost`, sign-flipped for shorts.
                     let per = unit_value - front.cost_per_unit;
                     let per = if short { Decimal::zero() - per } else { per };
-                    gain += take.mul_rounded(per);
+                    let lot_gain = take.mul_rounded(per);
+                    gain += lot_gain;
                     closed.push(ClosedLot {
                         qty: take,
                         cost_per_unit: front.cost_per_unit,
                         cost_commodity: front.cost_commodity.clone(),
                         date: front.date,
+                        gain: lot_gain,
                     });
                 } else {
-                    mixed = true;
+                    let closing = (unit_value, value_commodity.as_str());
+                    foreign.extend(mixed_lot(front, take, closing, &key, lt.value.date, accounts, db));
                 }
                 front.qty = if short { front.qty + take } else { front.qty - take };
                 remaining = if remaining.is_negative() {
@@ -213,14 +447,46 @@ pub fn realize_capital(
                 } else {
                     remaining - take
                 };
-                if front.qty.is_zero() {
-                    queue.pop_front();
-                }
             }
+            queue.retain(|lot| !lot.qty.is_zero());
+
+            // Every closed lot is a `gains` row, whether or not a capital
+            // posting is booked for it below.
+            let disposal = Disposal {
+                tx_idx: idx,
+                posting_idx: p_idx,
+                date: lt.value.date,
+                commodity: a.commodity.clone(),
+                decimals: a.decimals,
+                is_virtual: lp.value.is_virtual,
+                balanced: lp.value.balanced,
+                proceeds_per_unit: unit_value,
+                gain,
+                gain_commodity: value_commodity.clone(),
+                acquisition: !a.value.is_negative(),
+                lots: closed,
+            };
+            realized.extend(realized_lots(&disposal, &lp.value.account, accounts));
+            let mixed = !foreign.is_empty();
+            realized.append(&mut foreign);
 
             if manual {
                 // User-booked disposal: lots consumed, nothing injected,
-                // no new lot opened.
+                // no new lot opened. An acquisition that also names its
+                // lot date is a carried-forward lot (`acc close` opening
+                // balances) and re-opens at its own cost and date.
+                if !remaining.is_zero()
+                    && !remaining.is_negative()
+                    && let Some((cost_per_unit, cost_commodity, date)) =
+                        carried_lot(&lp.value, a.value, target, db)
+                {
+                    let average = (booking == Booking::Average).then(|| cost_commodity.clone());
+                    queue.push(Lot { qty: remaining, cost_per_unit, cost_commodity, date });
+                    queue.sort_by_key(|lot| lot.date);
+                    if let Some(c) = average {
+                        average_costs(queue, &c);
+                    }
+                }
                 continue;
             }
 
@@ -238,35 +504,25 @@ pub fn realize_capital(
             let against_target =
                 sums.len() == 2 && target.is_some_and(|t| sums.contains_key(t));
             if !remaining.is_zero() && (!remaining.is_negative() || against_target) {
-                queue.push_back(Lot {
+                queue.push(Lot {
                     qty: remaining,
                     cost_per_unit: unit_value,
                     cost_commodity: value_commodity.clone(),
                     date: lt.value.date,
                 });
+                if booking == Booking::Average {
+                    average_costs(queue, &value_commodity);
+                }
             }
 
-            // Record a realization if lots were closed with a real gain.
-            if mixed || closed.is_empty() {
-                continue;
-            }
-            let prec = precisions.get(&value_commodity).copied().unwrap_or(2);
-            if gain.is_display_zero(prec) {
+            // Book a realization if lots were closed with a real gain.
+            if mixed
+                || disposal.lots.is_empty()
+                || gain_postings(&disposal, accounts, precisions).is_empty()
+            {
                 continue;
             }
-            disposals.push(Disposal {
-                tx_idx: idx,
-                posting_idx: p_idx,
-                commodity: a.commodity.clone(),
-                decimals: a.decimals,
-                is_virtual: lp.value.is_virtual,
-                balanced: lp.value.balanced,
-                proceeds_per_unit: unit_value,
-                gain,
-                gain_commodity: value_commodity,
-                acquisition: !a.value.is_negative(),
-                lots: closed,
-            });
+            disposals.push(disposal);
         }
     }
 
@@ -280,6 +536,451 @@ pub fn realize_capital(
     for (tx_idx, disps) in by_tx {
         rewrite_tx(&mut txs[tx_idx], &disps, accounts, precisions);
     }
+    for (tx_idx, legs, disposals) in transfers {
+        rewrite_transfer(&mut txs[tx_idx], &legs, &disposals, accounts, precisions);
+    }
+
+    let mut open: Vec<OpenLot> = lots
+        .into_iter()
+        .flat_map(|((account, commodity), queue)| {
+            queue.into_iter().map(move |lot| OpenLot {
+                account: account.clone(),
+                commodity: commodity.clone(),
+                qty: lot.qty,
+                cost_per_unit: lot.cost_per_unit,
+                cost_commodity: lot.cost_commodity,
+                date: lot.date,
+            })
+        })
+        .collect();
+    open.sort_by(|a, b| {
+        (&a.account, &a.commodity, a.date).cmp(&(&b.account, &b.commodity, b.date))
+    });
+    Ok(Lots { open, realized })
+}
+
+/// The `gains` rows of one disposal: each closed lot's cost basis,
+/// proceeds and gain. A short's opening sale is its proceeds; its cost is
+/// what covering it costs at the disposal.
+fn realized_lots<'a>(
+    disp: &'a Disposal,
+    account: &'a str,
+    accounts: &'a CapitalAccounts,
+) -> impl Iterator<Item = RealizedLot> + 'a {
+    disp.lots.iter().map(move |lot| {
+        let cost_basis = if disp.acquisition {
+            lot.qty.mul_rounded(disp.proceeds_per_unit)
+        } else {
+            lot.qty.mul_rounded(lot.cost_per_unit)
+        };
+        RealizedLot {
+            account: account.to_string(),
+            commodity: disp.commodity.clone(),
+            qty: lot.qty,
+            acquired: lot.date,
+            disposed: disp.date,
+            proceeds: cost_basis + lot.gain,
+            cost_basis,
+            gain: lot.gain,
+            currency: disp.gain_commodity.clone(),
+            long_term: accounts.is_long_term(lot.date, disp.date),
+        }
+    })
+}
+
+/// The `gains` row of a lot closed at `closing` (per-unit value and its
+/// commodity) when its cost is in another commodity: the closing value
+/// converted to the cost commodity, else the cost converted to the
+/// closing one. `position` is the lot's account and commodity. `None` when the price DB has no rate either way.
+fn mixed_lot(
+    lot: &Lot,
+    qty: Decimal,
+    closing: (Decimal, &str),
+    position: &(String, String),
+    date: Date,
+    accounts: &CapitalAccounts,
+    db: &Index,
+) -> Option<RealizedLot> {
+    let (unit_value, value_commodity) = closing;
+    let day = date.to_string();
+    let (open, close, currency) = match db.find(value_commodity, &lot.cost_commodity, &day) {
+        Some(rate) => (lot.cost_per_unit, unit_value * rate, lot.cost_commodity.clone()),
+        None => {
+            let rate = db.find(&lot.cost_commodity, value_commodity, &day)?;
+            (lot.cost_per_unit * rate, unit_value, value_commodity.to_string())
+        }
+    };
+    // A short's opening sale is its proceeds, as in `realized_lots`.
+    let (cost_basis, proceeds) = if lot.qty.is_negative() {
+        (qty.mul_rounded(close), qty.mul_rounded(open))
+    } else {
+        (qty.mul_rounded(open), qty.mul_rounded(close))
+    };
+    Some(RealizedLot {
+        account: position.0.clone(),
+        commodity: position.1.clone(),
+        qty,
+        acquired: lot.date,
+        disposed: date,
+        proceeds,
+        cost_basis,
+        gain: proceeds - cost_basis,
+        currency,
+        long_term: accounts.is_long_term(lot.date, date),
+    })
+}
+
+/// Whether `account` is on the balance sheet — one of our own accounts,
+/// between which lots move rather than realize: declared `type asset`,
+/// `liability` or `equity`, else (undeclared) under one of those roots.
+fn is_balance_sheet(types: &AccountTypes, account: &str) -> bool {
+    match types.get(account) {
+        Some(t) => matches!(t, AccountType::Asset | AccountType::Liability | AccountType::Equity),
+        None => {
+            let root = account.split(':').next().unwrap_or(account);
+            ["assets", "liabilities", "equity"].iter().any(|r| root.eq_ignore_ascii_case(r))
+        }
+    }
+}
+
+/// Move the lots of `commodity` from the sending legs of a single-
+/// commodity transaction to its receiving legs. Each sender gives up lots
+/// in its own booking order; the pooled lots then fill the balance-sheet
+/// (`own`) receivers oldest first, in posting order, and are filed into
+/// each receiver's queue by acquisition date. What the other receivers
+/// take leaves the books: those lots are disposed of at `value` — the
+/// commodity's per-unit market value in a lot's cost commodity — and
+/// marked on their sender's leg. `None` when the transaction moves no
+/// lots — no sender, no receiver, or no sender holding any; an error when
+/// a `specific` sender names a lot it doesn't hold.
+fn move_lots(
+    tx: &Transaction,
+    commodity: &str,
+    lots: &mut HashMap<(String, String), Vec<Lot>>,
+    bookings: &Bookings,
+    own: &dyn Fn(&str) -> bool,
+    value: &dyn Fn(&str) -> Option<Decimal>,
+) -> Result<Option<Vec<TransferLeg>>, Error> {
+    let legs_of = |incoming: bool| -> Vec<(usize, &Posting, Decimal)> {
+        tx.postings
+            .iter()
+            .enumerate()
+            .filter(|(_, lp)| contributes(&lp.value))
+            .filter_map(|(i, lp)| {
+                let a = lp.value.amount.as_ref()?;
+                let keep = !a.value.is_zero() && a.value.is_negative() != incoming;
+                keep.then(|| (i, &lp.value, a.value.abs()))
+            })
+            .collect()
+    };
+    let (senders, mut receivers) = (legs_of(false), legs_of(true));
+    if senders.is_empty() || receivers.is_empty() {
+        return Ok(None);
+    }
+    // Own accounts fill first, so only what goes elsewhere leaves the books.
+    receivers.sort_by_key(|(_, p, _)| !own(&p.account));
+
+    // Pooled lots, each with the index of the sending leg it came from.
+    let mut legs = Vec::new();
+    let mut pool: Vec<(usize, ClosedLot)> = Vec::new();
+    for (posting_idx, p, qty) in senders {
+        let booking = Booking::for_account(bookings, &p.account);
+        let selector = if booking == Booking::Specific { selector_for(p) } else { Selector::default() };
+        let queue = lots.entry((p.account.clone(), commodity.to_string())).or_default();
+        if !selector_matches(queue, &selector) {
+            return Err(no_lot_error(&tx.postings[posting_idx], commodity, &selector));
+        }
+        let mut need = qty;
+        for i in closing_order(queue, booking, &selector) {
+            if need.is_zero() {
+                break;
+            }
+            let lot = &mut queue[i];
+            if lot.qty <= Decimal::zero() {
+                continue;
+            }
+            let take = need.min(lot.qty);
+            pool.push((
+                legs.len(),
+                ClosedLot {
+                    qty: take,
+                    cost_per_unit: lot.cost_per_unit,
+                    cost_commodity: lot.cost_commodity.clone(),
+                    date: lot.date,
+                    gain: Decimal::zero(),
+                },
+            ));
+            lot.qty -= take;
+            need -= take;
+        }
+        queue.retain(|lot| !lot.qty.is_zero());
+        legs.push(TransferLeg { posting_idx, lots: Vec::new(), disposed: Vec::new(), uncovered: need, price: None });
+    }
+    if pool.is_empty() {
+        return Ok(None);
+    }
+    pool.sort_by_key(|(_, lot)| lot.date);
+    let mut pool = VecDeque::from(pool);
+
+    // The market value what leaves the books goes at: in the cost
+    // commodity of the oldest lot. A lot costed in another commodity
+    // can't be netted against it (mixed currency, native mode); it leaves
+    // uncovered, realizing nothing.
+    let price = value(&pool[0].1.cost_commodity).map(|v| (v, pool[0].1.cost_commodity.clone()));
+    for (posting_idx, p, qty) in receivers {
+        let kept = own(&p.account);
+        let mut need = qty;
+        let mut got: Vec<ClosedLot> = Vec::new();
+        while !need.is_zero() {
+            let Some((from, front)) = pool.front_mut() else { break };
+            let take = need.min(front.qty);
+            let mut piece = ClosedLot { qty: take, ..front.clone() };
+            front.qty -= take;
+            need -= take;
+            let from = &mut legs[*from];
+            if kept {
+                from.lots.push(piece.clone());
+                got.push(piece);
+            } else if let Some((unit, c)) = price.as_ref().filter(|(_, c)| *c == piece.cost_commodity) {
+                piece.gain = take.mul_rounded(*unit - piece.cost_per_unit);
+                from.disposed.push(piece.clone());
+                from.price = Some((*unit, c.clone()));
+                got.push(piece);
+            } else {
+                from.uncovered += take;
+            }
+            if pool.front().is_some_and(|(_, l)| l.qty.is_zero()) {
+                pool.pop_front();
+            }
+        }
+        let covered = got.iter().fold(Decimal::zero(), |acc, l| acc + l.qty);
+        if kept {
+            let queue = lots.entry((p.account.clone(), commodity.to_string())).or_default();
+            queue.extend(got.iter().map(|l| Lot {
+                qty: l.qty,
+                cost_per_unit: l.cost_per_unit,
+                cost_commodity: l.cost_commodity.clone(),
+                date: l.date,
+            }));
+            queue.sort_by_key(|lot| lot.date);
+            if Booking::for_account(bookings, &p.account) == Booking::Average {
+                let mut commodities: Vec<String> = got.iter().map(|l| l.cost_commodity.clone()).collect();
+                commodities.dedup();
+                for c in commodities {
+                    average_costs(queue, &c);
+                }
+            }
+            legs.push(TransferLeg { posting_idx, lots: got, disposed: Vec::new(), uncovered: need, price: None });
+        } else {
+            // The receiver books no lots: its covered quantity is priced,
+            // the rest passes through as is.
+            legs.push(TransferLeg {
+                posting_idx,
+                lots: Vec::new(),
+                disposed: Vec::new(),
+                uncovered: qty - covered,
+                price: if covered.is_zero() { None } else { price.clone() },
+            });
+        }
+    }
+    // Sent but received nowhere (the receivers are short): the lots still
+    // leave their sender.
+    for (from, lot) in pool {
+        legs[from].lots.push(lot);
+    }
+    Ok(Some(legs))
+}
+
+/// The disposals of a transfer's sending legs: one per leg that sent lots
+/// out of the books, for the capital postings and the `gains` rows.
+fn transfer_disposals(
+    tx_idx: usize,
+    tx: &Transaction,
+    commodity: &str,
+    legs: &[TransferLeg],
+) -> Vec<Disposal> {
+    legs.iter()
+        .filter(|leg| !leg.disposed.is_empty())
+        .filter_map(|leg| {
+            let (unit, currency) = leg.price.clone()?;
+            let p = &tx.postings[leg.posting_idx].value;
+            Some(Disposal {
+                tx_idx,
+                posting_idx: leg.posting_idx,
+                date: tx.date,
+                commodity: commodity.to_string(),
+                decimals: p.amount.as_ref().map_or(0, |a| a.decimals),
+                is_virtual: p.is_virtual,
+                balanced: p.balanced,
+                proceeds_per_unit: unit,
+                gain: leg.disposed.iter().fold(Decimal::zero(), |acc, l| acc + l.gain),
+                gain_commodity: currency,
+                acquisition: false,
+                lots: leg.disposed.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Whether `tx` is the booker's generated swap for `split`: marked with
+/// the [`SWAP_TAG`] and swapping out `split`'s old commodity on its date.
+fn is_split_swap(split: &Split, tx: &Transaction) -> bool {
+    tx.metadata.get(SWAP_TAG).is_some()
+        && split.date == tx.date
+        && tx.postings.iter().any(|lp| lp.value.amount.as_ref().is_some_and(|a| a.commodity == split.from))
+}
+
+/// Move every lot of the swapped commodity onto the new one, rescaled:
+/// quantity × ratio, per-unit cost ÷ ratio, acquisition date kept. The
+/// swap's outgoing legs carry the `@ ratio` cost; each is paired with the
+/// same account's incoming leg.
+fn split_lots(
+    tx: &Transaction,
+    split: &Split,
+    lots: &mut HashMap<(String, String), Vec<Lot>>,
+) -> Vec<TransferLeg> {
+    let ratio = split.ratio();
+    let abs_sum = |lots: &[ClosedLot]| lots.iter().fold(Decimal::zero(), |acc, l| acc + l.qty);
+    let mut legs = Vec::new();
+    for (posting_idx, lp) in tx.postings.iter().enumerate() {
+        let p = &lp.value;
+        let Some(a) = &p.amount else { continue };
+        if a.commodity != split.from || p.costs.is_none() {
+            continue;
+        }
+        let queue = lots.remove(&(p.account.clone(), split.from.clone())).unwrap_or_default();
+        let old: Vec<ClosedLot> = queue
+            .iter()
+            .map(|l| ClosedLot {
+                qty: l.qty.abs(),
+                cost_per_unit: l.cost_per_unit,
+                cost_commodity: l.cost_commodity.clone(),
+                date: l.date,
+                gain: Decimal::zero(),
+            })
+            .collect();
+        let rescaled: Vec<Lot> = queue
+            .into_iter()
+            .map(|l| Lot {
+                qty: l.qty.mul_rounded(ratio),
+                cost_per_unit: l.cost_per_unit.div_rounded(ratio),
+                ..l
+            })
+            .collect();
+        let new: Vec<ClosedLot> = rescaled
+            .iter()
+            .map(|l| ClosedLot {
+                qty: l.qty.abs(),
+                cost_per_unit: l.cost_per_unit,
+                cost_commodity: l.cost_commodity.clone(),
+                date: l.date,
+                gain: Decimal::zero(),
+            })
+            .collect();
+        let target = lots.entry((p.account.clone(), split.to.clone())).or_default();
+        target.extend(rescaled);
+        target.sort_by_key(|lot| lot.date);
+
+        let incoming = tx.postings.iter().position(|other| {
+            other.value.account == p.account
+                && other.value.costs.is_none()
+                && other.value.amount.as_ref().is_some_and(|b| b.commodity == split.to)
+        });
+        if let Some(incoming) = incoming {
+            let qty = tx.postings[incoming].value.amount.as_ref().map_or(Decimal::zero(), |b| b.value.abs());
+            legs.push(TransferLeg {
+                posting_idx: incoming,
+                uncovered: qty - abs_sum(&new),
+                lots: new,
+                disposed: Vec::new(),
+                price: None,
+            });
+        }
+        legs.push(TransferLeg {
+            posting_idx,
+            uncovered: a.value.abs() - abs_sum(&old),
+            lots: old,
+            disposed: Vec::new(),
+            price: None,
+        });
+    }
+    legs
+}
+
+/// Split each leg of a lot-carrying transfer into one posting per lot
+/// (annotated `{cost} [lot-date]`, plus `@ price` for a lot leaving the
+/// books), a `@ price` posting for the quantity a receiver outside the
+/// balance sheet was covered by lots, and a plain posting for any
+/// uncovered quantity. The sign follows the source posting; comments stay
+/// on the first leg. A leg that carried no lots passes through unchanged.
+/// Each disposal's capital postings are appended.
+fn rewrite_transfer(
+    lt: &mut Located<Transaction>,
+    legs: &[TransferLeg],
+    disposals: &[Disposal],
+    accounts: &CapitalAccounts,
+    precisions: &HashMap<String, usize>,
+) {
+    let mut rewritten: Vec<Located<Posting>> = Vec::new();
+    for (p_idx, lp) in lt.value.postings.iter().enumerate() {
+        let Some(leg) = legs.iter().find(|l| {
+            l.posting_idx == p_idx && (!l.lots.is_empty() || !l.disposed.is_empty() || l.price.is_some())
+        }) else {
+            rewritten.push(lp.clone());
+            continue;
+        };
+        let price = leg.price.as_ref().map(|(value, commodity)| {
+            Costs::PerUnit(Amount {
+                commodity: commodity.clone(),
+                value: *value,
+                decimals: precisions.get(commodity).copied().unwrap_or(2),
+            })
+        });
+        let total = lp.value.amount.as_ref().map_or(Decimal::zero(), |a| a.value.abs());
+        let outgoing = lp.value.amount.as_ref().is_some_and(|a| a.value.is_negative());
+        let piece = |qty: Decimal, lot: Option<&ClosedLot>, priced: bool| {
+            let mut p = lp.value.clone();
+            if let Some(a) = &mut p.amount {
+                a.value = if outgoing { Decimal::zero() - qty } else { qty };
+            }
+            p.lot_cost = lot.map(|l| LotCost {
+                amount: Amount {
+                    commodity: l.cost_commodity.clone(),
+                    value: l.cost_per_unit,
+                    decimals: precisions.get(&l.cost_commodity).copied().unwrap_or(2),
+                },
+                total: false,
+                fixed: false,
+            });
+            p.lot_date = lot.map(|l| l.date);
+            if priced {
+                p.costs = price.clone();
+            }
+            p.balance_assertion = None;
+            p
+        };
+        let mut pieces: Vec<Posting> = Vec::new();
+        pieces.extend(leg.lots.iter().map(|lot| piece(lot.qty, Some(lot), false)));
+        pieces.extend(leg.disposed.iter().map(|lot| piece(lot.qty, Some(lot), true)));
+        let moved = leg.lots.iter().chain(&leg.disposed).fold(Decimal::zero(), |acc, l| acc + l.qty);
+        let covered = total - moved - leg.uncovered;
+        if covered > Decimal::zero() && leg.price.is_some() {
+            pieces.push(piece(covered, None, true));
+        }
+        if leg.uncovered > Decimal::zero() {
+            pieces.push(piece(leg.uncovered, None, false));
+        }
+        for (i, mut p) in pieces.into_iter().enumerate() {
+            if i > 0 {
+                p.comments.clear();
+            }
+            rewritten.push(Located { file: lp.file.clone(), line: lp.line, value: p });
+        }
+    }
+    for disp in disposals {
+        rewritten.extend(capital_postings(disp, accounts, precisions, &lt.file, lt.line));
+    }
+    lt.value.postings = rewritten;
 }
 
 /// Rewrite a transaction's disposal postings: each becomes one leg per
@@ -350,6 +1051,7 @@ fn rewrite_tx(
                     }),
                     lot_date: Some(lot.date),
                     balance_assertion: None,
+                    state: None,
                     is_virtual: disp.is_virtual,
                     balanced: disp.balanced,
                     // Keep the source posting's comments on the first leg.
@@ -358,11 +1060,15 @@ fn rewrite_tx(
                     } else {
                         Vec::new()
                     },
+                    // Metadata is not display: every leg still belongs to
+                    // the tagged posting, so a `tag` query finds them all.
+                    metadata: lp.value.metadata.clone(),
+                    aux_date: lp.value.aux_date,
                 },
             });
         }
         // Over-sell: the closed lots cover only part of the disposal
-        // (FIFO ran out — acquisitions booked outside this file). The
+        // (the lots ran out — acquisitions booked outside this file). The
         // uncovered remainder has no cost basis; keep it as a plain
         // proceeds-priced leg (no `{}`) so the full disposed quantity
         // survives the rewrite and the transaction still balances.
@@ -389,57 +1095,233 @@ fn rewrite_tx(
                     lot_cost: None,
                     lot_date: None,
                     balance_assertion: None,
+                    state: None,
                     is_virtual: disp.is_virtual,
                     balanced: disp.balanced,
                     comments: Vec::new(),
+                    metadata: Metadata::default(),
+                    aux_date: None,
                 },
             });
         }
-        // Inject the realized gain as a real capital posting in the gain
-        // commodity (the rebalancer converts it to the target at the
-        // disposal date). It balances against the `{}` cost-basis on the
+        // Inject the realized gain as real capital postings in the gain
+        // commodity (the rebalancer converts them to the target at the
+        // disposal date). They balance against the `{}` cost-basis on the
         // asset legs, so the tx still sums to zero. The execution spread
         // (slippage) is booked separately by the realizer — not here.
-        if !disp.gain.is_display_zero(price_prec) {
-            let account = if disp.gain.is_negative() {
-                accounts.capital_loss
-            } else {
-                accounts.capital_gain
-            };
-            capitals.push(Located {
-                file: file.clone(),
-                line,
-                value: Posting {
-                    account: account.to_string(),
-                    amount: Some(Amount {
-                        commodity: disp.gain_commodity.clone(),
-                        value: -disp.gain,
-                        decimals: price_prec,
-                    }),
-                    costs: None,
-                    lot_cost: None,
-                    lot_date: None,
-                    balance_assertion: None,
-                    // Real (not virtual) → 1:1 copyable, survives re-load.
-                    // Real postings are always balance-contributing.
-                    is_virtual: false,
-                    balanced: true,
-                    comments: Vec::new(),
-                },
-            });
-        }
+        capitals.extend(capital_postings(disp, accounts, precisions, &file, line));
     }
 
     rewritten.extend(capitals);
     lt.value.postings = rewritten;
 }
 
+/// The real capital postings carrying a disposal's gain, in the gain
+/// commodity — one, or one per holding-period term ([`gain_postings`]).
+fn capital_postings(
+    disp: &Disposal,
+    accounts: &CapitalAccounts,
+    precisions: &HashMap<String, usize>,
+    file: &Arc<str>,
+    line: usize,
+) -> Vec<Located<Posting>> {
+    let price_prec = precisions.get(&disp.gain_commodity).copied().unwrap_or(2);
+    gain_postings(disp, accounts, precisions)
+        .into_iter()
+        .map(|(account, gain)| Located {
+            file: file.clone(),
+            line,
+            value: Posting {
+                account: account.to_string(),
+                amount: Some(Amount {
+                    commodity: disp.gain_commodity.clone(),
+                    value: -gain,
+                    decimals: price_prec,
+                }),
+                costs: None,
+                lot_cost: None,
+                lot_date: None,
+                balance_assertion: None,
+                state: None,
+                // Real (not virtual) → 1:1 copyable, survives re-load.
+                // Real postings are always balance-contributing.
+                is_virtual: false,
+                balanced: true,
+                comments: Vec::new(),
+                metadata: Metadata::default(),
+                aux_date: None,
+            },
+        })
+        .collect()
+}
+
+/// The capital postings a disposal books: `(account, gain)` pairs, gain
+/// positive for a gain. Without term accounts that is the whole gain on
+/// the plain pair. With them, the closed lots net per holding-period term
+/// and each term books by its own sign; a term that nets to a display
+/// zero folds into the other rather than being dropped, so the postings
+/// always sum to the full gain. Empty when nothing shows.
+fn gain_postings<'a>(
+    disp: &Disposal,
+    accounts: &'a CapitalAccounts,
+    precisions: &HashMap<String, usize>,
+) -> Vec<(&'a str, Decimal)> {
+    let prec = precisions.get(&disp.gain_commodity).copied().unwrap_or(2);
+    if !accounts.split_by_term() {
+        if disp.gain.is_display_zero(prec) {
+            return Vec::new();
+        }
+        return vec![(accounts.account_for(disp.gain, None), disp.gain)];
+    }
+    let (mut short, mut long) = (Decimal::zero(), Decimal::zero());
+    for lot in &disp.lots {
+        if accounts.is_long_term(lot.date, disp.date) {
+            long += lot.gain;
+        } else {
+            short += lot.gain;
+        }
+    }
+    if short.is_display_zero(prec) {
+        long += short;
+        short = Decimal::zero();
+    } else if long.is_display_zero(prec) {
+        short += long;
+        long = Decimal::zero();
+    }
+    [(short, false), (long, true)]
+        .into_iter()
+        .filter(|(g, _)| !g.is_display_zero(prec))
+        .map(|(g, is_long)| (accounts.account_for(g, Some(is_long)), g))
+        .collect()
+}
+
+/// Indices of `queue` in the order `booking` closes them. Lots keep their
+/// insertion (acquisition) order, so FIFO is the identity; `average` also
+/// closes oldest first — its lots already share one cost.
+fn closing_order(queue: &[Lot], booking: Booking, selector: &Selector) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..queue.len()).collect();
+    match booking {
+        Booking::Fifo | Booking::Average => {}
+        Booking::Lifo => order.reverse(),
+        // Stable, so equal costs stay oldest first.
+        Booking::Hifo => order.sort_by(|&a, &b| queue[b].cost_per_unit.cmp(&queue[a].cost_per_unit)),
+        Booking::Specific => order.sort_by_key(|&i| {
+            let lot = &queue[i];
+            selector.is_empty() || !selector.matches(lot.cost_per_unit, &lot.cost_commodity, lot.date)
+        }),
+    }
+    order
+}
+
+/// Whether `selector` names at least one open long lot of `queue` (an
+/// empty selector always does).
+fn selector_matches(queue: &[Lot], selector: &Selector) -> bool {
+    selector.is_empty()
+        || queue.iter().any(|lot| {
+            lot.qty > Decimal::zero()
+                && selector.matches(lot.cost_per_unit, &lot.cost_commodity, lot.date)
+        })
+}
+
+/// A `specific` disposal whose `{cost}` / `[date]` names no open lot.
+fn no_lot_error(lp: &Located<Posting>, commodity: &str, selector: &Selector) -> Error {
+    Error::from(format!(
+        "{}:{}: no open {} lot in {} matches {}",
+        lp.file, lp.line, commodity, lp.value.account, selector
+    ))
+}
+
+/// Re-price every open lot in `commodity` to the position's weighted
+/// average cost. Run after each acquisition under `average` booking, so a
+/// disposal — whichever lots it closes — realizes against the average.
+fn average_costs(queue: &mut [Lot], commodity: &str) {
+    let mut qty = Decimal::zero();
+    let mut cost = Decimal::zero();
+    for lot in queue.iter().filter(|l| l.cost_commodity == commodity) {
+        qty += lot.qty;
+        cost += lot.qty.mul_rounded(lot.cost_per_unit);
+    }
+    if qty.is_zero() {
+        return;
+    }
+    let average = cost.div_rounded(qty);
+    for lot in queue.iter_mut().filter(|l| l.cost_commodity == commodity) {
+        lot.cost_per_unit = average;
+    }
+}
+
+/// The lot a disposal leg names under `specific` booking: its per-unit
+/// `{cost}` (a `{{total}}` divided by the quantity) and its `[date]`.
+fn selector_for(p: &Posting) -> Selector {
+    let cost = p.lot_cost.as_ref().and_then(|lc| {
+        let per_unit = if lc.total {
+            let qty = p.amount.as_ref()?.value.abs();
+            if qty.is_zero() {
+                return None;
+            }
+            lc.amount.value.div_rounded(qty)
+        } else {
+            lc.amount.value
+        };
+        Some((per_unit, lc.amount.commodity.clone()))
+    });
+    Selector { cost, date: p.lot_date }
+}
+
 /// Balance-contributing postings: real and bracket-virtual `[account]`.
 /// A paren-virtual `(account)` posting stays out of the balance.
 fn contributes(p: &Posting) -> bool {
     !p.is_virtual || p.balanced
 }
 
+/// The lot a `{cost} [lot-date]` acquisition of `qty` carries forward:
+/// per-unit cost, its commodity and the acquisition date. Under `-X` the
+/// cost is the commodity's market value in the target on that date, as
+/// for any lot, falling back to the written cost when that is already in
+/// the target.
+fn carried_lot(
+    p: &Posting,
+    qty: Decimal,
+    target: Option<&str>,
+    db: &Index,
+) -> Option<(Decimal, String, Date)> {
+    let (lot, date, amount) = (p.lot_cost.as_ref()?, p.lot_date?, p.amount.as_ref()?);
+    let per_unit = lot.weight(qty).div_rounded(qty);
+    match target {
+        Some(t) => db
+            .find(&amount.commodity, t, &date.to_string())
+            .or((lot.amount.commodity == t).then_some(per_unit))
+            .map(|r| (r, t.to_string(), date)),
+        None => Some((per_unit, lot.amount.commodity.clone(), date)),
+    }
+}
+
+/// The commodities a native trade is paid or received in: those its
+/// `@` / `@@` costs and `{}` lot-costs are written in, or — for a clean
+/// two-commodity exchange with neither — the one the first leg is priced
+/// in (ledger's convention). Their legs are the counter side of the trade,
+/// not a position.
+fn counter_commodities(tx: &Transaction, sums: &HashMap<String, Decimal>) -> Vec<String> {
+    let legs = || tx.postings.iter().map(|lp| &lp.value).filter(|p| contributes(p));
+    let mut counter: Vec<String> = Vec::new();
+    for p in legs() {
+        let costs = p.costs.as_ref().map(|c| match c {
+            Costs::PerUnit(c) | Costs::Total(c) => &c.commodity,
+        });
+        for commodity in costs.into_iter().chain(p.lot_cost.as_ref().map(|l| &l.amount.commodity)) {
+            if sums.contains_key(commodity) && !counter.contains(commodity) {
+                counter.push(commodity.clone());
+            }
+        }
+    }
+    if counter.is_empty() && sums.len() == 2
+        && let Some(first) = legs().find_map(|p| p.amount.as_ref())
+    {
+        counter.extend(sums.keys().filter(|k| **k != first.commodity).cloned());
+    }
+    counter
+}
+
 /// Per-unit *booked* (trade) value of a posting in the counter-commodity.
 ///
 /// An explicit `@` cost wins; otherwise the implied rate of a clean
@@ -483,8 +1365,9 @@ mod tests {
     fn setup(src: &str) -> (Vec<Located<Transaction>>, Index, HashMap<String, usize>) {
         let entries = parser::parse(src).unwrap();
         let resolved = resolver::resolve(entries).unwrap();
-        let prices = crate::indexer::index(resolved.prices);
-        let txs = crate::booker::book(resolved.transactions).unwrap();
+        let prices = crate::indexer::index_with_splits(resolved.prices, resolved.splits.clone());
+        let txs = crate::booker::book_with_splits(resolved.transactions, &resolved.splits, &resolved.bookings)
+            .unwrap();
         let mut precisions: HashMap<String, usize> = HashMap::new();
         for lt in &txs {
             for lp in &lt.value.postings {
@@ -504,6 +1387,11 @@ mod tests {
         CapitalAccounts {
             capital_gain: "income:capital",
             capital_loss: "expenses:capital",
+            short_gain: None,
+            short_loss: None,
+            long_gain: None,
+            long_loss: None,
+            long_term_days: LONG_TERM_DAYS,
         }
     }
 
@@ -560,7 +1448,7 @@ mod tests {
             \tassets:btc   -1 BTC @ 50000 USD\n\
             \tassets:cash   50000 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), None, &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
         // Gain booked on the capital account, income negative.
         assert_eq!(gain_on(&txs, "income:capital"), Decimal::parse("-20000").unwrap());
     }
@@ -582,7 +1470,7 @@ mod tests {
             \tassets:btc   -1 BTC\n\
             \tassets:cash   51000 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), Some("USD"), &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), Some("USD"), &db, &prec).unwrap();
         assert_eq!(gain_on(&txs, "income:capital"), Decimal::parse("-18000").unwrap());
     }
 
@@ -601,11 +1489,337 @@ mod tests {
             \tassets:btc   -1 BTC @ 50000 USD\n\
             \tassets:cash   50000 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), None, &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
         // FIFO closes lot 1 only (50000 − 30000 = 20000).
         assert_eq!(gain_on(&txs, "income:capital"), Decimal::parse("-20000").unwrap());
     }
 
+    /// Three buys at 100, 300, 200 USD, then one 1-BTC sale at 400 USD
+    /// from an account declaring `booking METHOD`.
+    fn booked_sale(method: &str) -> Vec<Located<Transaction>> {
+        let src = format!(
+            "account assets:btc\n\
+            \tbooking {method}\n\
+            2024-01-01 buy a\n\
+            \tassets:btc    1 BTC @ 100 USD\n\
+            \tassets:cash  -100 USD\n\
+            2024-02-01 buy b\n\
+            \tassets:btc    1 BTC @ 300 USD\n\
+            \tassets:cash  -300 USD\n\
+            2024-03-01 buy c\n\
+            \tassets:btc    1 BTC @ 200 USD\n\
+            \tassets:cash  -200 USD\n\
+            2024-06-01 sell\n\
+            \tassets:btc   -1 BTC @ 400 USD\n\
+            \tassets:cash   400 USD\n"
+        );
+        let entries = parser::parse(&src).unwrap();
+        let bookings = resolver::resolve(entries.clone()).unwrap().bookings;
+        let (mut txs, db, prec) = setup(&src);
+        realize_capital(&mut txs, &caps(), &bookings, &AccountTypes::default(), None, &db, &prec).unwrap();
+        txs
+    }
+
+    #[test]
+    fn booking_method_picks_the_lot() {
+        // FIFO closes the 100 lot, LIFO the 200 lot, HIFO the 300 lot,
+        // average closes at the pooled (100+300+200)/3 = 200.
+        let gain = |m| gain_on(&booked_sale(m), "income:capital");
+        assert_eq!(gain("fifo"), Decimal::parse("-300").unwrap());
+        assert_eq!(gain("lifo"), Decimal::parse("-200").unwrap());
+        assert_eq!(gain("hifo"), Decimal::parse("-100").unwrap());
+        assert_eq!(gain("average"), Decimal::parse("-200").unwrap());
+        let lifo = booked_sale("lifo");
+        assert_eq!(split_legs(&lifo, "BTC")[0].lot_date.unwrap().to_string(), "2024-03-01");
+    }
+
+    #[test]
+    fn average_keeps_the_pool_cost_after_a_partial_sale() {
+        // Buy 1 @100 and 1 @300 (average 200), sell 1 @250 (+50), buy 2
+        // @400: the pool is 1@200 + 2@400 → 1000/3, so selling all three
+        // at 400 realizes 1200 − 1000 = 200. Total gain 250.
+        let src = "\
+            account assets:btc\n\
+            \tbooking average\n\
+            2024-01-01 buy a\n\
+            \tassets:btc    1 BTC @ 100 USD\n\
+            \tassets:cash  -100 USD\n\
+            2024-02-01 buy b\n\
+            \tassets:btc    1 BTC @ 300 USD\n\
+            \tassets:cash  -300 USD\n\
+            2024-03-01 sell\n\
+            \tassets:btc   -1 BTC @ 250 USD\n\
+            \tassets:cash   250 USD\n\
+            2024-04-01 buy c\n\
+            \tassets:btc    2 BTC @ 400 USD\n\
+            \tassets:cash  -800 USD\n\
+            2024-06-01 sell all\n\
+            \tassets:btc   -3 BTC @ 400 USD\n\
+            \tassets:cash   1200 USD\n";
+        let bookings = resolver::resolve(parser::parse(src).unwrap()).unwrap().bookings;
+        let (mut txs, db, prec) = setup(src);
+        realize_capital(&mut txs, &caps(), &bookings, &AccountTypes::default(), None, &db, &prec).unwrap();
+        let gain = gain_on(&txs, "income:capital");
+        assert!((gain + Decimal::parse("250").unwrap()).abs() < Decimal::parse("0.01").unwrap());
+    }
+
+    #[test]
+    fn specific_booking_closes_the_named_lot() {
+        // The disposal names the 2024-02-01 lot by `{cost} [date]`: that
+        // lot closes and its gain is injected like a FIFO sale's (350 −
+        // 300 = 50), leaving the 100 lot for the FIFO-order fallback of
+        // the next, unannotated sale (400 − 100 = 300).
+        let src = "\
+            account assets:btc\n\
+            \tbooking specific\n\
+            2024-01-01 buy a\n\
+            \tassets:btc    1 BTC @ 100 USD\n\
+            \tassets:cash  -100 USD\n\
+            2024-02-01 buy b\n\
+            \tassets:btc    1 BTC @ 300 USD\n\
+            \tassets:cash  -300 USD\n\
+            2024-03-01 sell b\n\
+            \tassets:btc   -1 BTC {300 USD} [2024-02-01] @ 350 USD\n\
+            \tassets:cash   350 USD\n\
+            2024-06-01 sell a\n\
+            \tassets:btc   -1 BTC @ 400 USD\n\
+            \tassets:cash   400 USD\n";
+        let bookings = resolver::resolve(parser::parse(src).unwrap()).unwrap().bookings;
+        let (mut txs, db, prec) = setup(src);
+        let lots = realize_capital(&mut txs, &caps(), &bookings, &AccountTypes::default(), None, &db, &prec).unwrap();
+        assert_eq!(gain_on(&txs[2..3], "income:capital"), Decimal::parse("-50").unwrap());
+        assert_eq!(gain_on(&txs, "income:capital"), Decimal::parse("-350").unwrap());
+        assert_eq!(lots.realized[0].acquired, Date::parse("2024-02-01").unwrap());
+    }
+
+    #[test]
+    fn specific_selector_matching_no_lot_is_an_error() {
+        let src = "\
+            account assets:btc\n\
+            \tbooking specific\n\
+            2024-01-01 buy\n\
+            \tassets:btc    1 BTC @ 100 USD\n\
+            \tassets:cash  -100 USD\n\
+            2024-03-01 sell\n\
+            \tassets:btc   -1 BTC {300 USD} @ 350 USD\n\
+            \tassets:cash   350 USD\n";
+        let bookings = resolver::resolve(parser::parse(src).unwrap()).unwrap().bookings;
+        let (mut txs, db, prec) = setup(src);
+        let err = realize_capital(&mut txs, &caps(), &bookings, &AccountTypes::default(), None, &db, &prec).unwrap_err();
+        assert_eq!(err.to_string(), ":7: no open BTC lot in assets:btc matches {300 USD}");
+    }
+
+    #[test]
+    fn term_accounts_split_a_disposal_by_holding_period() {
+        // Lot a (2023-01-01, @100) is held 517 days by the sale, lot b
+        // (2024-03-01, @300) 92: selling both at 400 books +300 long and
+        // +100 short. With the 600-day threshold both are short.
+        let src = "\
+            2023-01-01 buy a\n\
+            \tassets:btc    1 BTC @ 100 USD\n\
+            \tassets:cash  -100 USD\n\
+            2024-03-01 buy b\n\
+            \tassets:btc    1 BTC @ 300 USD\n\
+            \tassets:cash  -300 USD\n\
+            2024-06-01 sell\n\
+            \tassets:btc   -2 BTC @ 400 USD\n\
+            \tassets:cash   800 USD\n";
+        let terms = |days| CapitalAccounts {
+            short_gain: Some("income:capital:short"),
+            long_gain: Some("income:capital:long"),
+            long_term_days: days,
+            ..caps()
+        };
+        let (mut txs, db, prec) = setup(src);
+        realize_capital(&mut txs, &terms(LONG_TERM_DAYS), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
+        assert_eq!(gain_on(&txs, "income:capital:long"), Decimal::parse("-300").unwrap());
+        assert_eq!(gain_on(&txs, "income:capital:short"), Decimal::parse("-100").unwrap());
+        assert_eq!(gain_on(&txs, "income:capital"), Decimal::zero());
+
+        let (mut txs, db, prec) = setup(src);
+        realize_capital(&mut txs, &terms(600), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
+        assert_eq!(gain_on(&txs, "income:capital:short"), Decimal::parse("-400").unwrap());
+        assert_eq!(gain_on(&txs, "income:capital:long"), Decimal::zero());
+    }
+
+    #[test]
+    fn split_and_convert_rescale_lots_without_a_gain() {
+        // 10 AAPL @200 split 4:1 → 40 AAPL @50, still dated 2024-01-01:
+        // nothing realized on the split, selling 40 @55 realizes 200.
+        let src = "\
+            2024-01-01 buy\n\
+            \tassets:broker    10 AAPL @ 200 USD\n\
+            \tassets:cash   -2000 USD\n\
+            2024-06-10 split AAPL 4:1\n\
+            2024-07-01 sell\n\
+            \tassets:broker   -40 AAPL @ 55 USD\n\
+            \tassets:cash    2200 USD\n";
+        let (mut txs, db, prec) = setup(src);
+        let lots = realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
+        assert_eq!(gain_on(&txs, "income:capital"), Decimal::parse("-200").unwrap());
+        assert!(lots.open.iter().all(|lot| lot.commodity != "AAPL"));
+        assert_eq!(lots.realized.len(), 1);
+        assert_eq!(lots.realized[0].qty, Decimal::from(40));
+        assert_eq!(lots.realized[0].acquired.to_string(), "2024-01-01");
+        // The swap legs carry the lot at its old and new cost.
+        let swap = &txs[1].value;
+        assert_eq!(swap.description, "split AAPL 4:1");
+        let costs: Vec<Decimal> =
+            swap.postings.iter().map(|lp| lp.value.lot_cost.as_ref().unwrap().amount.value).collect();
+        assert_eq!(costs, [Decimal::from(200), Decimal::from(50)]);
+
+        let src = "\
+            2024-01-01 buy\n\
+            \tassets:wallet     2 OLD @ 3 USD\n\
+            \tassets:cash      -6 USD\n\
+            2024-06-10 convert OLD NEW 1000\n";
+        let (mut txs, db, prec) = setup(src);
+        let lots = realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
+        assert_eq!(gain_on(&txs, "income:capital"), Decimal::zero());
+        let [lot] = lots.open.as_slice() else { panic!("expected one open lot") };
+        assert_eq!((lot.commodity.as_str(), lot.qty), ("NEW", Decimal::from(2000)));
+        assert_eq!(lot.cost_commodity, "USD");
+        assert_eq!(lot.cost_per_unit, Decimal::parse("0.003").unwrap());
+        assert_eq!(lot.date.to_string(), "2024-01-01");
+    }
+
+    #[test]
+    fn only_the_tagged_swap_rescales_lots() {
+        // A sale that happens to read like the swap is still a sale.
+        let src = "\
+            2024-01-01 buy\n\
+            \tassets:broker    10 AAPL @ 200 USD\n\
+            \tassets:cash   -2000 USD\n\
+            2024-06-10 split AAPL 4:1\n\
+            2024-06-10 split AAPL 4:1\n\
+            \tassets:broker   -40 AAPL @ 55 USD\n\
+            \tassets:cash    2200 USD\n";
+        let (mut txs, db, prec) = setup(src);
+        assert!(txs[1].value.metadata.get(SWAP_TAG).is_some());
+        assert!(txs[2].value.metadata.get(SWAP_TAG).is_none());
+        let lots = realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
+        assert_eq!(gain_on(&txs, "income:capital"), Decimal::parse("-200").unwrap());
+        assert!(lots.open.iter().all(|lot| lot.commodity != "AAPL"));
+    }
+
+    #[test]
+    fn transfer_moves_lots_with_date_and_cost() {
+        // Two lots bought on the exchange; 1.5 BTC moves to the wallet
+        // and 0.01 BTC pays the network fee. The wallet receives the 100
+        // lot whole and 0.5 of the 200 lot, dated as bought; the fee
+        // leaves the books, a disposal of 0.01 of lot b at the day's 300:
+        // 0.01×(300−200) = 1. Selling the wallet then realizes
+        // 1×(400−100) + 0.5×(400−200) = 400 — nothing on the move itself.
+        let src = "\
+            P 2024-03-01 BTC USD 300\n\
+            2024-01-01 buy a\n\
+            \tassets:exchange    1 BTC @ 100 USD\n\
+            \tassets:cash       -100 USD\n\
+            2024-02-01 buy b\n\
+            \tassets:exchange    1 BTC @ 200 USD\n\
+            \tassets:cash       -200 USD\n\
+            2024-03-01 withdraw\n\
+            \tassets:exchange   -1.51 BTC\n\
+            \tassets:wallet      1.5 BTC\n\
+            \texpenses:fees      0.01 BTC\n\
+            2024-06-01 sell\n\
+            \tassets:wallet     -1.5 BTC @ 400 USD\n\
+            \tassets:cash        600 USD\n";
+        let (mut txs, db, prec) = setup(src);
+        let lots = realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec)
+            .unwrap();
+        assert_eq!(gain_on(&txs[2..3], "income:capital"), Decimal::parse("-1").unwrap());
+        assert_eq!(gain_on(&txs, "income:capital"), Decimal::parse("-401").unwrap());
+        let withdraw = &txs[2].value;
+        let wallet: Vec<String> = withdraw
+            .postings
+            .iter()
+            .filter(|lp| lp.value.account == "assets:wallet")
+            .map(|lp| lp.value.lot_date.unwrap().to_string())
+            .collect();
+        assert_eq!(wallet, vec!["2024-01-01", "2024-02-01"]);
+        // The fee is priced at the day's value and holds no lot.
+        let fee = withdraw.postings.iter().find(|lp| lp.value.account == "expenses:fees").unwrap();
+        assert!(fee.value.lot_cost.is_none());
+        assert!(matches!(&fee.value.costs, Some(Costs::PerUnit(c)) if c.value == Decimal::from(300)));
+        assert!(lots.open.iter().all(|l| l.account != "expenses:fees"));
+        assert_eq!(lots.realized[0].qty, Decimal::parse("0.01").unwrap());
+        // The exchange keeps the remaining 0.49 of lot b.
+        let sent = withdraw
+            .postings
+            .iter()
+            .filter(|lp| lp.value.account == "assets:exchange")
+            .filter_map(|lp| lp.value.amount.as_ref())
+            .fold(Decimal::zero(), |acc, a| acc + a.value);
+        assert_eq!(sent, Decimal::parse("-1.51").unwrap());
+        let exchange = lots.open.iter().find(|l| l.account == "assets:exchange").unwrap();
+        assert_eq!(exchange.qty, Decimal::parse("0.49").unwrap());
+    }
+
+    #[test]
+    fn spending_from_an_own_account_realizes_the_gain() {
+        // BTC spent on food leaves the books: a disposal at the day's
+        // market value, not a lot filed under expenses.
+        let src = "\
+            P 2024-06-01 BTC EUR 50000\n\
+            2024-01-01 * buy\n\
+            \tassets:wallet   1 BTC @ 100 EUR\n\
+            \tassets:cash  -100 EUR\n\
+            2024-06-01 * pizza\n\
+            \texpenses:food   0.01 BTC\n\
+            \tassets:wallet  -0.01 BTC\n";
+        let (mut txs, db, prec) = setup(src);
+        let lots = realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec)
+            .unwrap();
+        assert_eq!(gain_on(&txs, "income:capital"), Decimal::from(-499));
+        assert!(lots.open.iter().all(|l| l.account == "assets:wallet"));
+        // A declared type overrides the root name: an asset-typed
+        // `expenses:food` is one of our own accounts again.
+        let types = AccountTypes {
+            declared: HashMap::from([("expenses:food".to_string(), AccountType::Asset)]),
+        };
+        let (mut txs, db, prec) = setup(src);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &types, None, &db, &prec).unwrap();
+        assert!(!any_capital(&txs));
+    }
+
+    #[test]
+    fn spending_sale_proceeds_is_no_disposal() {
+        // The USD an AAPL sale brings in (and the EUR a BTC sale does) is
+        // the money of that trade, not a position: spending it later
+        // neither realizes a gain nor annotates the spending.
+        let src = "\
+            2024-01-01 * buy\n\
+            \tassets:broker   10 AAPL @ 100 USD\n\
+            \tassets:cash  -1000 USD\n\
+            2024-03-01 * sell\n\
+            \tassets:broker  -10 AAPL @ 120 USD\n\
+            \tassets:cash   1200 USD\n\
+            2024-04-01 * sell\n\
+            \tassets:btc   -0.1 BTC\n\
+            \tassets:bank   5000 EUR\n\
+            2024-08-01 * groceries\n\
+            \texpenses:food   100 USD\n\
+            \tassets:cash\n\
+            2024-08-02 * coffee\n\
+            \texpenses:food   5 EUR\n\
+            \tassets:bank\n";
+        let (mut txs, db, prec) = setup(src);
+        let lots = realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec)
+            .unwrap();
+        for lt in &txs[3..] {
+            assert_eq!(lt.value.postings.len(), 2);
+            for lp in &lt.value.postings {
+                assert!(!lp.value.account.contains("capital"));
+                assert!(lp.value.costs.is_none() && lp.value.lot_cost.is_none() && lp.value.lot_date.is_none());
+            }
+        }
+        assert_eq!(gain_on(&txs, "income:capital"), Decimal::from(-200));
+        assert_eq!(lots.realized.len(), 1);
+        assert_eq!(lots.realized[0].commodity, "AAPL");
+        assert!(lots.open.iter().all(|l| l.commodity != "USD" && l.commodity != "EUR"));
+    }
+
     #[test]
     fn loss_routes_to_loss_account() {
         // Bought for 50000, sold for 30000: a 20000 loss → expenses:capital,
@@ -618,7 +1832,7 @@ mod tests {
             \tassets:btc   -1 BTC @ 30000 USD\n\
             \tassets:cash   30000 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), None, &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
         // Loss routes to the expense account, positive.
         assert_eq!(gain_on(&txs, "expenses:capital"), Decimal::parse("20000").unwrap());
         assert_eq!(gain_on(&txs, "income:capital"), Decimal::zero());
@@ -638,8 +1852,30 @@ mod tests {
             \tassets:cash   150 USD\n\
             \tincome:trade     -50 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), None, &db, &prec);
+        let lots = realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
         assert!(!any_capital(&txs));
+        // Still a `gains` row: the lot it closed, at the `@` proceeds.
+        let btc: Vec<&RealizedLot> = lots.realized.iter().filter(|r| r.commodity == "BTC").collect();
+        assert_eq!(btc.len(), 1);
+        assert_eq!(btc[0].gain, Decimal::from(50));
+    }
+
+    #[test]
+    fn carried_lot_reopens_at_its_cost_and_date() {
+        // An opening balance written `{{cost}} [lot-date]` re-opens the
+        // lot, so a later sale realizes against the original acquisition.
+        let src = "\
+            2025-01-01 * opening balances\n\
+            \tassets:btc  1 BTC {{100 USD}} [2023-05-01]\n\
+            \tequity:opening\n\
+            2025-06-01 sell\n\
+            \tassets:btc   -1 BTC @ 150 USD\n\
+            \tassets:cash   150 USD\n";
+        let (mut txs, db, prec) = setup(src);
+        let lots = realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
+        assert_eq!(lots.realized.len(), 1);
+        assert_eq!(lots.realized[0].acquired.to_string(), "2023-05-01");
+        assert_eq!(lots.realized[0].gain, Decimal::from(50));
     }
 
     #[test]
@@ -653,8 +1889,35 @@ mod tests {
             \tassets:btc   -1 BTC\n\
             \tassets:cash   30000 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), None, &db, &prec);
+        let lots = realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
+        assert!(!any_capital(&txs));
+        // The disposal is still listed, at a zero gain.
+        let btc: Vec<&RealizedLot> = lots.realized.iter().filter(|r| r.commodity == "BTC").collect();
+        assert_eq!(btc.len(), 1);
+        assert_eq!(btc[0].proceeds, Decimal::from(30000));
+        assert!(btc[0].gain.is_zero());
+    }
+
+    #[test]
+    fn mixed_currency_disposal_is_listed_at_a_converted_value() {
+        // Bought in USD, sold for EUR: no capital posting (the two can't
+        // be netted), but the row is listed with the proceeds in USD.
+        let src = "\
+            P 2024-06-01 EUR USD 1.2\n\
+            2024-01-01 buy\n\
+            \tassets:btc    1 BTC @ 100 USD\n\
+            \tassets:cash  -100 USD\n\
+            2024-06-01 sell\n\
+            \tassets:btc   -1 BTC @ 90 EUR\n\
+            \tassets:cash   90 EUR\n";
+        let (mut txs, db, prec) = setup(src);
+        let lots = realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
         assert!(!any_capital(&txs));
+        let btc: Vec<&RealizedLot> = lots.realized.iter().filter(|r| r.commodity == "BTC").collect();
+        assert_eq!(btc.len(), 1);
+        assert_eq!(btc[0].currency, "USD");
+        assert_eq!(btc[0].proceeds, Decimal::from(108));
+        assert_eq!(btc[0].gain, Decimal::from(8));
     }
 
     #[test]
@@ -669,7 +1932,7 @@ mod tests {
             \tassets:btc   -1 BTC @ 150 USD\n\
             \tassets:cash   150 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), None, &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
         // The single disposal leg now carries {cost} and [lot-date].
         let legs = split_legs(&txs, "BTC");
         assert_eq!(legs.len(), 1);
@@ -702,7 +1965,7 @@ mod tests {
             \tassets:btc  -10 BTC @ 120 USD\n\
             \tassets:cash  1200 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), None, &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
         let legs = split_legs(&txs, "BTC");
         assert_eq!(legs.len(), 2);
         assert!(legs.iter().all(|p| p.lot_date.is_some()));
@@ -720,7 +1983,7 @@ mod tests {
             \tassets:btc   -1 BTC @ 150 USD\n\
             \tassets:cash   150 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), None, &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
         let legs = split_legs(&txs, "BTC");
         assert_eq!(legs.len(), 1);
         let leg = legs[0];
@@ -752,7 +2015,7 @@ mod tests {
             \tassets:btc   -2 BTC @ 200 USD\n\
             \tassets:cash   400 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), None, &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
         // Full 2 BTC still disposed across the rewritten legs.
         let disposed = txs
             .iter()
@@ -777,7 +2040,7 @@ mod tests {
             \tassets:btc    1 BTC\n\
             \tassets:cash  -30000 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), None, &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
         assert!(!any_capital(&txs));
     }
 
@@ -797,7 +2060,7 @@ mod tests {
             \tassets:bank   -103 EUR\n\
             \tassets:usd     100 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), Some("EUR"), &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), Some("EUR"), &db, &prec).unwrap();
         assert_eq!(gain_on(&txs, "income:capital"), Decimal::parse("-2").unwrap());
     }
 
@@ -821,7 +2084,7 @@ mod tests {
             \tassets:eth     -1 ETH\n\
             \tassets:btc   0.08 BTC\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), Some("EUR"), &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), Some("EUR"), &db, &prec).unwrap();
         assert_eq!(gain_on(&txs, "income:capital"), Decimal::parse("-200").unwrap());
         assert_eq!(gain_on(&txs, "expenses:capital"), Decimal::zero());
     }
@@ -843,7 +2106,7 @@ mod tests {
             \tassets:btc   -2 BTC @ 200 USD\n\
             \tassets:cash   400 USD\n";
         let (mut txs, db, prec) = setup(src);
-        realize_capital(&mut txs, &caps(), Some("EUR"), &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), Some("EUR"), &db, &prec).unwrap();
         // Exactly one covered lot carries a {} cost; the uncovered 1 BTC
         // is a plain leg.
         assert_eq!(split_legs(&txs, "BTC").len(), 1);
@@ -861,7 +2124,7 @@ mod tests {
             \tassets:cash  -50 USD\n";
         let (mut txs, db, prec) = setup(src);
         // Must not panic, and realizes nothing.
-        realize_capital(&mut txs, &caps(), None, &db, &prec);
+        realize_capital(&mut txs, &caps(), &Bookings::default(), &AccountTypes::default(), None, &db, &prec).unwrap();
         assert!(!any_capital(&txs));
     }
 }
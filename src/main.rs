@@ -28,9 +28,9 @@ struct Args {
 
 /// Filter / sort / conversion flags shared by every report-style
 /// command (`balance`, `register`, `print`, `accounts`, `codes`,
-/// `commodities`, `navigate`). Flattened into each variant via
-/// `#[command(flatten)]` so `acc format --help` and `acc update
-/// --help` stay uncluttered.
+/// `commodities`, `lots`, `gains`, `bs`, `is`, `cashflow`, `budget`,
+/// `navigate`). Flattened into each variant via `#[command(flatten)]` so
+/// `acc format --help` and `acc update --help` stay uncluttered.
 #[derive(ClapArgs, Clone, Debug)]
 struct ReportArgs {
     /// Include only transactions on or after this date (YYYY-MM-DD)
@@ -49,12 +49,29 @@ struct ReportArgs {
     #[arg(long = "period", short = 'p', value_name = "PERIOD")]
     periods: Vec<String>,
 
+    /// Report by auxiliary (effective) date: a transaction written
+    /// `DATE=AUXDATE`, or a posting carrying a `; [=DATE]` comment, is
+    /// date-filtered, sorted, shown in the register and converted under
+    /// `-X` at that date instead of its primary one. Entries without an
+    /// aux date keep their primary date.
+    #[arg(long = "aux-date")]
+    aux_date: bool,
+
     /// Include transactions dated after today. Hidden by default so
     /// forward-dated recurring entries (rent, subscriptions) don't
     /// clutter "what has happened" reports.
     #[arg(long)]
     future: bool,
 
+    /// Project the recurring rules (`~ EVERY from DATE`) up to DATE: each
+    /// occurrence after the last recorded transaction is added to the
+    /// journal before filtering, tagged `forecast`, and the future cutoff
+    /// moves out to DATE. Accepts YYYY, YYYY-MM or YYYY-MM-DD (its start);
+    /// DATE itself is excluded. `acc bal ^assets:checking --forecast
+    /// 2027-01` is the balance going into January.
+    #[arg(long = "forecast", value_name = "DATE")]
+    forecast: Option<String>,
+
     /// Show real postings only — drop every virtual posting
     /// (paren-virtual `(account)` and bracket-virtual `[account]`)
     /// from the output. The realizer, lotter and translator inject
@@ -127,14 +144,33 @@ struct ReportArgs {
     exchange: Option<String>,
 
     /// Show unrealized revaluation: revalue open foreign-currency balances at the
-    /// latest available exchange rate, instead of the historical
-    /// per-posting valuation. Revalues every open foreign balance (scope
-    /// with a filter); off by default — the default stays historical
-    /// (realized only). Only meaningful together with `-X`, and requires
-    /// `holding gain` / `holding loss` accounts to be declared.
+    /// exchange rate on the report's end date (`-e` / `-p`, or
+    /// `--value-date`; else the latest available rate), instead of the
+    /// historical per-posting valuation. Revalues every open foreign
+    /// balance (scope with a filter); off by default — the default stays
+    /// historical (realized only). Only meaningful together with `-X`, and
+    /// requires `holding gain` / `holding loss` accounts to be declared.
     #[arg(short = 'V', long = "unrealized")]
     unrealized: bool,
 
+    /// The day `-V` revalues open positions as of (YYYY-MM-DD): the
+    /// positions held at its close, at the rates in force on it, booked on
+    /// it. Defaults to the last day before `-e`, or of a single `-p`.
+    #[arg(long = "value-date", value_name = "DATE", requires = "unrealized")]
+    value_date: Option<String>,
+
+    /// Translate the accounts `--rate-accounts` selects (default: those
+    /// declared `type income` / `type expense`) at the average rate of
+    /// their month or year instead of the spot rate on each posting's
+    /// date. The difference is booked to the `cta gain` / `cta loss`
+    /// accounts. Only with `-X`.
+    #[arg(long = "rate-method", value_enum, value_name = "METHOD", requires = "exchange")]
+    rate_method: Option<RateMethod>,
+
+    /// Account pattern `--rate-method` averages. Repeat to add patterns.
+    #[arg(long = "rate-accounts", value_name = "PATTERN", requires = "rate_method")]
+    rate_accounts: Vec<String>,
+
     /// Keep only transactions whose balance-contributing postings use
     /// at least N distinct commodities (paren-virtual slippage labels are
     /// ignored). Counts native commodities — applied before `-X`
@@ -147,6 +183,64 @@ struct ReportArgs {
     /// least two commodities.
     #[arg(long = "mixed")]
     mixed: bool,
+
+    /// Keep only cleared postings (`*`): a posting's own marker, else its
+    /// transaction's. Combines with `--pending` / `-U` as "any of".
+    #[arg(short = 'C', long = "cleared")]
+    cleared: bool,
+
+    /// Keep only pending postings (`!`).
+    #[arg(long = "pending")]
+    pending: bool,
+
+    /// Keep only uncleared postings (no marker).
+    #[arg(short = 'U', long = "uncleared")]
+    uncleared: bool,
+
+    /// Write the report as structured rows: text, json, csv or tsv. Not
+    /// for `navigate` or `print --raw`.
+    #[arg(short = 'O', long = "output-format", value_enum, default_value = "text")]
+    output_format: OutputFormat,
+
+    /// Fail, listing each posting's `file:line`, when any posting has no
+    /// rate path to the `-X` target — instead of leaving it in its own
+    /// commodity. `acc missing-rates` groups the same postings.
+    #[arg(long = "strict-rates", requires = "exchange")]
+    strict_rates: bool,
+
+    /// Pass over a rate whose `P` entry is more than DAYS days from the
+    /// day it would value, and try other paths. A commodity's own
+    /// `max-age N` takes precedence. Each refused rate is warned about.
+    #[arg(long = "max-rate-age", value_name = "DAYS", requires = "exchange")]
+    max_rate_age: Option<u32>,
+}
+
+/// Values of `--rate-method`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum RateMethod {
+    Spot,
+    MonthlyAverage,
+    YearlyAverage,
+}
+
+/// Values of `-O/--output-format`.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+    Tsv,
+}
+
+impl From<OutputFormat> for acc::commands::output::Format {
+    fn from(f: OutputFormat) -> Self {
+        match f {
+            OutputFormat::Text => Self::Text,
+            OutputFormat::Json => Self::Json,
+            OutputFormat::Csv => Self::Csv,
+            OutputFormat::Tsv => Self::Tsv,
+        }
+    }
 }
 
 /// The lint checks, selectable as positional arguments to `lint`. With
@@ -188,6 +282,21 @@ enum Command {
         /// Show accounts with zero balance
         #[arg(short = 'E', long)]
         empty: bool,
+        /// One column per month: each account's change over the month
+        #[arg(short = 'M', long, group = "interval")]
+        monthly: bool,
+        /// One column per quarter
+        #[arg(short = 'Q', long, group = "interval")]
+        quarterly: bool,
+        /// One column per year
+        #[arg(short = 'Y', long, group = "interval")]
+        yearly: bool,
+        /// With a column per period, add a total column per row
+        #[arg(short = 'T', long = "row-total", requires = "interval")]
+        row_total: bool,
+        /// With a column per period, add an average column per row
+        #[arg(long, requires = "interval")]
+        average: bool,
         /// Filter by account name pattern
         pattern: Vec<String>,
     },
@@ -195,6 +304,10 @@ enum Command {
     Register {
         #[command(flatten)]
         filter: ReportArgs,
+        /// Under `-X`, note under each converted posting the rate path, each
+        /// hop's rate and the date of the `P` entry it came from.
+        #[arg(long = "explain-rates", requires = "exchange")]
+        explain_rates: bool,
         /// Filter by account name pattern
         pattern: Vec<String>,
     },
@@ -205,6 +318,10 @@ enum Command {
         /// Show raw data without computed amounts
         #[arg(long)]
         raw: bool,
+        /// Under `-X`, add a `; rate: …` comment to each converted posting:
+        /// the rate path, each hop's rate and the date of its `P` entry.
+        #[arg(long = "explain-rates", requires = "exchange", conflicts_with = "raw")]
+        explain_rates: bool,
         /// Filter by account name pattern
         pattern: Vec<String>,
     },
@@ -238,6 +355,118 @@ enum Command {
         /// Filter by account name pattern
         pattern: Vec<String>,
     },
+    /// List open lots with cost basis, holding days and, under `-X`,
+    /// market value and unrealized gain. `-e` sets the as-of date.
+    Lots {
+        #[command(flatten)]
+        filter: ReportArgs,
+        /// Filter by account name pattern
+        pattern: Vec<String>,
+    },
+    /// List realized gains, one row per closed lot — quantity, acquisition
+    /// and disposal date, proceeds, cost basis, gain and term — with
+    /// totals per commodity and per year. `-b` / `-e` / `-p` select by
+    /// disposal date.
+    Gains {
+        #[command(flatten)]
+        filter: ReportArgs,
+        /// Filter by account name pattern
+        pattern: Vec<String>,
+    },
+    /// Balance sheet: assets, liabilities and equity by declared account
+    /// `type` as of `-e`, with income and expense rolled into equity.
+    /// `-b` starts the net-income period; earlier results are retained
+    /// earnings.
+    Bs {
+        #[command(flatten)]
+        filter: ReportArgs,
+        /// Show accounts with zero balance
+        #[arg(short = 'E', long)]
+        empty: bool,
+        /// Filter by account name pattern
+        pattern: Vec<String>,
+    },
+    /// Income statement: income and expenses by declared account `type`
+    /// over the period, income shown positive, with the net income
+    #[command(name = "is")]
+    Is {
+        #[command(flatten)]
+        filter: ReportArgs,
+        /// Show accounts with zero balance
+        #[arg(short = 'E', long)]
+        empty: bool,
+        /// Filter by account name pattern
+        pattern: Vec<String>,
+    },
+    /// Cash flow statement: opening balance, movement by counter-party in
+    /// operating / investing / financing sections, net change and closing
+    /// balance of the cash accounts the pattern names
+    Cashflow {
+        #[command(flatten)]
+        filter: ReportArgs,
+        /// Show accounts with zero balance
+        #[arg(short = 'E', long)]
+        empty: bool,
+        /// Cash account pattern
+        pattern: Vec<String>,
+    },
+    /// Compare actual balances with the `~~` budget blocks: actual,
+    /// budget, difference and percent used per budgeted account
+    Budget {
+        #[command(flatten)]
+        filter: ReportArgs,
+        /// One table per month
+        #[arg(short = 'M', long, group = "interval")]
+        monthly: bool,
+        /// One table per quarter
+        #[arg(short = 'Q', long, group = "interval")]
+        quarterly: bool,
+        /// One table per year
+        #[arg(short = 'Y', long, group = "interval")]
+        yearly: bool,
+        /// Filter by account name pattern
+        pattern: Vec<String>,
+    },
+    /// Net worth over time: the cumulative balance of the asset and
+    /// liability accounts (or of the pattern's) at the end of every period
+    Networth {
+        #[command(flatten)]
+        filter: ReportArgs,
+        /// Sample at the end of every day
+        #[arg(short = 'D', long, group = "interval")]
+        daily: bool,
+        /// Sample at the end of every week (Monday to Sunday)
+        #[arg(short = 'W', long, group = "interval")]
+        weekly: bool,
+        /// Sample at the end of every month (default)
+        #[arg(short = 'M', long, group = "interval")]
+        monthly: bool,
+        /// Sample at the end of every quarter
+        #[arg(short = 'Q', long, group = "interval")]
+        quarterly: bool,
+        /// Sample at the end of every year
+        #[arg(short = 'Y', long, group = "interval")]
+        yearly: bool,
+        /// Draw a sparkline per commodity under the table
+        #[arg(long)]
+        chart: bool,
+        /// With -X, value each sample at the rates on its own date instead
+        /// of each posting's historical value
+        #[arg(long, requires = "exchange", conflicts_with = "unrealized")]
+        market: bool,
+        /// Filter by account name pattern (default: accounts of type asset
+        /// or liability)
+        pattern: Vec<String>,
+    },
+    /// The exchange rates `-X` lacked: per commodity, the days a posting
+    /// needed a rate to the target and found no path, so `acc update` can
+    /// be pointed at exactly those gaps. Requires -X.
+    MissingRates {
+        #[command(flatten)]
+        filter: ReportArgs,
+        /// Filter by account name pattern
+        pattern: Vec<String>,
+    },
     /// Interactive account navigater
     Navigate {
         #[command(flatten)]
@@ -375,6 +604,72 @@ enum Command {
         /// Expense account — used when the remainder is a debit (> 0).
         expense: String,
     },
+    /// Year-end close: print closing entries that zero every income and
+    /// expense account into RETAINED (dated the day before `--date`) and
+    /// opening entries that restore every other balance against OPENING
+    /// (dated `--date`), open lots with their cost and acquisition date.
+    /// Needs declared account `type`s. The entries go to stdout, aligned
+    /// and date-sorted.
+    #[command(arg_required_else_help = true)]
+    Close {
+        /// First day of the new period: YYYY, YYYY-MM or YYYY-MM-DD.
+        /// Everything before it is closed.
+        #[arg(long = "date", value_name = "DATE")]
+        date: String,
+        /// Equity account the income and expense balances close into.
+        #[arg(long = "retained", value_name = "ACCOUNT")]
+        retained: String,
+        /// Equity account the opening balances are booked against.
+        #[arg(long = "opening", value_name = "ACCOUNT")]
+        opening: String,
+    },
+    /// List the upcoming occurrences of the recurring rules (`~ EVERY from
+    /// DATE`), from the day after the last recorded transaction up to
+    /// UNTIL, as a register. With `--emit`, print them instead as aligned,
+    /// untagged ledger entries to stdout, ready to append to the journal,
+    /// e.g. `acc forecast 2027-01 --emit >> FILE`.
+    #[command(arg_required_else_help = true)]
+    Forecast {
+        /// End of the forecast (exclusive): YYYY, YYYY-MM or YYYY-MM-DD.
+        until: String,
+        /// Print the occurrences as ledger entries.
+        #[arg(long, conflicts_with = "output_format")]
+        emit: bool,
+        /// Write the register as structured rows: text, json, csv or tsv.
+        #[arg(short = 'O', long = "output-format", value_enum, default_value = "text")]
+        output_format: OutputFormat,
+    },
+    /// The exchange rate from FROM to TO on DATE (default today), with the
+    /// path `-X` takes to it: each hop's rate and the date of the `P`
+    /// entry it used. Reads the journal and, like `-X`, `$PRICES`.
+    #[command(arg_required_else_help = true)]
+    Rate {
+        /// Commodity to convert from.
+        from: String,
+        /// Commodity to convert to.
+        to: String,
+        /// The day to look the rate up on (YYYY-MM-DD).
+        date: Option<String>,
+    },
+    /// Reconcile an account against a bank statement in an interactive
+    /// screen: mark the postings on the statement, compare the cleared
+    /// balance with the statement's, and write the `*` markers back.
+    #[command(arg_required_else_help = true)]
+    Reconcile {
+        /// The account to reconcile (its full name).
+        account: String,
+        /// The statement's ending balance; it can also be typed in the
+        /// screen.
+        #[arg(long = "balance", value_name = "AMOUNT", allow_hyphen_values = true)]
+        balance: Option<String>,
+        /// The commodity to reconcile, when the account holds several.
+        #[arg(long = "commodity", value_name = "SYMBOL")]
+        commodity: Option<String>,
+        /// On finishing, append a balance assertion for the statement
+        /// balance dated DATE (YYYY-MM-DD), the statement date.
+        #[arg(long = "assert", value_name = "DATE")]
+        assert: Option<String>,
+    },
     /// Rename an account across the `-f` files / directories by prefix —
     /// `rename foo:5 foo:4` renames `foo:5`, `foo:50`, `foo:5:…` (OLD need
     /// not be a whole segment); anchored at the start, so `bar:foo:5` is
@@ -472,6 +767,14 @@ impl Command {
             | Self::Accounts { pattern, .. }
             | Self::Codes { pattern, .. }
             | Self::Commodities { pattern, .. }
+            | Self::Lots { pattern, .. }
+            | Self::Gains { pattern, .. }
+            | Self::Bs { pattern, .. }
+            | Self::Is { pattern, .. }
+            | Self::Cashflow { pattern, .. }
+            | Self::Budget { pattern, .. }
+            | Self::Networth { pattern, .. }
+            | Self::MissingRates { pattern, .. }
             | Self::Navigate { pattern, .. } => pattern.as_slice(),
             Self::Update { .. }
             | Self::Lint { .. }
@@ -480,6 +783,10 @@ impl Command {
             | Self::Import { .. }
             | Self::Rename { .. }
             | Self::Completions { .. }
+            | Self::Close { .. }
+            | Self::Forecast { .. }
+            | Self::Rate { .. }
+            | Self::Reconcile { .. }
             | Self::Sweep { .. } => &[],
         }
     }
@@ -495,6 +802,14 @@ impl Command {
             | Self::Accounts { filter, .. }
             | Self::Codes { filter, .. }
             | Self::Commodities { filter, .. }
+            | Self::Lots { filter, .. }
+            | Self::Gains { filter, .. }
+            | Self::Bs { filter, .. }
+            | Self::Is { filter, .. }
+            | Self::Cashflow { filter, .. }
+            | Self::Budget { filter, .. }
+            | Self::Networth { filter, .. }
+            | Self::MissingRates { filter, .. }
             | Self::Navigate { filter, .. } => Some(filter),
             Self::Update { .. }
             | Self::Lint { .. }
@@ -503,6 +818,10 @@ impl Command {
             | Self::Import { .. }
             | Self::Rename { .. }
             | Self::Completions { .. }
+            | Self::Close { .. }
+            | Self::Forecast { .. }
+            | Self::Rate { .. }
+            | Self::Reconcile { .. }
             | Self::Sweep { .. } => None,
         }
     }
@@ -750,6 +1069,122 @@ fn try_standalone(
             )
         }
 
+        // Close loads the journal up to its date and runs the enrichment
+        // phases natively, so the lots and realized gains it carries forward
+        // are in place. No report flags.
+        Command::Close { date, retained, opening } => {
+            if paths.is_empty() {
+                eprintln!("Error: No files specified. Use -f PATH.");
+                std::process::exit(1);
+            }
+            let date = period_start(date);
+            let mut close_paths: Vec<std::path::PathBuf> = Vec::new();
+            for input in paths {
+                let path = std::path::Path::new(input);
+                if path.is_dir() {
+                    collect_ledger_files(path, &mut close_paths);
+                } else {
+                    close_paths.push(path.to_path_buf());
+                }
+            }
+            Some(acc::load(&close_paths).map_err(|e| acc::Error::from(e.to_string())).and_then(
+                |mut j| {
+                    j.transactions.retain(|lt| lt.value.date < date);
+                    acc::pipeline::enrich(&mut j, None, false, None, None)?;
+                    acc::commands::close::run(&j, date, retained, opening)
+                },
+            ))
+        }
+
+        // Forecast loads and books the journal like sweep, then projects the
+        // recurring rules past its last transaction. No report flags.
+        Command::Forecast { until, emit, output_format } => {
+            if paths.is_empty() {
+                eprintln!("Error: No files specified. Use -f PATH.");
+                std::process::exit(1);
+            }
+            let until = period_start(until);
+            let mut forecast_paths: Vec<std::path::PathBuf> = Vec::new();
+            for input in paths {
+                let path = std::path::Path::new(input);
+                if path.is_dir() {
+                    collect_ledger_files(path, &mut forecast_paths);
+                } else {
+                    forecast_paths.push(path.to_path_buf());
+                }
+            }
+            Some(
+                acc::load(&forecast_paths)
+                    .map_err(|e| acc::Error::from(e.to_string()))
+                    .and_then(|j| acc::commands::forecast::run(j, until, *emit, (*output_format).into())),
+            )
+        }
+
+        // Rate loads the journal and the whole `$PRICES` tree — the pair
+        // asked about need not be held, so the `-X` selective load would
+        // drop its prices. No report flags.
+        Command::Rate { from, to, date } => {
+            let date = date.as_deref().map_or_else(acc::date::Date::today, |d| {
+                acc::date::Date::parse(d).unwrap_or_else(|e| fail(&e))
+            });
+            let mut rate_paths: Vec<std::path::PathBuf> = Vec::new();
+            if let Ok(dir) = std::env::var("PRICES") {
+                let path = std::path::Path::new(&dir);
+                if path.is_dir() {
+                    collect_ledger_files(path, &mut rate_paths);
+                }
+            }
+            for input in paths {
+                let path = std::path::Path::new(input);
+                if path.is_dir() {
+                    collect_ledger_files(path, &mut rate_paths);
+                } else {
+                    rate_paths.push(path.to_path_buf());
+                }
+            }
+            if rate_paths.is_empty() {
+                eprintln!("Error: No files specified. Use -f PATH.");
+                std::process::exit(1);
+            }
+            Some(
+                acc::load(&rate_paths)
+                    .map_err(|e| acc::Error::from(e.to_string()))
+                    .and_then(|j| acc::commands::rate::run(&j, from, to, date)),
+            )
+        }
+
+        // Reconcile loads and books the journal like sweep, then rewrites
+        // the marked posting lines in place. No report flags.
+        Command::Reconcile { account, balance, commodity, assert } => {
+            if paths.is_empty() {
+                eprintln!("Error: No files specified. Use -f PATH.");
+                std::process::exit(1);
+            }
+            let assert = assert
+                .as_deref()
+                .map(|d| acc::date::Date::parse(d).unwrap_or_else(|e| fail(&e)));
+            let mut reconcile_paths: Vec<std::path::PathBuf> = Vec::new();
+            for input in paths {
+                let path = std::path::Path::new(input);
+                if path.is_dir() {
+                    collect_ledger_files(path, &mut reconcile_paths);
+                } else {
+                    reconcile_paths.push(path.to_path_buf());
+                }
+            }
+            Some(acc::load(&reconcile_paths).map_err(|e| acc::Error::from(e.to_string())).and_then(
+                |j| {
+                    acc::commands::reconcile::run(
+                        &j,
+                        account,
+                        commodity.as_deref(),
+                        balance.as_deref(),
+                        assert,
+                    )
+                },
+            ))
+        }
+
         // Rename rewrites account names across the `-f` files. It does its
         // own per-file parse + surgical edit (never the report pipeline).
         // Config files carry no postings, so passing them along with `-f`
@@ -811,6 +1246,16 @@ fn try_standalone(
     }
 }
 
+/// The end of a `--forecast` / `acc forecast` window, or the `acc close`
+/// date: the start of the given year, month or day. Exits via `fail` on a
+/// malformed value.
+fn period_start(s: &str) -> acc::date::Date {
+    match expand_period(s) {
+        Ok((start, _)) => acc::date::Date::parse(&start).expect("expand_period returns YYYY-MM-DD"),
+        Err(e) => fail(&e),
+    }
+}
+
 /// Resolve the effective date filter from `-b` / `-e` / `-p` plus the
 /// default "hide future" cutoff (today+1, exclusive). Returns the
 /// expanded period ranges (consumed by the multi-period filter) and owned
@@ -856,12 +1301,16 @@ fn resolve_date_range(
     // and including today. With an explicit `-e`/`-p`, take the earlier.
     // Only report commands reach here (lint and the other standalone commands
     // returned earlier), so the cutoff never touches a whole-journal check.
+    // `--forecast` moves the cutoff out to its end, so the projected
+    // occurrences show without also needing `--future`.
     let show_future = filter_args.map(|f| f.future).unwrap_or(false);
+    let forecast = filter_args.and_then(|f| f.forecast.as_deref()).map(period_start);
     let future_cap: Option<String> = (!show_future).then(|| {
         let today_str = acc::date::ms_to_date(acc::date::current_ms());
         let today = acc::date::Date::parse(&today_str)
             .expect("current_ms() returns valid YYYY-MM-DD");
-        acc::date::Date::from_days(today.days() + 1).to_string()
+        let tomorrow = acc::date::Date::from_days(today.days() + 1);
+        forecast.map_or(tomorrow, |f| f.max(tomorrow)).to_string()
     });
     let end = match (user_end, future_cap) {
         (Some(u), Some(cap)) => Some(if u < cap { u } else { cap }),
@@ -884,8 +1333,8 @@ fn start() -> Result<(), acc::Error> {
         return Ok(());
     };
 
-    // Standalone commands (format, diff, update, sweep, rename, import,
-    // completions, lint) bypass the report pipeline entirely and return here.
+    // Standalone commands (format, diff, update, sweep, close, forecast,
+    // rename, import, completions, lint) bypass the report pipeline entirely and return here.
     if let Some(result) = try_standalone(&command, &args.paths) {
         return result;
     }
@@ -899,6 +1348,39 @@ fn start() -> Result<(), acc::Error> {
     // reach here — the standalone commands (format, lint, update, …) already
     // returned above via `try_standalone`.
     let filter_args: Option<&ReportArgs> = command.filter();
+    let output_format = filter_args.map_or(OutputFormat::Text, |f| f.output_format);
+    if output_format != OutputFormat::Text {
+        let structured = matches!(
+            command,
+            Command::Balance { .. }
+                | Command::Register { .. }
+                | Command::Print { raw: false, .. }
+                | Command::Accounts { .. }
+                | Command::Commodities { .. }
+                | Command::Codes { .. }
+                | Command::Networth { .. }
+                | Command::MissingRates { .. }
+                | Command::Lots { .. }
+                | Command::Gains { .. }
+                | Command::Bs { .. }
+                | Command::Is { .. }
+                | Command::Cashflow { .. }
+                | Command::Budget { .. }
+        );
+        if !structured {
+            fail("-O/--output-format applies to every report but navigate and print --raw");
+        }
+        if matches!(
+            command,
+            Command::Register { explain_rates: true, .. } | Command::Print { explain_rates: true, .. }
+        ) {
+            fail("--explain-rates annotates text output only");
+        }
+    }
+    let output_format = acc::commands::output::Format::from(output_format);
+    if matches!(command, Command::MissingRates { .. }) && filter_args.is_some_and(|f| f.exchange.is_none()) {
+        fail("missing-rates needs -X COMMODITY: the target the rates are missing to");
+    }
 
     // Price-DB files (`$PRICES`, only under `-X`) are kept separate
     // from the user's journal files so they can be loaded *selectively*: the
@@ -958,6 +1440,17 @@ fn start() -> Result<(), acc::Error> {
     }
     .map_err(|e| acc::Error::from(e.to_string()))?;
 
+    // `--max-rate-age`: from here on the price DB passes over older rates,
+    // for enrichment as much as for the report.
+    journal.prices.set_max_age(filter_args.and_then(|f| f.max_rate_age));
+
+    // `--forecast`: add the projected occurrences of the recurring rules
+    // first, so enrichment, filtering and conversion treat them like
+    // recorded transactions.
+    if let Some(until) = filter_args.and_then(|f| f.forecast.as_deref()) {
+        acc::pipeline::forecast(&mut journal, period_start(until))?;
+    }
+
     // Resolve the `-X` target through the journal's aliases so
     // `-X EUR` and `-X €` both collapse to the canonical symbol the
     // price DB and postings are stored under. Without this, the
@@ -975,18 +1468,61 @@ fn start() -> Result<(), acc::Error> {
 
     // Enrichment phases (expander → realizer → lotter → translator, plus
     // the `--unrealized` revaluator). These must see the whole journal —
-    // the lotter tracks lots FIFO across all transactions; the translator
+    // the lotter tracks lots across all transactions; the translator
     // and revaluator identify pass-through / open positions by their
     // journal-wide native sum — so they run before any filtering, together
     // in `pipeline::enrich`.
-    let unrealized = filter_args.map(|f| f.unrealized).unwrap_or(false);
-    acc::pipeline::enrich(&mut journal, exchange_target.as_deref(), unrealized);
-
+    //
     // Resolve the -b / -e / -p date filter plus the default future
-    // cutoff. The owned bounds are borrowed for the filter below.
+    // cutoff first: `lots` reports the position as of the last day before
+    // `end`, and lot state is causal, so it drops every later transaction
+    // before the lotter sees them.
     let (period_ranges, begin_owned, end_owned) = resolve_date_range(filter_args);
     let begin = begin_owned.as_deref();
     let end = end_owned.as_deref();
+    let as_of = end
+        .and_then(|e| acc::date::Date::parse(e).ok())
+        .map(|e| acc::date::Date::from_days(e.days().saturating_sub(1)))
+        .unwrap_or_else(|| {
+            acc::date::Date::parse(&acc::date::ms_to_date(acc::date::current_ms()))
+                .expect("current_ms() returns valid YYYY-MM-DD")
+        });
+    if let Command::Lots { .. } = &command {
+        journal.transactions.retain(|lt| lt.value.date <= as_of);
+    }
+
+    // `-V` revalues as of `--value-date`, else the report's explicit end
+    // (the day before `-e`, the last of a single `-p`), else at the latest
+    // rate — so a past balance sheet is marked at its own closing rates.
+    let unrealized = filter_args.map(|f| f.unrealized).unwrap_or(false);
+    let explicit_end = filter_args.is_some_and(|f| f.end.is_some() || f.periods.len() == 1);
+    let value_date = match filter_args.and_then(|f| f.value_date.as_deref()) {
+        Some(d) => Some(acc::date::Date::parse(d).unwrap_or_else(|e| fail(&e))),
+        None => explicit_end.then_some(as_of),
+    };
+    // `--rate-method`: average the translation rate of the selected
+    // accounts; the translator books the difference to CTA, so it needs
+    // the CTA accounts.
+    let rate_method = filter_args.and_then(|f| {
+        let period = match f.rate_method? {
+            RateMethod::Spot => return None,
+            RateMethod::MonthlyAverage => acc::translator::AveragePeriod::Month,
+            RateMethod::YearlyAverage => acc::translator::AveragePeriod::Year,
+        };
+        if journal.cta_gain.is_none() || journal.cta_loss.is_none() {
+            fail("--rate-method needs `cta gain` and `cta loss` accounts declared");
+        }
+        let accounts = (!f.rate_accounts.is_empty())
+            .then(|| acc::filter::PatternMatcher::from_parts(&f.rate_accounts));
+        Some(acc::pipeline::RateMethod { period, accounts })
+    });
+    acc::pipeline::enrich(
+        &mut journal,
+        exchange_target.as_deref(),
+        unrealized,
+        value_date,
+        rate_method.as_ref(),
+    )?;
 
     // Filter phase: scope the journal to the command's pattern and
     // the global --begin / --end date range. Runs once here so every
@@ -996,33 +1532,53 @@ fn start() -> Result<(), acc::Error> {
     // and `--related-all` requests the same for any report command,
     // unlike `reg` / `bal` which otherwise reduce to the matched
     // postings only — a pattern then picks *which entries* to show, not
-    // which lines of them.
+    // which lines of them. `cashflow` needs the whole entry too: the
+    // pattern names the cash, the other postings are its counter-parties.
     let related = filter_args.map(|f| f.related).unwrap_or(false);
     let related_all = filter_args.map(|f| f.related_all).unwrap_or(false);
-    let whole_transactions = related_all || matches!(command, Command::Print { .. });
+    let whole_transactions =
+        related_all || matches!(command, Command::Print { .. } | Command::Cashflow { .. });
     let pos = filter_args.map(|f| f.pos).unwrap_or(false);
     let neg = filter_args.map(|f| f.neg).unwrap_or(false);
     let sign = acc::filter::SignFilter::from_flags(pos, neg);
     let display = filter_args.and_then(|f| f.display.as_deref());
     // Parse `--amount` up front so a malformed EXPR fails cleanly here,
     // consistent with how the date flags are validated before the report runs.
+    let aux_date = filter_args.map(|f| f.aux_date).unwrap_or(false);
     let amount = filter_args
         .and_then(|f| f.amount.as_deref())
         .map(|e| acc::filter::AmountFilter::parse(e).unwrap_or_else(|err| fail(&err)));
+    // `bs` balances are cumulative: `-b` only splits retained earnings
+    // from the period's net income, so every earlier transaction stays.
+    // `cashflow` likewise needs the earlier cash for its opening balance.
+    let cumulative =
+        matches!(command, Command::Bs { .. } | Command::Cashflow { .. } | Command::Networth { .. });
+    let filter_begin = if cumulative { None } else { begin };
+    // `-C` / `--pending` / `-U`: keep the postings in a selected clear
+    // state before the pattern sees them, so `-r` relates to them only.
+    if let Some(f) = filter_args {
+        let states = acc::filter::states_from_flags(f.cleared, f.pending, f.uncleared);
+        if !states.is_empty() {
+            acc::filter::retain_states(&mut journal.transactions, &states);
+        }
+    }
     let mut journal = acc::filter::filter(
         journal,
         command.patterns(),
-        begin,
+        filter_begin,
         end,
         related,
         whole_transactions,
         sign,
         display,
         amount.as_ref(),
+        aux_date,
     );
 
     // Multiple `-p`: keep transactions whose date falls within any
     // of the supplied periods. Half-open `[begin, end)` per period.
+    // Under `--aux-date` each posting is checked by its effective date,
+    // matching the single-range filter above.
     if period_ranges.len() > 1 {
         let parsed: Vec<(acc::date::Date, acc::date::Date)> = period_ranges
             .iter()
@@ -1032,9 +1588,14 @@ fn start() -> Result<(), acc::Error> {
                 Some((b, e))
             })
             .collect();
-        journal.transactions.retain(|lt| {
-            parsed.iter().any(|(b, e)| lt.value.date >= *b && lt.value.date < *e)
-        });
+        let in_any = |d: acc::date::Date| parsed.iter().any(|(b, e)| d >= *b && d < *e);
+        for lt in &mut journal.transactions {
+            let tx_date = lt.value.report_date(aux_date);
+            lt.value.postings.retain(|lp| {
+                in_any(if aux_date { lp.value.aux_date.unwrap_or(tx_date) } else { tx_date })
+            });
+        }
+        journal.transactions.retain(|lt| !lt.value.postings.is_empty());
     }
 
     // `--commodities N` / `--mixed`: keep only transactions whose
@@ -1052,8 +1613,23 @@ fn start() -> Result<(), acc::Error> {
 
     // Rebalance phase: convert posting amounts into the -X target at
     // each posting's own transaction-date rate (historical valuation).
-    if let Some(target) = exchange_target.as_deref() {
-        acc::rebalancer::rebalance(&mut journal.transactions, target, &journal.prices);
+    // `networth --market` converts at each sample date instead, and
+    // `missing-rates` looks for what would not convert, so both keep the
+    // native amounts.
+    let native = matches!(command, Command::Networth { market: true, .. } | Command::MissingRates { .. });
+    let explain_rates = matches!(
+        command,
+        Command::Register { explain_rates: true, .. } | Command::Print { explain_rates: true, .. }
+    );
+    if let Some(target) = exchange_target.as_deref().filter(|_| !native) {
+        if filter_args.is_some_and(|f| f.strict_rates) {
+            acc::commands::missing_rates::check(&journal, target, aux_date)?;
+        }
+        if explain_rates {
+            acc::rebalancer::explain(&mut journal.transactions, target, &journal.prices, aux_date);
+        }
+        acc::rebalancer::rebalance(&mut journal.transactions, target, &journal.prices, aux_date);
+        acc::rebalancer::rebalance(&mut journal.budget, target, &journal.prices, aux_date);
     }
 
     // `-R` / `--real`: drop every virtual posting from the output.
@@ -1075,13 +1651,35 @@ fn start() -> Result<(), acc::Error> {
     let sort_keys: &[String] = filter_args
         .map(|f| f.sort.as_slice())
         .unwrap_or(&default_sort);
-    acc::sorter::sort(&mut journal.transactions, sort_keys);
+    acc::sorter::sort(&mut journal.transactions, sort_keys, aux_date);
 
     match command {
-        Command::Balance { flat, empty, .. } => {
-            acc::commands::balance::run(&journal, !flat, empty);
+        Command::Balance { flat, empty, monthly, quarterly, yearly, row_total, average, .. } => {
+            use acc::commands::balance::{Columns, Interval};
+            let interval = match (monthly, quarterly, yearly) {
+                (true, _, _) => Some(Interval::Monthly),
+                (_, true, _) => Some(Interval::Quarterly),
+                (_, _, true) => Some(Interval::Yearly),
+                _ => None,
+            };
+            // The columns span what the user asked for: `-b`, and `-e` or a
+            // single `-p` — not the hide-future cutoff, which would pad the
+            // report with empty months up to today.
+            let explicit_end = filter_args.is_some_and(|f| f.end.is_some() || f.periods.len() == 1);
+            let bound = |d: Option<&str>| d.and_then(|d| acc::date::Date::parse(d).ok());
+            let columns = interval.map(|interval| Columns {
+                interval,
+                begin: bound(begin),
+                end: if explicit_end { bound(end) } else { None },
+                row_total,
+                average,
+                aux_date,
+            });
+            acc::commands::balance::run(&journal, !flat, empty, columns.as_ref(), output_format);
+        }
+        Command::Register { .. } => {
+            acc::commands::register::run(&journal, aux_date, explain_rates, output_format)
         }
-        Command::Register { .. } => acc::commands::register::run(&journal),
         Command::Print { raw: false, .. } => {
             // `print -X` rounds to display precision and re-balances each
             // transaction so the output is a valid, reloadable journal.
@@ -1095,11 +1693,83 @@ fn start() -> Result<(), acc::Error> {
                     &journal.precisions,
                 );
             }
-            acc::commands::print::run(&journal);
+            acc::commands::print::run(&journal, output_format);
+        }
+        Command::Accounts { tree, .. } => {
+            acc::commands::accounts::run(&journal, tree, output_format)
+        }
+        Command::Codes { .. } => acc::commands::codes::run(&journal, output_format),
+        Command::Commodities { date, .. } => {
+            acc::commands::commodities::run(&journal, date, output_format)
+        }
+        Command::Lots { ref pattern, .. } => {
+            acc::commands::lots::run(&journal, pattern, exchange_target.as_deref(), as_of, output_format)
+        }
+        Command::Gains { ref pattern, .. } => {
+            // Disposals are selected by their own date, not by the
+            // transactions the filter kept: the same `-b` / `-e` / `-p`
+            // bounds, applied to each row's disposal date.
+            let bound = |d: Option<&str>| d.and_then(|d| acc::date::Date::parse(d).ok());
+            let (b, e) = (bound(begin), bound(end));
+            let periods: Vec<(acc::date::Date, acc::date::Date)> = period_ranges
+                .iter()
+                .filter_map(|(b, e)| Some((bound(Some(b))?, bound(Some(e))?)))
+                .collect();
+            let in_period = |d: acc::date::Date| {
+                b.is_none_or(|b| d >= b)
+                    && e.is_none_or(|e| d < e)
+                    && (periods.len() < 2 || periods.iter().any(|(b, e)| d >= *b && d < *e))
+            };
+            acc::commands::gains::run(&journal, pattern, in_period, output_format)
+        }
+        Command::Bs { empty, .. } => {
+            let begin = begin.and_then(|d| acc::date::Date::parse(d).ok());
+            acc::commands::bs::run(&journal, empty, begin, output_format)
+        }
+        Command::Is { empty, .. } => acc::commands::is::run(&journal, empty, output_format),
+        Command::Cashflow { empty, ref pattern, .. } => {
+            let begin = begin.and_then(|d| acc::date::Date::parse(d).ok());
+            acc::commands::cashflow::run(&journal, pattern, begin, empty, output_format)
+        }
+        Command::Budget { monthly, quarterly, yearly, ref pattern, .. } => {
+            use acc::commands::balance::Interval;
+            let interval = match (monthly, quarterly, yearly) {
+                (true, _, _) => Some(Interval::Monthly),
+                (_, true, _) => Some(Interval::Quarterly),
+                (_, _, true) => Some(Interval::Yearly),
+                _ => None,
+            };
+            let bound = |d: Option<&str>| d.and_then(|d| acc::date::Date::parse(d).ok());
+            acc::commands::budget::run(&journal, pattern, bound(begin), bound(end), interval, aux_date, output_format)
+        }
+        Command::Networth { daily, weekly, quarterly, yearly, chart, market, ref pattern, .. } => {
+            use acc::commands::balance::{Columns, Interval};
+            let interval = match (daily, weekly, quarterly, yearly) {
+                (true, _, _, _) => Interval::Daily,
+                (_, true, _, _) => Interval::Weekly,
+                (_, _, true, _) => Interval::Quarterly,
+                (_, _, _, true) => Interval::Yearly,
+                _ => Interval::Monthly,
+            };
+            // As with `balance -M`: the samples run to `-e` / a single
+            // `-p`, not the hide-future cutoff.
+            let explicit_end = filter_args.is_some_and(|f| f.end.is_some() || f.periods.len() == 1);
+            let bound = |d: Option<&str>| d.and_then(|d| acc::date::Date::parse(d).ok());
+            let columns = Columns {
+                interval,
+                begin: bound(begin),
+                end: if explicit_end { bound(end) } else { None },
+                row_total: false,
+                average: false,
+                aux_date,
+            };
+            let market = exchange_target.as_deref().filter(|_| market);
+            acc::commands::networth::run(&journal, &columns, pattern.is_empty(), market, chart, output_format)
+        }
+        Command::MissingRates { .. } => {
+            let target = exchange_target.as_deref().expect("checked before loading");
+            acc::commands::missing_rates::run(&journal, target, aux_date, output_format)
         }
-        Command::Accounts { tree, .. } => acc::commands::accounts::run(&journal, tree),
-        Command::Codes { .. } => acc::commands::codes::run(&journal),
-        Command::Commodities { date, .. } => acc::commands::commodities::run(&journal, date),
         Command::Navigate { empty, .. } => {
             if let Err(e) = acc::commands::navigate::run(&journal, empty) {
                 eprintln!("navigate: {}", e);
@@ -1107,5 +1777,11 @@ fn start() -> Result<(), acc::Error> {
         }
         _ => eprintln!("internal error: unexpected command reached match arm"),
     }
+
+    // The rates a maximum age refused, once per pair — after the report,
+    // as they explain its remainders.
+    for stale in journal.prices.stale() {
+        eprintln!("warning: {}", stale);
+    }
     Ok(())
 }
@@ -1,5 +1,6 @@
 //! Top-level record variants emitted by the parser.
 
+use std::fmt;
 use std::sync::Arc;
 
 use crate::date::Date;
@@ -26,6 +27,12 @@ pub enum Entry {
     Transaction(Transaction),
     Price(Price),
 
+    /// `DATE split SYMBOL NEW:OLD` or `DATE convert OLD NEW RATIO` — a
+    /// redenomination. Written like a transaction header with no postings;
+    /// an indented posting under it turns it back into an ordinary
+    /// transaction with that description.
+    Split(Split),
+
     /// `commodity SYMBOL` + any number of indented children:
     /// - `alias OTHER` → adds OTHER to `aliases`
     /// - `parity OTHER` → adds OTHER to `parities`: SYMBOL keeps its own
@@ -34,11 +41,17 @@ pub enum Entry {
     ///   1:1 price so the normal valuation path can chain it.
     /// - `precision N` → sets the display precision override, overriding
     ///   the precision inferred from posting amounts in reports.
+    /// - `max-age N` → a rate for SYMBOL more than N days from the day it
+    ///   values is not used; `-X` routes around it.
+    /// - `infer-prices` → each `@` / `@@` cost on a SYMBOL posting is also
+    ///   a price on its transaction's date, unless a `P` entry gives one.
     Commodity {
         symbol: String,
         aliases: Vec<String>,
         parities: Vec<String>,
         precision: Option<usize>,
+        max_age: Option<u32>,
+        infer_prices: bool,
     },
 
     /// `account NAME` without (or before) a sub-directive. Acts as a
@@ -60,6 +73,12 @@ pub enum Entry {
     /// A top-level comment line (`;` or `#` at column 0).
     Comment(String),
 
+    /// `include PATH` — pull another journal file (or every file a glob
+    /// matches) into this one at this position. The path is kept verbatim;
+    /// the loader resolves it relative to the including file and splices
+    /// the included entries in place of this one.
+    Include(String),
+
     /// Automated-transaction rule: a pattern that matches against
     /// posting accounts, plus the extra postings to inject (scaled by
     /// the matching posting's amount) into every matching transaction.
@@ -110,7 +129,10 @@ pub enum Entry {
     /// from each occurrence's date. Unlike ledger's `~` (an unbounded
     /// budget/forecast that *repeats* the amount), acc's are bounded to the year
     /// and *split* the total; the generated transactions are real — they book,
-    /// balance, and auto-fill a bare posting like any hand-written entry.
+    /// balance, and auto-fill a bare posting like any hand-written entry —
+    /// unless the header opens with `~~` (`~~ 2025 monthly`): a budget
+    /// block generates no postings, its occurrences are kept aside as the
+    /// budgeted amounts `acc budget` compares against.
     Periodic {
         /// The period token — a bare year `YYYY`.
         period: String,
@@ -119,8 +141,16 @@ pub enum Entry {
         /// Optional description for the generated transactions (`~ 2021 note`);
         /// empty when the header carries only the period (and cadence).
         description: String,
+        /// A `~~` header: a budget, not transactions.
+        budget: bool,
         postings: Vec<Located<Posting>>,
     },
+
+    /// `~ EVERY from DATE [until DATE] [description]` + indented postings —
+    /// a *recurring* transaction. It books nothing by itself; under
+    /// `--forecast` the forecaster projects its upcoming occurrences into
+    /// the journal, each with the written amounts.
+    Recurring(Recurring),
 }
 
 /// How a `~` periodic block spreads its amounts across the year. The written
@@ -137,6 +167,65 @@ pub enum Cadence {
     Daily,
 }
 
+/// A recurring rule: every occurrence from `start` (inclusive) to `end`
+/// (exclusive, open when `None`) is one transaction with `postings`.
+#[derive(Debug, Clone)]
+pub struct Recurring {
+    pub every: Every,
+    pub start: Date,
+    pub end: Option<Date>,
+    pub description: String,
+    pub postings: Vec<Located<Posting>>,
+}
+
+/// How often a [`Recurring`] rule repeats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Every {
+    Daily,
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl Every {
+    /// The cadence keyword of a `~ EVERY from …` header.
+    pub fn parse(word: &str) -> Option<Every> {
+        match word {
+            "daily" => Some(Every::Daily),
+            "weekly" => Some(Every::Weekly),
+            "monthly" => Some(Every::Monthly),
+            "quarterly" => Some(Every::Quarterly),
+            "yearly" => Some(Every::Yearly),
+            _ => None,
+        }
+    }
+}
+
+impl Recurring {
+    /// The `n`-th occurrence, counting `start` as the 0th. Monthly steps
+    /// keep the start's day of month, clamped to shorter months.
+    pub fn occurrence(&self, n: u32) -> Date {
+        match self.every {
+            Every::Daily => Date::from_days(self.start.days() + n),
+            Every::Weekly => Date::from_days(self.start.days() + 7 * n),
+            Every::Monthly => self.start.add_months(n),
+            Every::Quarterly => self.start.add_months(3 * n),
+            Every::Yearly => self.start.add_months(12 * n),
+        }
+    }
+
+    /// The occurrence dates in `[from, until)`, within the rule's own span.
+    pub fn occurrences(&self, from: Date, until: Date) -> Vec<Date> {
+        let until = self.end.map_or(until, |end| end.min(until));
+        (0..)
+            .map(|n| self.occurrence(n))
+            .take_while(|d| *d < until)
+            .filter(|d| *d >= from)
+            .collect()
+    }
+}
+
 /// Comparison operator for an `amount` clause on an auto-rule.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CompareOp {
@@ -350,6 +439,76 @@ pub struct Price {
     pub rate: Decimal,
 }
 
+/// A `split` or `convert` directive: from `date` on, every unit of `from`
+/// is worth `new / old` units of `to`. A stock split names one commodity
+/// (`split AAPL 4:1` — four new shares per old one); a redenomination
+/// names two (`convert OLD NEW 1000` — a thousand NEW per OLD). The
+/// booker swaps the holdings over on the date, the lotter rescales the
+/// open lots without realizing anything, and the price index rescales
+/// rates across the date so valuation stays continuous.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Split {
+    pub date: Date,
+    pub from: String,
+    pub to: String,
+    pub new: Decimal,
+    pub old: Decimal,
+}
+
+impl Split {
+    /// Parse the directive text after the date — `split SYM N:M` or
+    /// `convert OLD NEW RATIO`. `None` when it isn't one (an ordinary
+    /// transaction description that happens to start with the keyword).
+    pub fn parse(date: Date, text: &str) -> Option<Split> {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        let positive = |s: &str| Decimal::parse(s).ok().filter(|d| !d.is_zero() && !d.is_negative());
+        match words.as_slice() {
+            ["split", sym, ratio] => {
+                let (new, old) = ratio.split_once(':')?;
+                Some(Split {
+                    date,
+                    from: sym.to_string(),
+                    to: sym.to_string(),
+                    new: positive(new)?,
+                    old: positive(old)?,
+                })
+            }
+            ["convert", from, to, ratio] if from != to => Some(Split {
+                date,
+                from: from.to_string(),
+                to: to.to_string(),
+                new: positive(ratio)?,
+                old: Decimal::from(1),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Units of `to` per unit of `from`.
+    pub fn ratio(&self) -> Decimal {
+        self.new.div_rounded(self.old)
+    }
+}
+
+impl fmt::Display for Split {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.from == self.to {
+            write!(f, "split {} {}:{}", self.from, plain(self.new), plain(self.old))
+        } else {
+            write!(f, "convert {} {} {}", self.from, self.to, plain(self.new))
+        }
+    }
+}
+
+/// A ratio term without trailing zeros (`4`, `0.5`).
+fn plain(d: Decimal) -> String {
+    let s = d.to_string();
+    match s.contains('.') {
+        true => s.trim_end_matches('0').trim_end_matches('.').to_string(),
+        false => s,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::AutoPattern;
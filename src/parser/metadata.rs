@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+
+/// Structured metadata read out of comments, ledger-style:
+///
+/// - `:tag1:tag2:` — one or more bare tags, stored with an empty value.
+/// - `key: value` — a word ending in `:` names the key; the rest of the
+///   comment is its value (`; csv: 2024-01-03,Coffee,-4.50`).
+///
+/// Both forms can share a comment (`; :work: project: alpha`). Names keep
+/// their spelling but compare case-insensitively, like every other filter
+/// dimension. A later comment overrides an earlier value for the same key.
+///
+/// Transactions and postings each carry their own map. Lookups that want
+/// the effective value for a posting go through [`Metadata::lookup`], where
+/// the posting-level value wins over the transaction-level one.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    entries: BTreeMap<String, String>,
+}
+
+impl Metadata {
+    /// Fold the tags and `key: value` pair of one comment into the map.
+    pub fn add_comment(&mut self, text: &str) {
+        let mut rest = text.trim();
+        while !rest.is_empty() {
+            let (token, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            if let Some(inner) = token.strip_prefix(':').and_then(|t| t.strip_suffix(':')) {
+                for tag in inner.split(':').filter(|t| !t.is_empty()) {
+                    self.insert(tag, "");
+                }
+            } else if let Some(key) = token.strip_suffix(':')
+                && !key.is_empty()
+                && !key.contains(':')
+            {
+                self.insert(key, tail.trim());
+                return;
+            }
+            rest = tail.trim_start();
+        }
+    }
+
+    fn insert(&mut self, key: &str, value: &str) {
+        let existing = self.entries.keys().find(|k| k.eq_ignore_ascii_case(key)).cloned();
+        self.entries.remove(existing.as_deref().unwrap_or(key));
+        self.entries.insert(key.to_string(), value.to_string());
+    }
+
+    /// The value recorded under `key` (case-insensitive). A bare tag has
+    /// the empty value, so `Some("")` means "tagged, no value".
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Effective value of `key` for a posting: its own value if it has
+    /// one, else the enclosing transaction's.
+    pub fn lookup<'a>(posting: &'a Metadata, transaction: &'a Metadata, key: &str) -> Option<&'a str> {
+        posting.get(key).or_else(|| transaction.get(key))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// All entries in key order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn meta(comments: &[&str]) -> Metadata {
+        let mut m = Metadata::default();
+        for c in comments {
+            m.add_comment(c);
+        }
+        m
+    }
+
+    #[test]
+    fn tags_and_key_value() {
+        let m = meta(&[":work:travel:", "project: alpha beta"]);
+        assert_eq!(m.get("work"), Some(""));
+        assert_eq!(m.get("travel"), Some(""));
+        assert_eq!(m.get("PROJECT"), Some("alpha beta"));
+        assert_eq!(m.get("other"), None);
+    }
+
+    #[test]
+    fn mixed_comment_and_plain_text() {
+        let m = meta(&["paid late :fee: csv: 2024-01-03,Coffee,-4.50"]);
+        assert_eq!(m.get("fee"), Some(""));
+        assert_eq!(m.get("csv"), Some("2024-01-03,Coffee,-4.50"));
+        // Account-like words and free text are not metadata.
+        assert!(meta(&["moved from a:11:cash"]).is_empty());
+    }
+
+    #[test]
+    fn later_value_overrides_and_posting_wins() {
+        let tx = meta(&["project: alpha", "Project: beta", ":shared:"]);
+        assert_eq!(tx.get("project"), Some("beta"));
+        let posting = meta(&["project: gamma"]);
+        assert_eq!(Metadata::lookup(&posting, &tx, "project"), Some("gamma"));
+        assert_eq!(Metadata::lookup(&posting, &tx, "shared"), Some(""));
+        assert_eq!(Metadata::lookup(&Metadata::default(), &tx, "project"), Some("beta"));
+    }
+}
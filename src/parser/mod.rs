@@ -9,13 +9,15 @@ pub mod entry;
 pub mod error;
 pub mod expression;
 pub mod located;
+pub mod metadata;
 pub mod posting;
 pub mod transaction;
 
 pub use comment::Comment;
-pub use entry::{Entry, Price};
+pub use entry::{Entry, Price, Recurring, Split};
 pub use error::ParseError;
 pub use located::Located;
+pub use metadata::Metadata;
 pub use posting::{Amount, Costs, LotCost, Posting};
 pub use transaction::{State, Transaction};
 
@@ -117,7 +119,10 @@ fn dispatch(
             parse_auto_rule(text[1..].trim_start(), line, file, entries)
         }
         [b'~', b' ', ..] | [b'~', b'\t', ..] => {
-            parse_periodic(text[1..].trim_start(), line, file, entries)
+            parse_periodic(text[1..].trim_start(), false, line, file, entries)
+        }
+        [b'~', b'~', b' ', ..] | [b'~', b'~', b'\t', ..] => {
+            parse_periodic(text[2..].trim_start(), true, line, file, entries)
         }
         _ => parse_directive(text, line, file, entries),
     }
@@ -376,8 +381,11 @@ fn parse_price(
 /// resolve into one real transaction dated `YYYY-01-01`. Indented postings
 /// attach via `extend_block`, parsed like a transaction's; `$year`/`$month`/
 /// `$day` in an account are filled from that date at resolve time.
+/// `budget` is set for a `~~ PERIOD` header: a budget block, which takes
+/// a period and cadence but no recurring `EVERY from DATE` rule.
 fn parse_periodic(
     rest: &str,
+    budget: bool,
     line: usize,
     file: &Arc<str>,
     entries: &mut Vec<Located<Entry>>,
@@ -391,6 +399,12 @@ fn parse_periodic(
         .split_once(char::is_whitespace)
         .map(|(p, d)| (p.trim(), d.trim()))
         .unwrap_or((rest, ""));
+    if let Some(every) = crate::parser::entry::Every::parse(period) {
+        if budget {
+            return Err(ParseError::new(line, 1, "a `~~` budget block needs a year, e.g. `~~ 2025 monthly`"));
+        }
+        return parse_recurring(every, after, line, file, entries);
+    }
     // Optional cadence keyword right after the period; whatever follows (or the
     // whole remainder, if it isn't a cadence word) is the title.
     let (cadence, description) = match after.split_once(char::is_whitespace) {
@@ -403,6 +417,17 @@ fn parse_periodic(
             None => (Cadence::Yearly, after),
         },
     };
+    // Budgets used to be `~` blocks titled `budget`; that title now names
+    // an ordinary periodic block, so point the old form at `~~`.
+    if !budget && description.eq_ignore_ascii_case("budget") {
+        eprintln!(
+            "warning: {}:{}: `~ {}` is a periodic transaction titled `budget`, not a budget; write `~~ {}` for one",
+            file,
+            line,
+            rest,
+            rest[..rest.len() - description.len()].trim_end(),
+        );
+    }
     entries.push(Located {
         file: file.clone(),
         line,
@@ -410,12 +435,52 @@ fn parse_periodic(
             period: period.to_string(),
             cadence,
             description: description.to_string(),
+            budget,
             postings: Vec::new(),
         },
     });
     Ok(())
 }
 
+/// Parse the rest of a `~ EVERY from DATE [until DATE] [description]`
+/// header — a recurring rule. `from` is required; `until` is exclusive.
+fn parse_recurring(
+    every: crate::parser::entry::Every,
+    rest: &str,
+    line: usize,
+    file: &Arc<str>,
+    entries: &mut Vec<Located<Entry>>,
+) -> Result<(), ParseError> {
+    // `KEYWORD DATE` at the front of `text`: the date and what follows.
+    let keyword_date = |text: &str, keyword: &str| -> Result<Option<(crate::date::Date, String)>, ParseError> {
+        let mut words = text.splitn(3, char::is_whitespace);
+        if words.next() != Some(keyword) {
+            return Ok(None);
+        }
+        let date = words.next().unwrap_or("");
+        let date = crate::date::Date::parse(date)
+            .map_err(|e| ParseError::new(line, 1, format!("recurring `{keyword}` date: {e}")))?;
+        Ok(Some((date, words.next().unwrap_or("").trim().to_string())))
+    };
+    let Some((start, rest)) = keyword_date(rest, "from")? else {
+        return Err(ParseError::new(
+            line,
+            1,
+            "recurring `~` block needs a start, e.g. `~ monthly from 2025-01-01`",
+        ));
+    };
+    let (end, description) = match keyword_date(&rest, "until")? {
+        Some((end, description)) => (Some(end), description),
+        None => (None, rest),
+    };
+    entries.push(Located {
+        file: file.clone(),
+        line,
+        value: Entry::Recurring(Recurring { every, start, end, description, postings: Vec::new() }),
+    });
+    Ok(())
+}
+
 /// The optional cadence keyword after a `~ PERIOD` header (`monthly` / `daily`
 /// / `yearly`); `None` for anything else, which the caller treats as the title.
 fn cadence_keyword(word: &str) -> Option<crate::parser::entry::Cadence> {
@@ -443,10 +508,16 @@ fn parse_transaction(
     let (date_field, after_date) = text
         .split_once(char::is_whitespace)
         .unwrap_or((text, ""));
-    // Primary date only; strip `=AUXDATE` if present.
-    let date_str = date_field.split('=').next().unwrap();
+    // `DATE` or `DATE=AUXDATE`.
+    let (date_str, aux_str) = match date_field.split_once('=') {
+        Some((d, a)) => (d, Some(a)),
+        None => (date_field, None),
+    };
     let date = crate::date::Date::parse(date_str)
         .map_err(|e| ParseError::new(line, 1, e))?;
+    let aux_date = aux_str
+        .map(|a| crate::date::Date::parse(a).map_err(|e| ParseError::new(line, date_str.len() + 2, e)))
+        .transpose()?;
 
     let rest = after_date.trim_start();
     let (state, rest) = parse_state(rest);
@@ -454,11 +525,21 @@ fn parse_transaction(
     let (code, rest) = parse_code(rest, line)?;
     let description = rest.trim().to_string();
 
+    // A bare `split` / `convert` header is a redenomination directive;
+    // postings under it make it a transaction again (see `extend_block`).
+    if aux_date.is_none() && state == State::Uncleared && code.is_none()
+        && let Some(split) = Split::parse(date, &description)
+    {
+        entries.push(Located { file: file.clone(), line, value: Entry::Split(split) });
+        return Ok(());
+    }
+
     entries.push(Located {
         file: file.clone(),
         line,
         value: Entry::Transaction(Transaction {
             date,
+            aux_date,
             state,
             code,
             description,
@@ -466,6 +547,7 @@ fn parse_transaction(
             // to avoid the first regrow in the common case.
             postings: Vec::with_capacity(2),
             comments: Vec::new(),
+            metadata: Metadata::default(),
         }),
     });
     Ok(())
@@ -479,6 +561,19 @@ fn parse_state(rest: &str) -> (State, &str) {
     }
 }
 
+/// Split a leading `* ` / `! ` posting-state marker off a posting body.
+/// The marker must be followed by whitespace, so an account that merely
+/// starts with `*` or `!` is left alone.
+fn posting_state(body: &str) -> (Option<State>, &str) {
+    match body.as_bytes() {
+        [b'*' | b'!', b' ' | b'\t', ..] => {
+            let (state, rest) = parse_state(body);
+            (Some(state), rest.trim_start())
+        }
+        _ => (None, body),
+    }
+}
+
 fn parse_code(rest: &str, line: usize) -> Result<(Option<String>, &str), ParseError> {
     if !rest.starts_with('(') {
         return Ok((None, rest));
@@ -492,7 +587,7 @@ fn parse_code(rest: &str, line: usize) -> Result<(Option<String>, &str), ParseEr
 }
 
 /// Parse a top-level keyword directive. Recognised: `commodity`,
-/// `account`. Unknown keywords raise an error — acc has no silent-skip
+/// `account`, `include`. Unknown keywords raise an error — acc has no silent-skip
 /// policy for directives it doesn't understand.
 fn parse_directive(
     text: &str,
@@ -518,6 +613,8 @@ fn parse_directive(
                     aliases: Vec::new(),
                     parities: Vec::new(),
                     precision: None,
+                    max_age: None,
+                    infer_prices: false,
                 },
             });
             Ok(())
@@ -533,10 +630,35 @@ fn parse_directive(
             });
             Ok(())
         }
+        "include" => {
+            if arg.is_empty() {
+                return Err(ParseError::new(line, 1, "include directive missing path"));
+            }
+            entries.push(Located {
+                file: file.clone(),
+                line,
+                value: Entry::Include(arg.to_string()),
+            });
+            Ok(())
+        }
         other => Err(ParseError::new(line, 1, format!("unknown directive: {}", other))),
     }
 }
 
+/// The `[=DATE]` aux-date override in a comment, if it carries one.
+fn comment_aux_date(text: &str, line: usize) -> Result<Option<crate::date::Date>, ParseError> {
+    let Some(start) = text.find("[=") else {
+        return Ok(None);
+    };
+    let rest = &text[start + 2..];
+    let end = rest
+        .find(']')
+        .ok_or_else(|| ParseError::new(line, 1, "unclosed `[=DATE]` in comment"))?;
+    crate::date::Date::parse(rest[..end].trim())
+        .map(Some)
+        .map_err(|e| ParseError::new(line, 1, e))
+}
+
 /// Attach the content of an indented line to the last emitted entry:
 ///
 /// - under `Transaction` → a posting or an indented comment
@@ -559,7 +681,9 @@ fn extend_block(
     // attaches to that last posting (ledger-cli convention — a
     // comment that follows a posting belongs to it). Otherwise it
     // is a transaction-level comment that renders before any
-    // posting. Comments under non-Transaction entries are dropped.
+    // posting. Either way its tags and `key: value` pair are folded
+    // into the owner's metadata, and a `[=DATE]` sets the owner's aux
+    // date. Comments under non-Transaction entries are dropped.
     if content.starts_with(';') || content.starts_with('#') {
         if let Entry::Transaction(tx) = &mut last.value {
             let comment = Located {
@@ -567,15 +691,36 @@ fn extend_block(
                 line,
                 value: Comment { text: content[1..].trim().to_string() },
             };
+            let aux_date = comment_aux_date(&comment.value.text, line)?;
             if let Some(last_posting) = tx.postings.last_mut() {
-                last_posting.value.comments.push(comment);
+                let p = &mut last_posting.value;
+                p.metadata.add_comment(&comment.value.text);
+                p.aux_date = aux_date.or(p.aux_date);
+                p.comments.push(comment);
             } else {
+                tx.metadata.add_comment(&comment.value.text);
+                tx.aux_date = aux_date.or(tx.aux_date);
                 tx.comments.push(comment);
             }
         }
         return Ok(());
     }
 
+    // A posting under a `split` / `convert` header: it was an ordinary
+    // transaction whose description merely reads like the directive.
+    if let Entry::Split(split) = &last.value {
+        last.value = Entry::Transaction(Transaction {
+            date: split.date,
+            aux_date: None,
+            state: State::Uncleared,
+            code: None,
+            description: split.to_string(),
+            postings: Vec::with_capacity(2),
+            comments: Vec::new(),
+            metadata: Metadata::default(),
+        });
+    }
+
     // Mid-line `;` is an inline comment. Split it off once, centrally —
     // every sub-parser below sees clean content.
     let (body, inline_comment) = match content.find(';') {
@@ -594,6 +739,8 @@ fn extend_block(
         Entry::Transaction(tx) => {
             let mut posting = parse_posting(body, line)?;
             if let Some(text) = inline_comment {
+                posting.metadata.add_comment(&text);
+                posting.aux_date = comment_aux_date(&text, line)?;
                 posting.comments.push(Located {
                     file: file.clone(),
                     line,
@@ -606,7 +753,7 @@ fn extend_block(
                 value: posting,
             });
         }
-        Entry::Commodity { aliases, parities, precision, .. } => {
+        Entry::Commodity { aliases, parities, precision, max_age, infer_prices, .. } => {
             if let Some(rest) = body.strip_prefix("alias ") {
                 let alias = rest.trim();
                 if alias.is_empty() {
@@ -625,8 +772,20 @@ fn extend_block(
                     ParseError::new(line, 1, format!("precision requires a non-negative integer, got `{}`", digits))
                 })?;
                 *precision = Some(n);
+            } else if let Some(rest) = body.strip_prefix("max-age ") {
+                let digits = rest.trim();
+                let n: u32 = digits.parse().map_err(|_| {
+                    ParseError::new(line, 1, format!("max-age requires a non-negative number of days, got `{}`", digits))
+                })?;
+                *max_age = Some(n);
+            } else if body.trim() == "infer-prices" {
+                *infer_prices = true;
             } else {
-                return Err(ParseError::new(line, 1, "expected `alias NAME`, `parity COMMODITY` or `precision N`"));
+                return Err(ParseError::new(
+                    line,
+                    1,
+                    "expected `alias NAME`, `parity COMMODITY`, `precision N`, `max-age N` or `infer-prices`",
+                ));
             }
         }
         Entry::Account(name) => {
@@ -655,12 +814,14 @@ fn extend_block(
             let auto_posting = parse_auto_posting(body, line)?;
             postings.push(auto_posting);
         }
-        Entry::Periodic { postings, .. } => {
+        Entry::Periodic { postings, .. } | Entry::Recurring(Recurring { postings, .. }) => {
             // Periodic postings are ordinary postings (real, auto-filling),
             // parsed exactly like a transaction's; `$year`/`$month`/`$day` in
             // the account are filled at resolve time, not here.
             let mut posting = parse_posting(body, line)?;
             if let Some(text) = inline_comment {
+                posting.metadata.add_comment(&text);
+                posting.aux_date = comment_aux_date(&text, line)?;
                 posting.comments.push(Located {
                     file: file.clone(),
                     line,
@@ -775,11 +936,14 @@ fn split_account_and_amount(body: &str) -> Option<(&str, &str)> {
 /// Parse the body of a posting line. `body` has already had its indent
 /// and any inline `;` comment stripped by the caller.
 ///
-/// Format: `[(|[]ACCOUNT[)|]]  AMOUNT [@ COST | @@ TOTAL] [= ASSERTION]`
+/// Format: `[*|! ][(|[]ACCOUNT[)|]]  AMOUNT [@ COST | @@ TOTAL] [= ASSERTION]`
 ///
 /// Account and amount are separated by **tab or two-plus spaces** — a
 /// single space stays part of the account name.
 fn parse_posting(body: &str, line: usize) -> Result<Posting, ParseError> {
+    // Posting-level clear state: a `*` / `!` marker and whitespace before
+    // the account, as ledger allows.
+    let (state, body) = posting_state(body);
     // Virtual-posting wrapping: `(account)` is virtual unbalanced,
     // `[account]` is virtual balanced. Plain account is real balanced.
     let (is_virtual, balanced, account, rest) = extract_account(body, line)?;
@@ -856,9 +1020,12 @@ fn parse_posting(body: &str, line: usize) -> Result<Posting, ParseError> {
         lot_cost,
         lot_date,
         balance_assertion,
+        state,
         is_virtual,
         balanced,
         comments: Vec::new(),
+        metadata: Metadata::default(),
+        aux_date: None,
     })
 }
 
@@ -1107,6 +1274,33 @@ mod tests {
         assert!(parse("P 2024-06-15 USD EUR\n").is_err());
     }
 
+    // --- Split / convert directive ---
+
+    #[test]
+    fn parse_split_and_convert() {
+        let got = parse("2024-06-10 split AAPL 4:1\n2024-07-01 convert OLD NEW 1000\n").unwrap();
+        let Entry::Split(s) = &got[0].value else { panic!("expected Split") };
+        assert_eq!((s.from.as_str(), s.to.as_str()), ("AAPL", "AAPL"));
+        assert_eq!(s.ratio(), Decimal::parse("4").unwrap());
+        assert_eq!(s.to_string(), "split AAPL 4:1");
+        let Entry::Split(c) = &got[1].value else { panic!("expected Split") };
+        assert_eq!((c.from.as_str(), c.to.as_str()), ("OLD", "NEW"));
+        assert_eq!(c.ratio(), Decimal::parse("1000").unwrap());
+        assert_eq!(c.to_string(), "convert OLD NEW 1000");
+    }
+
+    #[test]
+    fn split_header_with_postings_is_a_transaction() {
+        let got = parse("2024-06-10 split AAPL 4:1\n    a  1 USD\n    b\n").unwrap();
+        let Entry::Transaction(tx) = &got[0].value else { panic!("expected Transaction") };
+        assert_eq!(tx.description, "split AAPL 4:1");
+        assert_eq!(tx.postings.len(), 2);
+        // Not the grammar, or a cleared header: an ordinary transaction.
+        for src in ["2024-06-10 split the bill\n", "2024-06-10 * split AAPL 4:1\n"] {
+            assert!(matches!(parse(src).unwrap()[0].value, Entry::Transaction(_)));
+        }
+    }
+
     // --- Auto-rule patterns ---
 
     #[test]
@@ -1251,6 +1445,40 @@ mod tests {
         };
         assert_eq!(*cadence, Cadence::Yearly);
         assert_eq!(description, "just a title");
+        assert!(matches!(&got[0].value, Entry::Periodic { budget: false, .. }));
+        // `~~` opens a budget block; a `budget` word under `~` is a title.
+        let got = parse("~~ 2025 monthly groceries\n\texpenses:food  6000 EUR\n").unwrap();
+        let Entry::Periodic { cadence, description, budget, .. } = &got[0].value else {
+            panic!("expected Periodic")
+        };
+        assert_eq!((*cadence, description.as_str(), *budget), (Cadence::Monthly, "groceries", true));
+        let got = parse("~ 2025 monthly budget\n\ta  1\n\tb\n").unwrap();
+        let Entry::Periodic { description, budget, .. } = &got[0].value else {
+            panic!("expected Periodic")
+        };
+        assert_eq!((description.as_str(), *budget), ("budget", false));
+        assert!(parse("~~ monthly from 2025-01-01\n\ta  1\n").is_err());
+    }
+
+    #[test]
+    fn parse_recurring_header() {
+        use crate::parser::entry::Every;
+        let got = parse("~ monthly from 2025-01-31 until 2025-06-01 rent\n\texpenses:rent  1000 EUR\n\tassets:bank\n").unwrap();
+        let Entry::Recurring(r) = &got[0].value else { panic!("expected Recurring") };
+        assert_eq!(r.every, Every::Monthly);
+        assert_eq!((r.start.to_string(), r.end.map(|d| d.to_string())), ("2025-01-31".into(), Some("2025-06-01".into())));
+        assert_eq!(r.description, "rent");
+        assert_eq!(r.postings.len(), 2);
+        let dates: Vec<String> = r
+            .occurrences(
+                crate::date::Date::parse("2025-02-01").unwrap(),
+                crate::date::Date::parse("2026-01-01").unwrap(),
+            )
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        assert_eq!(dates, ["2025-02-28", "2025-03-31", "2025-04-30", "2025-05-31"]);
+        assert!(parse("~ weekly groceries\n\ta  1\n\tb\n").is_err());
     }
 
     // --- Comments ---
@@ -1301,9 +1529,25 @@ mod tests {
     fn parse_transaction_with_aux_date() {
         let got = parse("2024-06-15=2024-06-16 * Thing\n").unwrap();
         match &got[0].value {
-            Entry::Transaction(tx) => assert_eq!(tx.date.to_string(), "2024-06-15"),
+            Entry::Transaction(tx) => {
+                assert_eq!(tx.date.to_string(), "2024-06-15");
+                assert_eq!(tx.aux_date.unwrap().to_string(), "2024-06-16");
+            }
             _ => panic!("expected Transaction"),
         }
+        assert!(parse("2024-06-15=2024-13-01 * Thing\n").is_err());
+    }
+
+    #[test]
+    fn posting_aux_date_from_comment() {
+        let src = "2024-06-15=2024-06-17 * Card\n    expenses:food  5 USD  ; [=2024-06-20]\n    liabilities:card\n    ; settled :late: [=2024-06-21]\n";
+        let got = parse(src).unwrap();
+        let Entry::Transaction(tx) = &got[0].value else { panic!("expected Transaction") };
+        assert_eq!(tx.postings[0].value.aux_date.unwrap().to_string(), "2024-06-20");
+        assert_eq!(tx.postings[1].value.aux_date.unwrap().to_string(), "2024-06-21");
+        assert_eq!(tx.posting_date(&tx.postings[0].value, false).to_string(), "2024-06-15");
+        assert_eq!(tx.posting_date(&tx.postings[0].value, true).to_string(), "2024-06-20");
+        assert!(parse("2024-06-15 * X\n    a  5 USD  ; [=2024-06\n").is_err());
     }
 
     // --- Transaction with postings ---
@@ -1325,6 +1569,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn comments_fill_transaction_and_posting_metadata() {
+        let src = "2024-06-15 * Coffee\n    ; :work:\n    ; project: alpha\n    expenses:food  5 USD  ; project: beta\n    ; :receipt:\n    assets:cash\n";
+        let got = parse(src).unwrap();
+        let Entry::Transaction(tx) = &got[0].value else { panic!("expected Transaction") };
+        assert_eq!(tx.metadata.get("work"), Some(""));
+        assert_eq!(tx.metadata.get("project"), Some("alpha"));
+        let food = &tx.postings[0].value.metadata;
+        assert_eq!(food.get("project"), Some("beta"));
+        assert_eq!(food.get("receipt"), Some(""));
+        assert!(tx.postings[1].value.metadata.is_empty());
+    }
+
     #[test]
     fn parse_posting_with_cost() {
         let src = "2024-06-15 * Coffee\n    expenses:food  5 USD @ 0.92 EUR\n";
@@ -1335,6 +1592,20 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_posting_state() {
+        let src = "2024-06-15 Coffee\n    * expenses:food  5 USD\n    ! assets:cash\n    assets:bank\n";
+        let got = parse(src).unwrap();
+        if let Entry::Transaction(tx) = &got[0].value {
+            let p = &tx.postings[0].value;
+            assert_eq!(p.state, Some(State::Cleared));
+            assert_eq!(p.account, "expenses:food");
+            assert_eq!(tx.postings[1].value.state, Some(State::Pending));
+            assert_eq!(tx.postings[2].value.state, None);
+            assert_eq!(tx.posting_state(&tx.postings[2].value), State::Uncleared);
+        }
+    }
+
     #[test]
     fn parse_posting_with_total_cost() {
         let src = "2024-06-15 * Coffee\n    expenses:food  5 USD @@ 4.60 EUR\n";
@@ -1483,11 +1754,13 @@ mod tests {
         let src = "commodity USD\n    alias $\n    alias USdollar\n";
         let got = parse(src).unwrap();
         match &got[0].value {
-            Entry::Commodity { symbol, aliases, parities, precision } => {
+            Entry::Commodity { symbol, aliases, parities, precision, max_age, infer_prices } => {
                 assert_eq!(symbol, "USD");
                 assert_eq!(aliases, &vec!["$".to_string(), "USdollar".to_string()]);
                 assert!(parities.is_empty());
                 assert_eq!(*precision, None);
+                assert_eq!(*max_age, None);
+                assert!(!infer_prices);
             }
             _ => panic!("expected Commodity"),
         }
@@ -1498,11 +1771,13 @@ mod tests {
         let src = "commodity EUR\n    alias €\n    precision 2\n";
         let got = parse(src).unwrap();
         match &got[0].value {
-            Entry::Commodity { symbol, aliases, parities, precision } => {
+            Entry::Commodity { symbol, aliases, parities, precision, max_age, infer_prices } => {
                 assert_eq!(symbol, "EUR");
                 assert_eq!(aliases, &vec!["€".to_string()]);
                 assert!(parities.is_empty());
                 assert_eq!(*precision, Some(2));
+                assert_eq!(*max_age, None);
+                assert!(!infer_prices);
             }
             _ => panic!("expected Commodity"),
         }
@@ -1515,16 +1790,32 @@ mod tests {
         let src = "commodity USDC\n    parity $\n    precision 2\n";
         let got = parse(src).unwrap();
         match &got[0].value {
-            Entry::Commodity { symbol, aliases, parities, precision } => {
+            Entry::Commodity { symbol, aliases, parities, precision, max_age, infer_prices } => {
                 assert_eq!(symbol, "USDC");
                 assert!(aliases.is_empty());
                 assert_eq!(parities, &vec!["$".to_string()]);
                 assert_eq!(*precision, Some(2));
+                assert_eq!(*max_age, None);
+                assert!(!infer_prices);
             }
             _ => panic!("expected Commodity"),
         }
     }
 
+    #[test]
+    fn parse_commodity_with_max_age() {
+        let src = "commodity XMR\n    max-age 30\n";
+        let got = parse(src).unwrap();
+        assert!(matches!(&got[0].value, Entry::Commodity { max_age: Some(30), infer_prices: false, .. }));
+        assert!(parse("commodity XMR\n    max-age soon\n").is_err());
+    }
+
+    #[test]
+    fn parse_commodity_with_infer_prices() {
+        let got = parse("commodity HOUSE\n    infer-prices\n").unwrap();
+        assert!(matches!(&got[0].value, Entry::Commodity { infer_prices: true, .. }));
+    }
+
     #[test]
     fn parse_account_with_slippage_gain() {
         let src = "account Equity:SlippageGain\n    slippage gain\n";
@@ -1575,6 +1866,13 @@ mod tests {
         assert!(matches!(got[0].value, Entry::Account(ref n) if n == "Assets:Bank"));
     }
 
+    #[test]
+    fn parse_include_keeps_path_verbatim() {
+        let got = parse("include accounts/*.ledger\n").unwrap();
+        assert!(matches!(got[0].value, Entry::Include(ref p) if p == "accounts/*.ledger"));
+        assert!(parse("include\n").is_err());
+    }
+
     #[test]
     fn unknown_directive_errors() {
         assert!(parse("year 2024\n").is_err());
@@ -3,6 +3,8 @@ use crate::decimal::Decimal;
 
 use super::comment::Comment;
 use super::located::Located;
+use super::metadata::Metadata;
+use super::transaction::State;
 
 /// A single posting line inside a transaction — one debit or credit.
 ///
@@ -26,10 +28,22 @@ pub struct Posting {
     /// the lotter when it splits a disposal per lot, so `print`/`reg` can
     /// show which lot each leg closed. Display-only — no computation.
     pub lot_date: Option<Date>,
+    /// Posting-level aux date from a `; [=DATE]` comment. Overrides the
+    /// transaction's aux date under `--aux-date`, see
+    /// [`Transaction::posting_date`](super::transaction::Transaction::posting_date).
+    pub aux_date: Option<Date>,
     pub balance_assertion: Option<Amount>,
+    /// Posting-level clear state from a `* ` / `! ` before the account, so
+    /// one leg can be cleared ahead of the others. `None` inherits the
+    /// transaction's, see
+    /// [`Transaction::posting_state`](super::transaction::Transaction::posting_state).
+    pub state: Option<State>,
     pub is_virtual: bool,
     pub balanced: bool,
     pub comments: Vec<Located<Comment>>,
+    /// Tags and `key: value` pairs from the posting's own comments. They
+    /// override same-named transaction metadata, see [`Metadata::lookup`].
+    pub metadata: Metadata,
 }
 
 /// A numeric value paired with a commodity symbol (e.g. `$100.50`, `10 AAPL`).
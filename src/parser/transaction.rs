@@ -2,6 +2,7 @@ use crate::date::Date;
 
 use super::comment::Comment;
 use super::located::Located;
+use super::metadata::Metadata;
 use super::posting::Posting;
 
 /// A journal transaction: a dated event with two or more postings that
@@ -13,17 +14,50 @@ use super::posting::Posting;
 #[derive(Debug, Clone)]
 pub struct Transaction {
     pub date: Date,
+    /// Auxiliary (effective) date from a `DATE=AUXDATE` header — e.g. the
+    /// settlement date of a card payment. Reports key on it instead of
+    /// `date` under `--aux-date`; see [`Transaction::report_date`].
+    pub aux_date: Option<Date>,
     pub state: State,
     pub code: Option<String>,
     pub description: String,
     pub postings: Vec<Located<Posting>>,
     pub comments: Vec<Located<Comment>>,
+    /// Tags and `key: value` pairs from the transaction-level comments.
+    pub metadata: Metadata,
 }
 
 /// Transaction clear state, matching Ledger's `*`, `!`, and bare forms.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum State {
     Cleared,
     Uncleared,
     Pending,
 }
+
+impl Transaction {
+    /// The date reports key this transaction on: its aux date under
+    /// `--aux-date` when one is written, its primary date otherwise.
+    pub fn report_date(&self, aux: bool) -> Date {
+        match self.aux_date {
+            Some(d) if aux => d,
+            _ => self.date,
+        }
+    }
+
+    /// The clear state of one of this transaction's postings: its own
+    /// `* ` / `! ` marker when written, the transaction's otherwise.
+    pub fn posting_state(&self, p: &Posting) -> State {
+        p.state.unwrap_or(self.state)
+    }
+
+    /// The date reports key one of this transaction's postings on. Under
+    /// `--aux-date` a posting's own `; [=DATE]` wins over the transaction's
+    /// aux date; without it every posting shares the primary date.
+    pub fn posting_date(&self, p: &Posting, aux: bool) -> Date {
+        match p.aux_date {
+            Some(d) if aux => d,
+            _ => self.report_date(aux),
+        }
+    }
+}
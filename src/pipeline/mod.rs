@@ -3,7 +3,7 @@
 //! or sorting.
 //!
 //! These phases must see the *whole* journal, un-filtered: the lotter
-//! tracks lots FIFO across every transaction, and the translator
+//! tracks lots across every transaction, and the translator
 //! identifies pass-through accounts by their journal-wide native sum.
 //! Filtering first would change those sums and corrupt the result. They
 //! are also where the subtle cross-phase rules live, so they belong
@@ -14,18 +14,22 @@
 //! 2. **realizer**  — per-transaction slippage gain/loss: the trade-day
 //!    execution spread, where each leg's market value diverges from the
 //!    others. Runs on every multi-commodity transaction (buy and sell).
-//! 3. **lotter**    — realized capital gains via FIFO lots: the holding-
-//!    period market move of each disposed lot. Composes with the
+//! 3. **lotter**    — realized capital gains via lots, closed per each
+//!    account's booking method (FIFO by default): the holding-period
+//!    market move of each disposed lot. Composes with the
 //!    realizer — it books capital (the market move), the realizer books
 //!    slippage (the execution spread), so neither double-books the other.
 //! 4. **translator** — currency translation adjustment (CTA) for
 //!    pass-through accounts. Lot-tracked assets enter at their market
 //!    value and leave at that same value as the `{}` cost basis, so they
 //!    net to zero under conversion — CTA sees no drift there.
+//!    Under `--rate-method`, it also releases the difference between the
+//!    period-average and spot values of the averaged accounts to CTA.
 //! 5. **revaluator** — opt-in (`-X` with `--unrealized`) mark-to-market of
-//!    every open foreign balance to the latest available rate, booking the
-//!    unrealized revaluation to the `holding` accounts. Off by default, so the
-//!    historical (realized) view is untouched.
+//!    every open foreign balance at the report's value date (else the latest
+//!    available rate), booking the unrealized revaluation to the `holding`
+//!    accounts. Off by default, so the historical (realized) view is
+//!    untouched.
 //!
 //! `rebalance`, `filter` and `sort` are deliberately *not* here. They are
 //! driven by CLI flags (pattern, date range, `-X` target, sort keys) and
@@ -33,17 +37,38 @@
 //! per-posting and local, so it can run after filtering; these four
 //! cannot.
 
+use crate::filter::PatternMatcher;
 use crate::loader::Journal;
+use crate::resolver::AccountType;
+use crate::translator::{AveragePeriod, Averaging};
+
+/// `--rate-method monthly-average|yearly-average`: the accounts whose
+/// postings translate at the average rate of their month or year.
+pub struct RateMethod {
+    pub period: AveragePeriod,
+    /// The `--rate-accounts` pattern; with none, the accounts declared
+    /// `type income` or `type expense`.
+    pub accounts: Option<PatternMatcher>,
+}
 
 /// Run the journal-global enrichment phases in order, mutating
 /// `journal.transactions` in place.
 ///
 /// `target` is the resolved `-X` commodity, or `None` in native mode.
+/// `value_date` is the day `--unrealized` revalues as of; `None` marks to
+/// the latest available rate. `rate_method` averages the translation rate
+/// of some accounts; `None` keeps the spot rate throughout.
 /// Phases that only make sense under conversion (realizer, translator)
 /// are skipped when it is `None`; the lotter always runs when capital
 /// accounts are declared (it realizes in the booked commodity either
-/// way).
-pub fn enrich(journal: &mut Journal, target: Option<&str>, unrealized: bool) {
+/// way), and fails on a `specific` disposal that names no open lot.
+pub fn enrich(
+    journal: &mut Journal,
+    target: Option<&str>,
+    unrealized: bool,
+    value_date: Option<crate::date::Date>,
+    rate_method: Option<&RateMethod>,
+) -> Result<(), crate::Error> {
     crate::expander::expand(&mut journal.transactions, &journal.auto_rules);
 
     // The realizer books the per-trade execution spread (slippage) on every
@@ -70,14 +95,23 @@ pub fn enrich(journal: &mut Journal, target: Option<&str>, unrealized: bool) {
         let accounts = crate::lotter::CapitalAccounts {
             capital_gain: cg,
             capital_loss: cl,
+            short_gain: journal.capital_gain_short.as_deref(),
+            short_loss: journal.capital_loss_short.as_deref(),
+            long_gain: journal.capital_gain_long.as_deref(),
+            long_loss: journal.capital_loss_long.as_deref(),
+            long_term_days: journal.long_term_days.unwrap_or(crate::lotter::LONG_TERM_DAYS),
         };
-        crate::lotter::realize_capital(
+        let lots = crate::lotter::realize_capital(
             &mut journal.transactions,
             &accounts,
+            &journal.bookings,
+            &journal.account_types,
             target,
             &journal.prices,
             &journal.precisions,
-        );
+        )?;
+        journal.lots = lots.open;
+        journal.realized = lots.realized;
     }
 
     if let (Some(t), Some(cta_gain), Some(cta_loss)) =
@@ -92,10 +126,27 @@ pub fn enrich(journal: &mut Journal, target: Option<&str>, unrealized: bool) {
             cta_loss,
             precision,
         );
+        if let Some(method) = rate_method {
+            let types = &journal.account_types;
+            let averaged = |account: &str| match &method.accounts {
+                Some(pattern) => pattern.matches(account),
+                None => matches!(types.get(account), Some(AccountType::Income | AccountType::Expense)),
+            };
+            let averaging = Averaging { period: method.period, accounts: &averaged };
+            crate::translator::average(
+                &mut journal.transactions,
+                t,
+                &journal.prices,
+                &averaging,
+                cta_gain,
+                cta_loss,
+                precision,
+            );
+        }
     }
 
-    // `--unrealized`: mark open foreign positions to the latest available
-    // rate, booking the unrealized revaluation to the `holding` accounts.
+    // `--unrealized`: mark open foreign positions to the rate on the value
+    // date (else the latest available one), booking the unrealized revaluation to the `holding` accounts.
     // Opt-in and separate from the historical default, so the realized
     // (default) view stays unchanged.
     if let (Some(t), true, Some(rg), Some(rl)) = (
@@ -111,6 +162,31 @@ pub fn enrich(journal: &mut Journal, target: Option<&str>, unrealized: bool) {
             &journal.prices,
             &crate::revaluator::RevaluationAccounts { gain: rg, loss: rl },
             precision,
+            value_date,
         );
     }
+    Ok(())
+}
+
+/// `--forecast UNTIL`: book the occurrences of every recurring rule from
+/// the day after the last recorded transaction up to `until`, and merge
+/// them into `journal.transactions` in date order.
+///
+/// Runs before [`enrich`], so auto-rules, lots and conversion treat the
+/// projected entries like recorded ones. They are booked on their own:
+/// a bare leg is filled from its own entry, never from the ledger.
+pub fn forecast(
+    journal: &mut Journal,
+    until: crate::date::Date,
+) -> Result<(), crate::Error> {
+    let from = crate::forecaster::window_start(&journal.transactions);
+    let generated = crate::forecaster::forecast(&journal.recurring, from, until, true);
+    if generated.is_empty() {
+        return Ok(());
+    }
+    let booked = crate::booker::book_with_splits(generated, &[], &journal.bookings)
+        .map_err(|e| crate::Error::from(e.to_string()))?;
+    journal.transactions.extend(booked);
+    journal.transactions.sort_by_key(|lt| lt.value.date);
+    Ok(())
 }
@@ -28,6 +28,7 @@ use std::collections::{HashMap, HashSet};
 use crate::decimal::Decimal;
 use crate::indexer::Index;
 use crate::parser::located::Located;
+use crate::parser::metadata::Metadata;
 use crate::parser::posting::{Amount, Posting};
 use crate::parser::transaction::Transaction;
 
@@ -150,6 +151,7 @@ fn augment(
             lot_cost: None,
             lot_date: None,
             balance_assertion: None,
+            state: None,
             // Real posting: the spread is the trade-day delta between the
             // legs' market value (after `-X` conversion). It sits next to
             // the converted amounts and makes the transaction balance in
@@ -158,6 +160,8 @@ fn augment(
             is_virtual: false,
             balanced: true,
             comments: Vec::new(),
+            metadata: Metadata::default(),
+            aux_date: None,
         },
     });
 }
@@ -2,7 +2,8 @@
 //! commodity using the journal's PriceDB.
 //!
 //! Each posting is converted at the rate on its own transaction date
-//! (historical valuation). Postings whose commodity has no rate path to
+//! (historical valuation) — or, under `--aux-date`, on the transaction's
+//! effective date. Postings whose commodity has no rate path to
 //! `target` stay unchanged — downstream reports show them as remainders
 //! in their original commodity.
 //!
@@ -23,19 +24,86 @@
 
 use std::collections::HashMap;
 
+use crate::date::Date;
 use crate::decimal::Decimal;
+use crate::indexer::index::plain;
 use crate::indexer::Index;
+use crate::parser::comment::Comment;
 use crate::parser::located::Located;
 use crate::parser::posting::{Costs, Posting};
 use crate::parser::transaction::Transaction;
 
 /// Convert every posting's amount to `target` in place, each at the
-/// exchange rate on its own transaction date.
-pub fn rebalance(transactions: &mut [Located<Transaction>], target: &str, db: &Index) {
+/// exchange rate on its own transaction date. With `aux` (`--aux-date`)
+/// the rate is taken on the transaction's effective date instead
+/// ([`Transaction::report_date`]): a card payment converts at its
+/// settlement date, not its purchase date. A posting's own `[=DATE]`
+/// moves it between report periods but not onto another rate — legs
+/// converted at different rates would leave the transaction unbalanced.
+pub fn rebalance(transactions: &mut [Located<Transaction>], target: &str, db: &Index, aux: bool) {
     for lt in transactions {
-        let lookup_date: String = lt.value.date.to_string();
+        let date = lt.value.report_date(aux).to_string();
         for lp in &mut lt.value.postings {
-            convert(&mut lp.value, target, db, &lookup_date);
+            convert(&mut lp.value, target, db, &date);
+        }
+    }
+}
+
+/// A posting [`rebalance`] leaves in its own commodity: its weight has no
+/// rate path to the target on the transaction's conversion date.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unconverted<'a> {
+    pub file: &'a str,
+    pub line: usize,
+    /// The weight's commodity ([`weight`]) — the side lacking a rate.
+    pub commodity: &'a str,
+    pub date: Date,
+}
+
+/// Every posting [`rebalance`] would leave unconverted, in journal order.
+/// Runs on the native amounts; `--strict-rates` fails on a non-empty list
+/// and `missing-rates` groups it.
+pub fn unconverted<'a>(
+    transactions: &'a [Located<Transaction>],
+    target: &str,
+    db: &Index,
+    aux: bool,
+) -> Vec<Unconverted<'a>> {
+    let mut missing = Vec::new();
+    for lt in transactions {
+        let date = lt.value.report_date(aux);
+        for lp in &lt.value.postings {
+            let Some((_, commodity)) = weight(&lp.value) else { continue };
+            if target_value(&lp.value, target, db, &date.to_string()).is_none() {
+                missing.push(Unconverted { file: &lp.file, line: lp.line, commodity, date });
+            }
+        }
+    }
+    missing
+}
+
+/// Prefix of the comment [`explain`] attaches to a posting.
+pub const RATE_NOTE: &str = "rate: ";
+
+/// `--explain-rates`: attach to every posting [`rebalance`] will convert
+/// a `rate:` comment naming the weight it converts and the path, hop
+/// rates and `P` dates behind the rate ([`Index::explain`]). Runs on the
+/// native amounts, just before `rebalance`.
+pub fn explain(transactions: &mut [Located<Transaction>], target: &str, db: &Index, aux: bool) {
+    for lt in transactions {
+        let date = lt.value.report_date(aux).to_string();
+        for lp in &mut lt.value.postings {
+            let Some((value, from)) = weight(&lp.value) else { continue };
+            if from == target {
+                continue;
+            }
+            let Some(explanation) = db.explain(from, target, &date) else { continue };
+            let text = format!("{RATE_NOTE}{} {} on {}: {}", plain(&value), from, date, explanation);
+            lp.value.comments.push(Located {
+                file: lp.file.clone(),
+                line: lp.line,
+                value: Comment { text },
+            });
         }
     }
 }
@@ -129,8 +197,21 @@ fn settle_round_off(tx: &mut Transaction, target: &str, unit: Decimal) {
 /// a converted transaction won't balance (weighting by the booked rate
 /// keeps both legs of a trade netting to zero).
 pub fn target_value(p: &Posting, target: &str, db: &Index, date: &str) -> Option<Decimal> {
+    let (value, from) = weight(p)?;
+    if from == target {
+        return Some(value);
+    }
+    // `mul_rounded` instead of `*` because inverse-rate lookups from the
+    // PriceDB can serve a 28-digit tail which would overflow strict `*`.
+    db.find(from, target, date).map(|rate| value.mul_rounded(rate))
+}
+
+/// A posting's balance weight and the commodity it is in: quantity ×
+/// cost basis for a `{}` lot, quantity × price for `@` / `@@`, else the
+/// amount itself. `None` without an amount.
+pub fn weight(p: &Posting) -> Option<(Decimal, &str)> {
     let amount = p.amount.as_ref()?;
-    let (value, from) = if let Some(lot) = &p.lot_cost {
+    let weight = if let Some(lot) = &p.lot_cost {
         (lot.weight(amount.value), lot.amount.commodity.as_str())
     } else if let Some(costs) = &p.costs {
         match costs {
@@ -142,13 +223,7 @@ pub fn target_value(p: &Posting, target: &str, db: &Index, date: &str) -> Option
     } else {
         (amount.value, amount.commodity.as_str())
     };
-
-    if from == target {
-        return Some(value);
-    }
-    // `mul_rounded` instead of `*` because inverse-rate lookups from the
-    // PriceDB can serve a 28-digit tail which would overflow strict `*`.
-    db.find(from, target, date).map(|rate| value.mul_rounded(rate))
+    Some(weight)
 }
 
 fn convert(p: &mut Posting, target: &str, db: &Index, date: &str) {
@@ -196,6 +271,27 @@ mod tests {
         Decimal::parse(s).unwrap()
     }
 
+    #[test]
+    fn unconverted_lists_postings_without_a_rate_path() {
+        let (txs, db) = setup(
+            "P 2024-06-01 USD EUR 0.9\n\
+             2024-06-01 * x\n\
+             \tassets:usd   100 USD\n\
+             \tassets:gbp   -80 GBP @@ 100 USD\n\
+             2024-06-02 * y\n\
+             \tassets:gbp    10 GBP\n\
+             \tequity:open\n",
+        );
+        let missing = unconverted(&txs, "EUR", &db, false);
+        // The `@@` leg weighs in USD, so only the plain GBP legs lack a rate.
+        let found: Vec<(usize, &str, String)> =
+            missing.iter().map(|m| (m.line, m.commodity, m.date.to_string())).collect();
+        assert_eq!(
+            found,
+            [(6, "GBP", "2024-06-02".to_string()), (7, "GBP", "2024-06-02".to_string())]
+        );
+    }
+
     // ── target_value: the get_weight ladder ──────────────────────────
 
     #[test]
@@ -288,7 +384,7 @@ mod tests {
              \tassets:usd   100 USD\n\
              \tequity:open -100 USD\n",
         );
-        rebalance(&mut txs, "EUR", &db);
+        rebalance(&mut txs, "EUR", &db, false);
         let prec = HashMap::from([("EUR".to_string(), 2usize)]);
         round_for_print(&mut txs, "EUR", &prec);
         // 100 × 0.93331 = 93.331 → 93.33 at 2 decimals.
@@ -308,7 +404,7 @@ mod tests {
              \texpenses:b    10 USD\n\
              \tassets:cash  -20 USD\n",
         );
-        rebalance(&mut txs, "EUR", &db);
+        rebalance(&mut txs, "EUR", &db, false);
         let prec = HashMap::from([("EUR".to_string(), 2usize)]);
         round_for_print(&mut txs, "EUR", &prec);
         let sum: Decimal = txs[0]
@@ -331,7 +427,7 @@ mod tests {
              \tassets:usd   100 USD\n\
              \tassets:gbp  -100 GBP\n",
         );
-        rebalance(&mut txs, "EUR", &db);
+        rebalance(&mut txs, "EUR", &db, false);
         let prec = HashMap::from([("EUR".to_string(), 2usize)]);
         round_for_print(&mut txs, "EUR", &prec);
         // GBP leg has no rate → stays GBP; USD leg became 90 EUR. The
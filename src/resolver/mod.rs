@@ -9,14 +9,22 @@
 //! - a `commodity S / parity T` declaration is turned into a synthetic
 //!   1:1 `Price` (S T, rate 1, day 0) so the valuation path values S as
 //!   T without folding S's display — the price index / BFS chain it;
+//! - under `commodity S / infer-prices`, each `@` / `@@` cost on an S
+//!   posting becomes a synthetic `Price` on its transaction's date, unless
+//!   a `P` entry prices the pair that day;
 //! - `slippage gain`/`slippage loss`, `cta gain`/`cta loss` and
-//!   `capital gain`/`capital loss` account declarations are extracted;
+//!   `capital gain`/`capital loss` account declarations are extracted,
+//!   plus the optional `capital gain|loss short|long` term split and its
+//!   `long N` day threshold;
+//! - `booking METHOD` sub-directives become a per-account lot booking map,
+//!   `type CLASS` and `cashflow SECTION` ones per-account [`AccountMap`]s;
 //! - transactions and prices are split into separate, date-sorted vecs;
 //! - all other entries (Commodity/Account scaffolds, Comment) are
 //!   dropped — their information has been extracted.
 //!
-//! Errors on alias conflicts (`$ → USD` and later `$ → EUR`) and on
-//! duplicate fx / cta / capital account declarations.
+//! Errors on alias conflicts (`$ → USD` and later `$ → EUR`), on
+//! duplicate fx / cta / capital account declarations, and on an unknown
+//! or conflicting `booking` method, account `type` or `cashflow` section.
 
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -27,9 +35,11 @@ use crate::parser::entry::{
     AmountCondition, AutoAmount, AutoPattern, AutoPosting, AutoRule, Cadence, Entry,
 };
 use crate::parser::located::Located;
+use crate::parser::metadata::Metadata;
 use crate::parser::posting::{Costs, Posting};
 use crate::parser::transaction::{State, Transaction};
-use crate::parser::entry::Price;
+use crate::parser::entry::{Price, Recurring, Split};
+use crate::lotter::Booking;
 
 pub mod error;
 
@@ -64,6 +74,15 @@ impl LabelSet {
 pub struct Resolved {
     pub transactions: Vec<Located<Transaction>>,
     pub prices: Vec<Located<Price>>,
+    /// `split` / `convert` directives, alias-resolved, in date order.
+    pub splits: Vec<Located<Split>>,
+    /// Occurrences of `~~` budget blocks, alias- and role-resolved, in
+    /// date order. Never booked: only `acc budget` reads them.
+    pub budget: Vec<Located<Transaction>>,
+    /// `~ EVERY from …` rules, alias- and role-resolved, in source order.
+    /// `$year` / `$month` / `$day` stay in the accounts until an occurrence
+    /// is generated.
+    pub recurring: Vec<Located<Recurring>>,
     pub slippage_gain: Option<String>,
     pub slippage_loss: Option<String>,
     pub cta_gain: Option<String>,
@@ -72,6 +91,15 @@ pub struct Resolved {
     /// Both must be present for the lot/capital-gains phase to run.
     pub capital_gain: Option<String>,
     pub capital_loss: Option<String>,
+    /// Optional holding-period split of the capital pair: `capital gain
+    /// short` / `capital gain long` / `capital loss short` / `capital loss
+    /// long`. `long_term_days` is the threshold written after a `long`
+    /// role (`capital gain long 365`), if any.
+    pub capital_gain_short: Option<String>,
+    pub capital_gain_long: Option<String>,
+    pub capital_loss_short: Option<String>,
+    pub capital_loss_long: Option<String>,
+    pub long_term_days: Option<u32>,
     /// Declared via `account NAME / holding gain` / `holding
     /// loss`. Both must be present for the `--unrealized` revaluator to run.
     pub holding_gain: Option<String>,
@@ -81,6 +109,9 @@ pub struct Resolved {
     /// so declared commodities render with exactly N fractional digits,
     /// regardless of what the posting amounts contain.
     pub precisions: HashMap<String, usize>,
+    /// `max-age N` values from `commodity` directives, in days. The loader
+    /// hands them to the price index, which will not use an older rate.
+    pub max_ages: HashMap<String, u32>,
     /// `alias → canonical` map collected from `commodity` directives.
     /// Handed downstream so CLI targets like `-X EUR` can be resolved
     /// to `€` before they reach the rebalancer or the price DB.
@@ -98,6 +129,12 @@ pub struct Resolved {
     pub labels: LabelSet,
     pub labels_balance: LabelSet,
     pub labels_register: LabelSet,
+    /// `account NAME / booking METHOD` declarations, inherited down the tree.
+    pub bookings: Bookings,
+    /// `account NAME / type CLASS` declarations, inherited down the tree.
+    pub account_types: AccountTypes,
+    /// `account NAME / cashflow SECTION` declarations, inherited down the tree.
+    pub cashflow_sections: CashflowSections,
 }
 
 /// A named auto-rule template from `= NAME :: /pattern/`. Its `pattern` and
@@ -115,9 +152,15 @@ pub fn resolve(entries: Vec<Located<Entry>>) -> Result<Resolved, ResolveError> {
         aliases,
         roles,
         precisions,
+        max_ages,
+        infer_prices,
         labels,
         labels_balance,
         labels_register,
+        bookings,
+        account_types,
+        cashflow_sections,
+        long_term_days,
         lookups,
         templates,
     } = collect_declarations(&entries)?;
@@ -131,6 +174,10 @@ pub fn resolve(entries: Vec<Located<Entry>>) -> Result<Resolved, ResolveError> {
     let cta_loss = roles.get("cta loss").cloned();
     let capital_gain = roles.get("capital gain").cloned();
     let capital_loss = roles.get("capital loss").cloned();
+    let capital_gain_short = roles.get("capital gain short").cloned();
+    let capital_gain_long = roles.get("capital gain long").cloned();
+    let capital_loss_short = roles.get("capital loss short").cloned();
+    let capital_loss_long = roles.get("capital loss long").cloned();
     let holding_gain = roles.get("holding gain").cloned();
     let holding_loss = roles.get("holding loss").cloned();
 
@@ -148,6 +195,9 @@ pub fn resolve(entries: Vec<Located<Entry>>) -> Result<Resolved, ResolveError> {
 
     let mut transactions = Vec::new();
     let mut prices = Vec::new();
+    let mut splits = Vec::new();
+    let mut budget = Vec::new();
+    let mut recurring = Vec::new();
     let mut auto_rules = Vec::new();
 
     for Located { file, line, value } in entries {
@@ -157,6 +207,11 @@ pub fn resolve(entries: Vec<Located<Entry>>) -> Result<Resolved, ResolveError> {
                 p.quote = resolve_arc(&mut interner, &arc_aliases, p.quote);
                 prices.push(Located { file, line, value: p });
             }
+            Entry::Split(mut split) => {
+                apply_alias(&mut split.from, &aliases);
+                apply_alias(&mut split.to, &aliases);
+                splits.push(Located { file, line, value: split });
+            }
             Entry::Transaction(mut tx) => {
                 if tx.postings.len() < 2 {
                     return Err(ResolveError::new(
@@ -219,28 +274,50 @@ pub fn resolve(entries: Vec<Located<Entry>>) -> Result<Resolved, ResolveError> {
                 let rules = expand_instance(&name, &args, &templates, &lookups, &file, line)?;
                 auto_rules.extend(rules);
             }
-            Entry::Periodic { period, cadence, description, postings } => {
+            Entry::Periodic { period, cadence, description, budget: is_budget, postings } => {
                 // A `~ PERIOD [cadence]` block expands into real, ordinary
                 // transactions — one per occurrence in the year, the written
                 // total split across them. Each then gets the same alias / date /
                 // role finalization as any transaction, and the booker balances
-                // and auto-fills it downstream.
-                if postings.len() < 2 {
+                // and auto-fills it downstream. A budget block's occurrences go
+                // aside unbooked, so a single posting is enough.
+                let min = if is_budget { 1 } else { 2 };
+                if postings.len() < min {
                     return Err(ResolveError::new(
                         file.clone(),
                         line,
                         format!(
-                            "periodic block `~ {period}` must have at least two postings, got {}",
+                            "periodic block `~ {period}` must have at least {} postings, got {}",
+                            if is_budget { "one" } else { "two" },
                             postings.len()
                         ),
                     ));
                 }
                 let occurrences =
                     expand_periodic(&period, cadence, &description, postings, &file, line)?;
+                let into = if is_budget { &mut budget } else { &mut transactions };
                 for mut tx in occurrences {
                     finalize_tx_postings(&mut tx, &aliases, &roles);
-                    transactions.push(Located { file: file.clone(), line, value: tx });
+                    into.push(Located { file: file.clone(), line, value: tx });
+                }
+            }
+            Entry::Recurring(mut rule) => {
+                // A recurring rule books nothing now; the forecaster dates its
+                // occurrences later, so only aliases and roles resolve here.
+                if rule.postings.len() < 2 {
+                    return Err(ResolveError::new(
+                        file.clone(),
+                        line,
+                        format!("recurring block must have at least two postings, got {}", rule.postings.len()),
+                    ));
+                }
+                for lp in &mut rule.postings {
+                    apply_to_posting(&mut lp.value, &aliases);
+                    if let Some(name) = resolve_role_account(&lp.value.account, &roles) {
+                        lp.value.account = name;
+                    }
                 }
+                recurring.push(Located { file: file.clone(), line, value: rule });
             }
             // Account/Lookup/AutoTemplate scaffolds and Comment entries carry
             // no data we still need here — the first pass already consumed the
@@ -249,9 +326,16 @@ pub fn resolve(entries: Vec<Located<Entry>>) -> Result<Resolved, ResolveError> {
         }
     }
 
+    if !infer_prices.is_empty() {
+        let inferred = infer_from_costs(&transactions, &infer_prices, &prices, &mut interner);
+        prices.extend(inferred);
+    }
+
     // Transactions must be date-sorted: the booker validates balance
     // assertions in chronological order.
     transactions.sort_by_key(|a| a.value.date);
+    splits.sort_by_key(|s| s.value.date);
+    budget.sort_by_key(|b| b.value.date);
     // Prices are NOT sorted here: the indexer stores each pair's series
     // in a `BTreeMap<day, rate>` that orders itself, and a same-day
     // collision resolves to the last directive in file order either way
@@ -261,20 +345,32 @@ pub fn resolve(entries: Vec<Located<Entry>>) -> Result<Resolved, ResolveError> {
     Ok(Resolved {
         transactions,
         prices,
+        splits,
+        budget,
+        recurring,
         slippage_gain,
         slippage_loss,
         cta_gain,
         cta_loss,
         capital_gain,
         capital_loss,
+        capital_gain_short,
+        capital_gain_long,
+        capital_loss_short,
+        capital_loss_long,
+        long_term_days,
         holding_gain,
         holding_loss,
         precisions,
+        max_ages,
         aliases,
         auto_rules,
         labels,
         labels_balance,
         labels_register,
+        bookings,
+        account_types,
+        cashflow_sections,
     })
 }
 
@@ -481,6 +577,56 @@ fn resolve_role_account(account: &str, roles: &HashMap<String, String>) -> Optio
     roles.get(&token.replace(':', " ")).cloned()
 }
 
+/// `infer-prices`: a `Price` per `@` / `@@` cost on a posting in one of
+/// `commodities`, on its transaction's date — the per-unit cost, `@@`
+/// divided by the quantity. A pair (either way round) that a `P` entry
+/// prices on that day keeps only the `P`; among trades of one day the
+/// index keeps the last, as for `P` entries.
+fn infer_from_costs(
+    transactions: &[Located<Transaction>],
+    commodities: &HashSet<String>,
+    explicit: &[Located<Price>],
+    interner: &mut HashSet<Arc<str>>,
+) -> Vec<Located<Price>> {
+    let quoted: HashSet<(&str, &str, u32)> = explicit
+        .iter()
+        .flat_map(|lp| {
+            let p = &lp.value;
+            let day = p.date.days();
+            [(&*p.base, &*p.quote, day), (&*p.quote, &*p.base, day)]
+        })
+        .collect();
+    let mut inferred = Vec::new();
+    for lt in transactions {
+        for lp in &lt.value.postings {
+            let p = &lp.value;
+            let (Some(amount), Some(costs)) = (&p.amount, &p.costs) else { continue };
+            if !commodities.contains(&amount.commodity) || amount.value.is_zero() {
+                continue;
+            }
+            let (rate, quote) = match costs {
+                Costs::PerUnit(c) => (c.value, &c.commodity),
+                Costs::Total(c) => (c.value.div_rounded(amount.value.abs()), &c.commodity),
+            };
+            let date = lt.value.date;
+            if quoted.contains(&(amount.commodity.as_str(), quote.as_str(), date.days())) {
+                continue;
+            }
+            inferred.push(Located {
+                file: lp.file.clone(),
+                line: lp.line,
+                value: Price {
+                    date,
+                    base: intern_str(interner, &amount.commodity),
+                    quote: intern_str(interner, quote),
+                    rate,
+                },
+            });
+        }
+    }
+    inferred
+}
+
 /// Return the interned `Arc<str>` for `s`, inserting it on first sight.
 fn intern_str(interner: &mut HashSet<Arc<str>>, s: &str) -> Arc<str> {
     if let Some(existing) = interner.get(s) {
@@ -511,6 +657,115 @@ fn resolve_arc(
     arc
 }
 
+/// An account's class for the financial statements, from an `account
+/// NAME / type CLASS` sub-directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AccountType {
+    Asset,
+    Liability,
+    Equity,
+    Income,
+    Expense,
+}
+
+impl AccountType {
+    /// Parse a class name (case-insensitive). `None` for anything else.
+    pub fn parse(s: &str) -> Option<AccountType> {
+        match s.to_ascii_lowercase().as_str() {
+            "asset" => Some(AccountType::Asset),
+            "liability" => Some(AccountType::Liability),
+            "equity" => Some(AccountType::Equity),
+            "income" => Some(AccountType::Income),
+            "expense" => Some(AccountType::Expense),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for AccountType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            AccountType::Asset => "asset",
+            AccountType::Liability => "liability",
+            AccountType::Equity => "equity",
+            AccountType::Income => "income",
+            AccountType::Expense => "expense",
+        })
+    }
+}
+
+/// A section of the cash flow statement, from an `account NAME /
+/// cashflow SECTION` sub-directive on a counter-party account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CashflowSection {
+    Operating,
+    Investing,
+    Financing,
+}
+
+impl CashflowSection {
+    /// Parse a section name (case-insensitive). `None` for anything else.
+    pub fn parse(s: &str) -> Option<CashflowSection> {
+        match s.to_ascii_lowercase().as_str() {
+            "operating" => Some(CashflowSection::Operating),
+            "investing" => Some(CashflowSection::Investing),
+            "financing" => Some(CashflowSection::Financing),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for CashflowSection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CashflowSection::Operating => "operating",
+            CashflowSection::Investing => "investing",
+            CashflowSection::Financing => "financing",
+        })
+    }
+}
+
+/// Per-account declarations that cover the account and every account
+/// below it; the deepest declared ancestor wins, and an account with no
+/// declared ancestor has none.
+#[derive(Debug, Clone)]
+pub struct AccountMap<T> {
+    pub declared: HashMap<String, T>,
+}
+
+impl<T> Default for AccountMap<T> {
+    fn default() -> Self {
+        AccountMap { declared: HashMap::new() }
+    }
+}
+
+impl<T: Copy> AccountMap<T> {
+    /// The value for `account`: its own declaration, else the deepest
+    /// declared parent's.
+    pub fn get(&self, account: &str) -> Option<T> {
+        let mut name = account;
+        loop {
+            if let Some(t) = self.declared.get(name) {
+                return Some(*t);
+            }
+            name = &name[..name.rfind(':')?];
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.declared.is_empty()
+    }
+}
+
+/// Declared account types, from `type CLASS`.
+pub type AccountTypes = AccountMap<AccountType>;
+
+/// Declared cash flow sections, from `cashflow SECTION`.
+pub type CashflowSections = AccountMap<CashflowSection>;
+
+/// Declared lot booking methods, from `booking METHOD`.
+pub type Bookings = AccountMap<Booking>;
+
 /// First pass: walk entries, build the alias table, index every role
 /// account by its directive text, and collect precision overrides.
 /// Errors on a conflicting re-declaration (same role, different account).
@@ -521,9 +776,17 @@ struct Declarations {
     aliases: HashMap<String, String>,
     roles: HashMap<String, String>,
     precisions: HashMap<String, usize>,
+    max_ages: HashMap<String, u32>,
+    /// Commodities declared `infer-prices`.
+    infer_prices: HashSet<String>,
     labels: LabelSet,
     labels_balance: LabelSet,
     labels_register: LabelSet,
+    bookings: Bookings,
+    account_types: AccountTypes,
+    cashflow_sections: CashflowSections,
+    /// The day threshold from a `capital gain|loss long N` role.
+    long_term_days: Option<u32>,
     /// Lookup tables from `= NAME[key] :: value` entries: table → (key → value).
     lookups: HashMap<String, HashMap<String, String>>,
     /// `= NAME :: /pattern/` auto-rule templates, by name.
@@ -537,11 +800,19 @@ fn collect_declarations(entries: &[Located<Entry>]) -> Result<Declarations, Reso
     // former per-role fields: a new role needs no change here.
     let mut roles: HashMap<String, Declaration> = HashMap::new();
     let mut precisions: HashMap<String, usize> = HashMap::new();
+    let mut max_ages: HashMap<String, u32> = HashMap::new();
+    let mut infer_prices: HashSet<String> = HashSet::new();
     // `label` / `label-balance` / `label-register` display labels.
     // `labels` is the shared fallback; the view-specific sets override it.
     let mut labels = LabelSet::default();
     let mut labels_balance = LabelSet::default();
     let mut labels_register = LabelSet::default();
+    // `booking METHOD` per account, with the declaring line for conflicts.
+    let mut bookings: HashMap<String, (Booking, usize)> = HashMap::new();
+    // `type CLASS` per account, likewise.
+    let mut account_types: HashMap<String, (AccountType, usize)> = HashMap::new();
+    let mut cashflow_sections: HashMap<String, (CashflowSection, usize)> = HashMap::new();
+    let mut long_term_days: Option<(u32, usize)> = None;
     // Lookup tables (`= NAME[key] :: value`) and `= NAME :: /pattern/`
     // templates, both gathered here so an instantiation can reference either
     // regardless of source order.
@@ -550,7 +821,7 @@ fn collect_declarations(entries: &[Located<Entry>]) -> Result<Declarations, Reso
 
     for e in entries {
         match &e.value {
-            Entry::Commodity { symbol, aliases: list, precision, .. } => {
+            Entry::Commodity { symbol, aliases: list, precision, max_age, infer_prices: infer, .. } => {
                 for a in list {
                     if let Some(existing) = aliases.get(a)
                         && existing != symbol {
@@ -568,6 +839,12 @@ fn collect_declarations(entries: &[Located<Entry>]) -> Result<Declarations, Reso
                 if let Some(p) = precision {
                     precisions.insert(symbol.clone(), *p);
                 }
+                if let Some(days) = max_age {
+                    max_ages.insert(symbol.clone(), *days);
+                }
+                if *infer {
+                    infer_prices.insert(symbol.clone());
+                }
             }
             Entry::RoleAccount { role, account } => {
                 // Display-label sub-directives, not roles: bare `label`
@@ -599,6 +876,101 @@ fn collect_declarations(entries: &[Located<Entry>]) -> Result<Declarations, Reso
                     }
                     continue;
                 }
+                // `booking METHOD`: the account's lot booking method. Like a
+                // role it may be repeated, but not changed.
+                if let Some(method) = role.strip_prefix("booking ") {
+                    let Some(booking) = Booking::parse(method.trim()) else {
+                        return Err(ResolveError::new(
+                            e.file.clone(),
+                            e.line,
+                            format!(
+                                "unknown booking method `{}` (expected fifo, lifo, hifo, average or specific)",
+                                method.trim()
+                            ),
+                        ));
+                    };
+                    if let Some((prev, line)) = bookings.get(account)
+                        && *prev != booking {
+                            return Err(ResolveError::new(
+                                e.file.clone(),
+                                e.line,
+                                format!(
+                                    "`{}` booking already set to `{}` at line {}",
+                                    account, prev, line
+                                ),
+                            ));
+                        }
+                    bookings.insert(account.clone(), (booking, e.line));
+                    continue;
+                }
+                // `type CLASS`: the account's statement class, same rules.
+                if let Some(class) = role.strip_prefix("type ") {
+                    let Some(t) = AccountType::parse(class.trim()) else {
+                        return Err(ResolveError::new(
+                            e.file.clone(),
+                            e.line,
+                            format!(
+                                "unknown account type `{}` (expected asset, liability, equity, income or expense)",
+                                class.trim()
+                            ),
+                        ));
+                    };
+                    if let Some((prev, line)) = account_types.get(account)
+                        && *prev != t {
+                            return Err(ResolveError::new(
+                                e.file.clone(),
+                                e.line,
+                                format!(
+                                    "`{}` type already set to `{}` at line {}",
+                                    account, prev, line
+                                ),
+                            ));
+                        }
+                    account_types.insert(account.clone(), (t, e.line));
+                    continue;
+                }
+                // `cashflow SECTION`: the counter-party's cash flow section.
+                if let Some(section) = role.strip_prefix("cashflow ") {
+                    let Some(c) = CashflowSection::parse(section.trim()) else {
+                        return Err(ResolveError::new(
+                            e.file.clone(),
+                            e.line,
+                            format!(
+                                "unknown cashflow section `{}` (expected operating, investing or financing)",
+                                section.trim()
+                            ),
+                        ));
+                    };
+                    if let Some((prev, line)) = cashflow_sections.get(account)
+                        && *prev != c {
+                            return Err(ResolveError::new(
+                                e.file.clone(),
+                                e.line,
+                                format!(
+                                    "`{}` cashflow section already set to `{}` at line {}",
+                                    account, prev, line
+                                ),
+                            ));
+                        }
+                    cashflow_sections.insert(account.clone(), (c, e.line));
+                    continue;
+                }
+                // `capital gain long 365`: the long-term roles may carry the
+                // holding-period threshold in days. Both long roles may state
+                // it, but they must agree; the role itself is stored bare.
+                let (role, days) = split_long_term_days(role);
+                if let Some(days) = days {
+                    if let Some((prev, line)) = long_term_days
+                        && prev != days {
+                            return Err(ResolveError::new(
+                                e.file.clone(),
+                                e.line,
+                                format!("long-term threshold already set to {} days at line {}", prev, line),
+                            ));
+                        }
+                    long_term_days = Some((days, e.line));
+                }
+                let role = &role;
                 if let Some(prev) = roles.get(role)
                     && prev.name != *account {
                         return Err(ResolveError::new(
@@ -683,14 +1055,40 @@ fn collect_declarations(entries: &[Located<Entry>]) -> Result<Declarations, Reso
         aliases,
         roles: roles.into_iter().map(|(role, d)| (role, d.name)).collect(),
         precisions,
+        max_ages,
+        infer_prices,
         labels,
         labels_balance,
         labels_register,
+        bookings: Bookings {
+            declared: bookings.into_iter().map(|(account, (b, _))| (account, b)).collect(),
+        },
+        account_types: AccountTypes {
+            declared: account_types.into_iter().map(|(account, (t, _))| (account, t)).collect(),
+        },
+        cashflow_sections: CashflowSections {
+            declared: cashflow_sections.into_iter().map(|(account, (c, _))| (account, c)).collect(),
+        },
+        long_term_days: long_term_days.map(|(days, _)| days),
         lookups,
         templates,
     })
 }
 
+/// Split a trailing day count off a long-term capital role: `capital gain
+/// long 365` → (`capital gain long`, `Some(365)`). Any other role passes
+/// through unchanged.
+fn split_long_term_days(role: &str) -> (String, Option<u32>) {
+    for base in ["capital gain long", "capital loss long"] {
+        if let Some(rest) = role.strip_prefix(base)
+            && let Some(days) = rest.strip_prefix(' ').and_then(|d| d.trim().parse().ok())
+        {
+            return (base.to_string(), Some(days));
+        }
+    }
+    (role.to_string(), None)
+}
+
 /// A single-fact declaration that lives only long enough to catch a
 /// conflicting re-declaration. The `line` is carried along for the
 /// error message; the final `Resolved` struct only keeps `name`.
@@ -715,6 +1113,9 @@ fn apply_to_posting(p: &mut Posting, aliases: &HashMap<String, String>) {
         };
         apply_alias(&mut a.commodity, aliases);
     }
+    if let Some(l) = &mut p.lot_cost {
+        apply_alias(&mut l.amount.commodity, aliases);
+    }
     if let Some(a) = &mut p.balance_assertion {
         apply_alias(&mut a.commodity, aliases);
     }
@@ -810,11 +1211,13 @@ fn expand_periodic(
                 .collect();
             Transaction {
                 date,
+                aux_date: None,
                 state: State::Uncleared,
                 code: None,
                 description: description.to_string(),
                 postings: occ_postings,
                 comments: Vec::new(),
+                metadata: Metadata::default(),
             }
         })
         .collect();
@@ -895,6 +1298,26 @@ mod tests {
         assert_eq!(p.value.rate, Decimal::from(1));
     }
 
+    #[test]
+    fn infer_prices_emits_costs_unless_a_p_entry_prices_the_day() {
+        let src = "commodity HOUSE\n    infer-prices\n\
+                   P 2024-03-01 € HOUSE 0.000004\n\
+                   2024-01-10 * buy\n    assets:house  1 HOUSE @@ 200000 €\n    assets:bank\n\
+                   2024-03-01 * more\n    assets:house  0.5 HOUSE @ 260000 €\n    assets:bank\n\
+                   2024-03-02 * rent\n    assets:bank  10 USD @ 0.9 €\n    income:rent\n";
+        let out = resolve(parsed(src)).unwrap();
+        let inferred: Vec<(String, &str, &str, Decimal)> = out
+            .prices
+            .iter()
+            .filter(|p| &*p.value.base == "HOUSE")
+            .map(|p| (p.value.date.to_string(), &*p.value.base, &*p.value.quote, p.value.rate))
+            .collect();
+        // Only the January trade: March 1 has a `P` entry (the other way
+        // round), and USD is not declared `infer-prices`.
+        assert_eq!(inferred, [("2024-01-10".to_string(), "HOUSE", "€", Decimal::from(200000))]);
+        assert_eq!(out.prices.len(), 2);
+    }
+
     #[test]
     fn applies_alias_to_posting_amount() {
         let src = "commodity USD\n    alias $\n2024-06-15 * X\n    expenses:food   $5\n    assets:cash  $-5\n";
@@ -938,6 +1361,48 @@ mod tests {
         assert_eq!(out.labels.get("a:2"), None);
     }
 
+    #[test]
+    fn collects_booking_methods_and_rejects_unknown_ones() {
+        let src = "account assets:broker\n    booking hifo\naccount assets:cash\n    booking fifo\n";
+        let r = resolve(parsed(src)).unwrap();
+        assert_eq!(r.bookings.get("assets:broker"), Some(Booking::Hifo));
+        assert_eq!(r.bookings.get("assets:cash"), Some(Booking::Fifo));
+        assert_eq!(r.bookings.get("assets:broker:btc"), Some(Booking::Hifo));
+        assert!(resolve(parsed("account a\n    booking fofi\n")).is_err());
+        assert!(resolve(parsed("account a\n    booking lifo\naccount a\n    booking hifo\n")).is_err());
+    }
+
+    #[test]
+    fn account_types_inherit_down_the_tree() {
+        let src = "account assets\n    type asset\naccount assets:loan\n    type Liability\n";
+        let types = resolve(parsed(src)).unwrap().account_types;
+        assert_eq!(types.get("assets:bank:checking"), Some(AccountType::Asset));
+        assert_eq!(types.get("assets:loan:car"), Some(AccountType::Liability));
+        assert_eq!(types.get("assetsx"), None);
+        assert!(resolve(parsed("account a\n    type cash\n")).is_err());
+        assert!(resolve(parsed("account a\n    type asset\naccount a\n    type equity\n")).is_err());
+        let sections = resolve(parsed("account assets:stock\n    cashflow investing\n")).unwrap().cashflow_sections;
+        assert_eq!(sections.get("assets:stock:aapl"), Some(CashflowSection::Investing));
+        assert!(resolve(parsed("account a\n    cashflow other\n")).is_err());
+    }
+
+    #[test]
+    fn long_term_roles_carry_the_threshold() {
+        let src = "account income:cap:long\n    capital gain long 730\n\
+                   account expenses:cap:long\n    capital loss long\n\
+                   account income:cap:short\n    capital gain short\n";
+        let r = resolve(parsed(src)).unwrap();
+        assert_eq!(r.capital_gain_long.as_deref(), Some("income:cap:long"));
+        assert_eq!(r.capital_loss_long.as_deref(), Some("expenses:cap:long"));
+        assert_eq!(r.capital_gain_short.as_deref(), Some("income:cap:short"));
+        assert_eq!(r.long_term_days, Some(730));
+        let err = resolve(parsed(
+            "account a\n    capital gain long 365\naccount b\n    capital loss long 730\n",
+        ))
+        .unwrap_err();
+        assert!(err.message.contains("365 days"));
+    }
+
     #[test]
     fn sorts_transactions_by_date() {
         let src = "2024-06-15 * Later\n    assets:cash  1 USD\n    equity  -1 USD\n\
@@ -13,11 +13,17 @@
 //! the latest rate on demand. The default (no `--unrealized`) stays purely
 //! historical, so the realized / tax-relevant view is unchanged.
 //!
-//! "Latest available" is a single rate per commodity — the most recent on
-//! record, regardless of date (the `--unrealized` flag carries no date by
-//! design). A group whose conversion rate is missing for any posting is
-//! skipped: its converted balance can't be measured, so it can't be
-//! marked to market.
+//! The revaluation is as of a date: the report's end (the day before `-e`,
+//! or the last day of a single `-p`) or an explicit `--value-date`. Only
+//! the transactions up to that day make up the positions, each is marked
+//! at the rate in force on it, and the revaluation is dated on it — so a
+//! past year-end balance sheet comes out the same whenever it is run.
+//! With no date, the latest available rate is used — the most recent on
+//! record, regardless of date — and the revaluation is dated today.
+//!
+//! A group whose conversion rate is missing for any posting is skipped:
+//! its converted balance can't be measured, so it can't be marked to
+//! market.
 
 use std::collections::HashMap;
 
@@ -25,6 +31,7 @@ use crate::date::Date;
 use crate::decimal::Decimal;
 use crate::indexer::Index;
 use crate::parser::located::Located;
+use crate::parser::metadata::Metadata;
 use crate::parser::posting::{Amount, Posting};
 use crate::parser::transaction::{State, Transaction};
 
@@ -37,17 +44,20 @@ pub struct RevaluationAccounts<'a> {
 }
 
 /// Far-future sentinel: `find(commodity, target, LATEST)` returns the most
-/// recent rate on record for the pair (the "latest available" rate).
+/// recent rate on record for the pair (the "latest available" rate), for
+/// a revaluation with no as-of date.
 const LATEST: &str = "9999-12-31";
 
 /// Inject one mark-to-market revaluation transaction per open foreign
-/// position. See the module docs for the semantics.
+/// position, as of `as_of` (`None`: the latest rate, dated today). See the
+/// module docs for the semantics.
 pub fn revaluate(
     txs: &mut Vec<Located<Transaction>>,
     target: &str,
     db: &Index,
     accounts: &RevaluationAccounts,
     precision: usize,
+    as_of: Option<Date>,
 ) {
     if txs.is_empty() {
         return;
@@ -58,13 +68,15 @@ pub fn revaluate(
     // the rebalancer will leave on the account), and whether any posting
     // lacked a rate (then the group can't be measured and is skipped).
     let mut groups: HashMap<(String, String), (Decimal, Decimal, bool)> = HashMap::new();
-    // The revaluation is "as of now" — date it today so it lands in the
-    // default report. Dating it at the journal's last transaction would
-    // hide it whenever the journal carries forward-dated entries (their
-    // max date is in the future, which the default future cutoff drops).
-    let reval_date = Date::today();
+    // Without an as-of date the revaluation is "as of now" — date it today
+    // so it lands in the default report. Dating it at the journal's last
+    // transaction would hide it whenever the journal carries forward-dated
+    // entries (their max date is in the future, which the default future
+    // cutoff drops).
+    let reval_date = as_of.unwrap_or_else(Date::today);
+    let rate_date = as_of.map_or_else(|| LATEST.to_string(), |d| d.to_string());
 
-    for lt in txs.iter() {
+    for lt in txs.iter().filter(|lt| as_of.is_none_or(|d| lt.value.date <= d)) {
         let date = lt.value.date.to_string();
         for lp in &lt.value.postings {
             let Some(a) = &lp.value.amount else { continue };
@@ -98,7 +110,7 @@ pub fn revaluate(
         if *rate_missing || native_bal.is_zero() {
             continue;
         }
-        let Some(rate) = db.find(commodity, target, LATEST) else {
+        let Some(rate) = db.find(commodity, target, &rate_date) else {
             continue;
         };
         let current = native_bal.mul_rounded(rate);
@@ -142,9 +154,12 @@ fn build_tx(
         lot_cost: None,
         lot_date: None,
         balance_assertion: None,
+        state: None,
         is_virtual: false,
         balanced: true,
         comments: Vec::new(),
+        metadata: Metadata::default(),
+        aux_date: None,
     };
     let counter = Posting {
         account: reval_account.to_string(),
@@ -157,15 +172,19 @@ fn build_tx(
         lot_cost: None,
         lot_date: None,
         balance_assertion: None,
+        state: None,
         is_virtual: false,
         balanced: true,
         comments: Vec::new(),
+        metadata: Metadata::default(),
+        aux_date: None,
     };
     Located {
         file: file.clone(),
         line,
         value: Transaction {
             date,
+            aux_date: None,
             state: State::Cleared,
             code: None,
             description: format!("holding revaluation {commodity}"),
@@ -174,6 +193,7 @@ fn build_tx(
                 Located { file: file.clone(), line, value: counter },
             ],
             comments: Vec::new(),
+            metadata: Metadata::default(),
         },
     }
 }
@@ -230,7 +250,7 @@ mod tests {
             \texpenses:x     855 EUR\n\
             \tassets:usd    -900 USD\n";
         let (mut txs, db, prec) = setup(src);
-        revaluate(&mut txs, "EUR", &db, &accounts(), prec);
+        revaluate(&mut txs, "EUR", &db, &accounts(), prec, None);
         // The revaluation posting itself (target currency) on assets:usd.
         assert_eq!(balance(&txs, "assets:usd", "EUR"), Decimal::parse("150").unwrap());
         // Gain booked (income, negative).
@@ -254,7 +274,7 @@ mod tests {
             \tassets:usd   -1000 USD\n";
         let (mut txs, db, prec) = setup(src);
         let before = txs.len();
-        revaluate(&mut txs, "EUR", &db, &accounts(), prec);
+        revaluate(&mut txs, "EUR", &db, &accounts(), prec, None);
         assert_eq!(txs.len(), before, "closed position must not be revalued");
     }
 
@@ -268,7 +288,33 @@ mod tests {
             \tequity:open  -100 EUR\n";
         let (mut txs, db, prec) = setup(src);
         let before = txs.len();
-        revaluate(&mut txs, "EUR", &db, &accounts(), prec);
+        revaluate(&mut txs, "EUR", &db, &accounts(), prec, None);
         assert_eq!(txs.len(), before);
     }
+
+    #[test]
+    fn as_of_date_marks_positions_held_then_at_that_days_rate() {
+        // At 2023-12-31 the account holds 1000 USD bought at 0.80, and the
+        // rate in force is 0.90: +100 EUR, dated that day. The later spend
+        // and 0.95 rate are after the date and don't count.
+        let src = "\
+            P 2023-01-01 USD EUR 0.80\n\
+            P 2023-12-01 USD EUR 0.90\n\
+            P 2024-06-01 USD EUR 0.95\n\
+            2023-01-01 * buy\n\
+            \tassets:usd    1000 USD\n\
+            \tassets:bank   -800 EUR\n\
+            2024-06-01 * spend\n\
+            \texpenses:x     855 EUR\n\
+            \tassets:usd    -900 USD\n";
+        let (mut txs, db, prec) = setup(src);
+        let as_of = Date::parse("2023-12-31").unwrap();
+        revaluate(&mut txs, "EUR", &db, &accounts(), prec, Some(as_of));
+        let reval = txs
+            .iter()
+            .find(|lt| lt.value.description.starts_with("holding revaluation"))
+            .expect("a revaluation transaction");
+        assert_eq!(reval.value.date, as_of);
+        assert_eq!(balance(&txs, "income:reval", "EUR"), Decimal::parse("-100").unwrap());
+    }
 }
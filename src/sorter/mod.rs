@@ -9,7 +9,8 @@
 //! argument or comma-join in the CLI):
 //!
 //! ```text
-//! date | d              transaction date (default)
+//! date | d              transaction date (default); the aux date
+//!                       under `--aux-date`, where one is written
 //! amount | amt          first posting's amount, as f64
 //! account | acc         first posting's account name
 //! description | desc    transaction description
@@ -24,8 +25,9 @@ use crate::parser::transaction::Transaction;
 
 /// Sort transactions in place by the given ordered list of criteria.
 /// An empty `fields` list is a no-op — the caller's prior order is
-/// preserved.
-pub fn sort(transactions: &mut [Located<Transaction>], fields: &[String]) {
+/// preserved. `aux` (`--aux-date`) sorts `date` by
+/// [`Transaction::report_date`] instead of the primary date.
+pub fn sort(transactions: &mut [Located<Transaction>], fields: &[String], aux: bool) {
     if fields.is_empty() {
         return;
     }
@@ -33,7 +35,7 @@ pub fn sort(transactions: &mut [Located<Transaction>], fields: &[String]) {
 
     transactions.sort_by(|a, b| {
         for (field, reverse) in &criteria {
-            let ord = compare(&a.value, &b.value, field);
+            let ord = compare(&a.value, &b.value, field, aux);
             if ord != std::cmp::Ordering::Equal {
                 return if *reverse { ord.reverse() } else { ord };
             }
@@ -66,9 +68,9 @@ fn parse_criterion(s: &str) -> (Field, bool) {
     (field, reverse)
 }
 
-fn compare(a: &Transaction, b: &Transaction, field: &Field) -> std::cmp::Ordering {
+fn compare(a: &Transaction, b: &Transaction, field: &Field, aux: bool) -> std::cmp::Ordering {
     match field {
-        Field::Date => a.date.cmp(&b.date),
+        Field::Date => a.report_date(aux).cmp(&b.report_date(aux)),
         Field::Description => a.description.cmp(&b.description),
         Field::Account => first_account(a).cmp(first_account(b)),
         Field::Amount => first_amount(a)
@@ -122,7 +124,7 @@ mod tests {
     #[test]
     fn empty_fields_is_a_noop() {
         let mut txs = setup(SRC);
-        sort(&mut txs, &[]);
+        sort(&mut txs, &[], false);
         // Booker keeps natural date order; an empty sort must not touch it.
         assert_eq!(order(&txs), ["cherry", "apple", "banana"]);
     }
@@ -130,21 +132,21 @@ mod tests {
     #[test]
     fn sort_by_date_ascending() {
         let mut txs = setup(SRC);
-        sort(&mut txs, &["date".into()]);
+        sort(&mut txs, &["date".into()], false);
         assert_eq!(order(&txs), ["cherry", "apple", "banana"]);
     }
 
     #[test]
     fn sort_by_date_reverse() {
         let mut txs = setup(SRC);
-        sort(&mut txs, &["-date".into()]);
+        sort(&mut txs, &["-date".into()], false);
         assert_eq!(order(&txs), ["banana", "apple", "cherry"]);
     }
 
     #[test]
     fn sort_by_amount_uses_first_posting() {
         let mut txs = setup(SRC);
-        sort(&mut txs, &["amount".into()]);
+        sort(&mut txs, &["amount".into()], false);
         // First postings: 10, 20, 30 → cherry, apple, banana.
         assert_eq!(order(&txs), ["cherry", "apple", "banana"]);
     }
@@ -152,7 +154,7 @@ mod tests {
     #[test]
     fn sort_by_account_uses_first_posting() {
         let mut txs = setup(SRC);
-        sort(&mut txs, &["account".into()]);
+        sort(&mut txs, &["account".into()], false);
         // assets:brokerage < assets:checking < assets:savings.
         assert_eq!(order(&txs), ["apple", "banana", "cherry"]);
     }
@@ -160,21 +162,21 @@ mod tests {
     #[test]
     fn sort_by_description() {
         let mut txs = setup(SRC);
-        sort(&mut txs, &["description".into()]);
+        sort(&mut txs, &["description".into()], false);
         assert_eq!(order(&txs), ["apple", "banana", "cherry"]);
     }
 
     #[test]
     fn payee_is_an_alias_for_description() {
         let mut txs = setup(SRC);
-        sort(&mut txs, &["payee".into()]);
+        sort(&mut txs, &["payee".into()], false);
         assert_eq!(order(&txs), ["apple", "banana", "cherry"]);
     }
 
     #[test]
     fn unknown_field_falls_back_to_date() {
         let mut txs = setup(SRC);
-        sort(&mut txs, &["nonsense".into()]);
+        sort(&mut txs, &["nonsense".into()], false);
         assert_eq!(order(&txs), ["cherry", "apple", "banana"]);
     }
 
@@ -190,7 +192,7 @@ mod tests {
             \ta  2 USD\n\
             \tb -2 USD\n";
         let mut txs = setup(src);
-        sort(&mut txs, &["date".into(), "description".into()]);
+        sort(&mut txs, &["date".into(), "description".into()], false);
         assert_eq!(order(&txs), ["alpha", "zebra"]);
     }
 }
@@ -0,0 +1,40 @@
+//! Shared helpers for the in-crate unit tests. Mirrors the integration
+//! tests' `tests/common` so command modules don't each roll their own
+//! temp-file fixture.
+
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+static COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// Write `contents` to a fresh `.ledger` file in its own temp dir and
+/// return the path. Every call gets a distinct dir, so tests running in
+/// parallel never share (or delete) each other's journal. The dir is
+/// removed on drop.
+pub(crate) struct TempJournal {
+    pub path: PathBuf,
+    dir: PathBuf,
+}
+
+impl TempJournal {
+    pub fn new(contents: &str) -> Self {
+        let id = COUNTER.fetch_add(1, Ordering::SeqCst);
+        let dir = std::env::temp_dir().join(format!("acc-ut-{}-{}", std::process::id(), id));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        let path = dir.join("journal.ledger");
+        std::fs::write(&path, contents).expect("write journal");
+        TempJournal { path, dir }
+    }
+}
+
+impl Drop for TempJournal {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// Load a journal from an inline source string. Panics on any error.
+pub(crate) fn load(src: &str) -> crate::Journal {
+    let tmp = TempJournal::new(src);
+    crate::load(&[&tmp.path]).expect("load should succeed")
+}
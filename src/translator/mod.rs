@@ -34,14 +34,25 @@
 //! quantities and never double-book; the CTA transaction is
 //! self-balancing. Groups with any missing price-DB rate are skipped
 //! (drift cannot be reliably computed).
+//!
+//! [`average`] books the other translation difference IAS 21 allows:
+//! income and expense translated at a period-average rate instead of the
+//! spot rate of each transaction (`--rate-method monthly-average|yearly-
+//! average`). The rebalancer still converts every posting at spot, so
+//! each converted transaction balances; per averaged account and period,
+//! the difference between the average-rate and spot-rate values is then
+//! released the same way — onto the account, off the CTA account — so
+//! the account reads at the average rate and the difference lands in
+//! CTA. Pass-through groups are left to [`translate`].
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 
 use crate::date::Date;
 use crate::decimal::Decimal;
 use crate::indexer::Index;
 use crate::parser::located::Located;
+use crate::parser::metadata::Metadata;
 use crate::parser::posting::{Amount, Posting};
 use crate::parser::transaction::{State, Transaction};
 
@@ -108,12 +119,110 @@ fn identify_transit_groups(
         .collect()
 }
 
+/// The period an average rate spans (`--rate-method`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AveragePeriod {
+    Month,
+    Year,
+}
+
+impl AveragePeriod {
+    /// The `[start, end)` period containing `date`.
+    fn bounds(self, date: Date) -> (Date, Date) {
+        let start = match self {
+            AveragePeriod::Month => format!("{:04}-{:02}-01", date.year(), date.month()),
+            AveragePeriod::Year => format!("{:04}-01-01", date.year()),
+        };
+        let start = Date::parse(&start).expect("valid period start");
+        let end = match self {
+            AveragePeriod::Month => start.next_month_start(),
+            AveragePeriod::Year => start.next_year_start(),
+        };
+        (start, end)
+    }
+}
+
+/// Which postings [`average`] translates at the period-average rate:
+/// those on an account `accounts` accepts.
+pub struct Averaging<'a> {
+    pub period: AveragePeriod,
+    pub accounts: &'a dyn Fn(&str) -> bool,
+}
+
+/// Release, per averaged account and period, the difference between its
+/// postings' values at the period-average rate and at the spot rate on
+/// their dates. Each release is dated on the account's last posting in the
+/// period; a positive difference (the account worth more at the average)
+/// credits `cta_gain`, a negative one debits `cta_loss`. Postings with no
+/// rate path, and pass-through groups, are skipped.
+pub fn average(
+    txs: &mut Vec<Located<Transaction>>,
+    target: &str,
+    db: &Index,
+    averaging: &Averaging,
+    cta_gain: &str,
+    cta_loss: &str,
+    precision: usize,
+) {
+    let transit = identify_transit_groups(txs);
+    // Average rate per (commodity, period start), computed once.
+    let mut rates: HashMap<(String, Date), Option<Decimal>> = HashMap::new();
+    // Per (account, period start) — ordered, so the releases are too.
+    let mut groups: BTreeMap<(String, Date), Adjustment> = BTreeMap::new();
+    for lt in txs.iter() {
+        let date = lt.value.date;
+        let lookup_date = date.to_string();
+        for lp in &lt.value.postings {
+            let p = &lp.value;
+            if !(averaging.accounts)(&p.account) {
+                continue;
+            }
+            let (Some(amount), Some((value, from))) = (&p.amount, crate::rebalancer::weight(p)) else {
+                continue;
+            };
+            if from == target || transit.contains(&(p.account.clone(), amount.commodity.clone())) {
+                continue;
+            }
+            let (start, end) = averaging.period.bounds(date);
+            let average = *rates
+                .entry((from.to_string(), start))
+                .or_insert_with(|| db.average(from, target, start, end));
+            let (Some(average), Some(spot)) = (average, db.find(from, target, &lookup_date)) else {
+                continue;
+            };
+            // The release debits `-drift` onto the account: a negative
+            // drift raises it from its spot value to its average value.
+            let drift = value.mul_rounded(spot) - value.mul_rounded(average);
+            let adj = groups.entry((p.account.clone(), start)).or_insert_with(|| Adjustment {
+                date,
+                file: lt.file.clone(),
+                line: lt.line,
+                account: p.account.clone(),
+                drift: Decimal::zero(),
+                description: "average rate adjustment",
+            });
+            adj.drift += drift;
+            (adj.date, adj.file, adj.line) = (date, lt.file.clone(), lt.line);
+        }
+    }
+
+    for adj in groups.into_values() {
+        if adj.drift.is_display_zero(precision) {
+            continue;
+        }
+        let cta = if adj.drift.is_negative() { cta_gain } else { cta_loss };
+        txs.push(build_release_tx(&adj, target, cta, precision));
+    }
+    txs.sort_by_key(|a| a.value.date);
+}
+
 struct Adjustment {
     date: Date,
     file: Arc<str>,
     line: usize,
     account: String,
     drift: Decimal,
+    description: &'static str,
 }
 
 fn collect_adjustments(
@@ -164,6 +273,7 @@ fn collect_adjustments(
                     line: lt.line,
                     account: key.0.clone(),
                     drift: entry.1,
+                    description: "commodity translation adjustment",
                 });
                 entry.1 = Decimal::zero();
             }
@@ -178,7 +288,7 @@ fn build_release_tx(
     cta_account: &str,
     precision: usize,
 ) -> Located<Transaction> {
-    let description = "commodity translation adjustment".to_string();
+    let description = adj.description.to_string();
     // Real postings (not bracket-virtual): the two legs sum to zero, so
     // the release transaction balances on its own and reloads cleanly —
     // 1:1 copyable, just like the lotter's capital postings. The debit
@@ -195,9 +305,12 @@ fn build_release_tx(
         lot_cost: None,
         lot_date: None,
         balance_assertion: None,
+        state: None,
         is_virtual: false,
         balanced: true,
         comments: Vec::new(),
+        metadata: Metadata::default(),
+        aux_date: None,
     };
     let credit = Posting {
         account: cta_account.to_string(),
@@ -210,15 +323,19 @@ fn build_release_tx(
         lot_cost: None,
         lot_date: None,
         balance_assertion: None,
+        state: None,
         is_virtual: false,
         balanced: true,
         comments: Vec::new(),
+        metadata: Metadata::default(),
+        aux_date: None,
     };
     Located {
         file: adj.file.clone(),
         line: adj.line,
         value: Transaction {
             date: adj.date,
+            aux_date: None,
             state: State::Cleared,
             code: None,
             description,
@@ -235,6 +352,7 @@ fn build_release_tx(
                 },
             ],
             comments: Vec::new(),
+            metadata: Metadata::default(),
         },
     }
 }
@@ -367,4 +485,33 @@ mod tests {
             Decimal::parse("5").unwrap()
         );
     }
+
+    #[test]
+    fn averaged_account_released_to_average_rate() {
+        // April: 0.90 for ten days, 1.20 for twenty — average 1.10. Rent of
+        // 100 USD paid on 04-05 at spot 0.90 (EUR90) reads EUR110 at the
+        // average: EUR20 more on the expense, credited to the CTA gain
+        // account.
+        let src = "\
+            P 2024-04-01 USD EUR 0.90\n\
+            P 2024-04-11 USD EUR 1.20\n\
+            2024-04-05 * rent\n\
+            \texpenses:rent    100 USD\n\
+            \tassets:bank     -100 USD\n";
+        let (mut txs, db) = setup(src);
+        let expenses = |account: &str| account.starts_with("expenses");
+        let averaging = Averaging { period: AveragePeriod::Month, accounts: &expenses };
+        average(&mut txs, "EUR", &db, &averaging, "income:cta", "expenses:cta", 2);
+        let release = txs
+            .iter()
+            .find(|lt| lt.value.description == "average rate adjustment")
+            .expect("average release missing");
+        assert_eq!(release.value.date, Date::parse("2024-04-05").unwrap());
+        let debit = &release.value.postings[0].value;
+        assert_eq!(debit.account, "expenses:rent");
+        assert_eq!(debit.amount.as_ref().unwrap().value, Decimal::parse("20").unwrap());
+        let credit = &release.value.postings[1].value;
+        assert_eq!(credit.account, "income:cta");
+        assert_eq!(credit.amount.as_ref().unwrap().value, Decimal::parse("-20").unwrap());
+    }
 }
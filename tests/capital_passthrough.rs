@@ -195,6 +195,34 @@ fn same_commodity_transfer_is_cta_not_capital() {
     assert_ne!(cta(&txs), Decimal::zero(), "held USD across a rate move → CTA");
 }
 
+#[test]
+fn transfer_between_own_accounts_carries_the_lot() {
+    // Bought on the exchange at 30000, moved to a cold wallet when the
+    // market was 40000, sold from the wallet at 50000. The lot travels with
+    // the coin, so the sale realizes the whole 20000 move as capital — and
+    // the transfer legs move at cost, so neither account drifts into CTA.
+    let src = format!(
+        "{ACCOUNTS}\
+         P 2024-01-01 BTC EUR 30000\n\
+         P 2024-03-01 BTC EUR 40000\n\
+         P 2024-06-01 BTC EUR 50000\n\
+         2024-01-01 * buy\n\
+         \tassets:exchange       1 BTC\n\
+         \tassets:cash  -30000 EUR\n\
+         2024-03-01 * withdraw\n\
+         \tassets:exchange      -1 BTC\n\
+         \tassets:wallet         1 BTC\n\
+         2024-06-01 * sell\n\
+         \tassets:wallet        -1 BTC\n\
+         \tassets:cash   50000 EUR\n"
+    );
+    let txs = common::run_x(&src, "EUR");
+    assert_eq!(capital(&txs), dec("-20000"), "realized once, on the sale");
+    assert_eq!(cta(&txs), Decimal::zero(), "the transfer moves at cost");
+    assert_eq!(common::balance(&txs, "assets:exchange", "EUR"), Decimal::zero());
+    assert_eq!(common::balance(&txs, "assets:wallet", "EUR"), Decimal::zero());
+}
+
 // ─── native mode (no -X): trade gain straight from the books ──────────
 
 #[test]
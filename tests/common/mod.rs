@@ -62,8 +62,8 @@ use acc::parser::transaction::Transaction;
 /// Returns the transformed transactions for target-currency assertions.
 pub fn run_x(src: &str, target: &str) -> Vec<Located<Transaction>> {
     let mut j = load(src);
-    acc::pipeline::enrich(&mut j, Some(target), false);
-    acc::rebalancer::rebalance(&mut j.transactions, target, &j.prices);
+    acc::pipeline::enrich(&mut j, Some(target), false, None, None).unwrap();
+    acc::rebalancer::rebalance(&mut j.transactions, target, &j.prices, false);
     j.transactions
 }
 
@@ -71,8 +71,8 @@ pub fn run_x(src: &str, target: &str) -> Vec<Located<Transaction>> {
 /// marked to the latest available rate (the revaluator runs).
 pub fn run_unrealized(src: &str, target: &str) -> Vec<Located<Transaction>> {
     let mut j = load(src);
-    acc::pipeline::enrich(&mut j, Some(target), true);
-    acc::rebalancer::rebalance(&mut j.transactions, target, &j.prices);
+    acc::pipeline::enrich(&mut j, Some(target), true, None, None).unwrap();
+    acc::rebalancer::rebalance(&mut j.transactions, target, &j.prices, false);
     j.transactions
 }
 
@@ -81,7 +81,7 @@ pub fn run_unrealized(src: &str, target: &str) -> Vec<Located<Transaction>> {
 /// capital-gain assertions.
 pub fn run_native(src: &str) -> Vec<Located<Transaction>> {
     let mut j = load(src);
-    acc::pipeline::enrich(&mut j, None, false);
+    acc::pipeline::enrich(&mut j, None, false, None, None).unwrap();
     j.transactions
 }
 
@@ -15,13 +15,36 @@ fn rebalance_uses_txdate_rate() {
          \tassets:usd   100 USD\n\
          \tequity:open -100 USD\n",
     );
-    acc::rebalancer::rebalance(&mut j.transactions, "EUR", &j.prices);
+    acc::rebalancer::rebalance(&mut j.transactions, "EUR", &j.prices, false);
     let amt = j.transactions[0].value.postings[0].value.amount.as_ref().unwrap();
     // Latest rate ≤ 2024-06-15 is 0.95 → 100 × 0.95 = 95
     assert_eq!(amt.value, Decimal::from(95));
     assert_eq!(amt.commodity, "EUR");
 }
 
+#[test]
+fn rebalance_uses_effective_date_under_aux_date() {
+    // Bought on the 15th, effective on the 18th; the card leg carries its
+    // own settlement date. Primary mode converts both legs at the 15th,
+    // `--aux-date` both at the transaction's effective date, so the
+    // converted transaction still balances.
+    let src = "P 2024-06-15 USD EUR 0.90\n\
+               P 2024-06-18 USD EUR 0.92\n\
+               P 2024-06-20 USD EUR 0.95\n\
+               2024-06-15=2024-06-18 * x\n\
+               \texpenses:food   100 USD\n\
+               \tliabilities:card  -100 USD  ; [=2024-06-20]\n";
+    let value = |aux: bool, leg: usize| {
+        let mut j = common::load(src);
+        acc::rebalancer::rebalance(&mut j.transactions, "EUR", &j.prices, aux);
+        j.transactions[0].value.postings[leg].value.amount.as_ref().unwrap().value
+    };
+    assert_eq!(value(false, 0), Decimal::from(90));
+    assert_eq!(value(false, 1), Decimal::from(-90));
+    assert_eq!(value(true, 0), Decimal::from(92));
+    assert_eq!(value(true, 1), Decimal::from(-92));
+}
+
 #[test]
 fn rebalance_uses_inverse_rate_when_direct_missing() {
     let mut j = common::load(
@@ -30,7 +53,7 @@ fn rebalance_uses_inverse_rate_when_direct_missing() {
          \ta  100 EUR\n\
          \tb  -100 EUR\n",
     );
-    acc::rebalancer::rebalance(&mut j.transactions, "USD", &j.prices);
+    acc::rebalancer::rebalance(&mut j.transactions, "USD", &j.prices, false);
     // No direct EUR→USD stored; inverse of USD→EUR 0.5 = 2.0
     let amt = j.transactions[0].value.postings[0].value.amount.as_ref().unwrap();
     assert_eq!(amt.value, Decimal::from(200));
@@ -46,7 +69,7 @@ fn rebalance_multi_hop_via_cross_commodity() {
          \ta  100 USD\n\
          \tb  -100 USD\n",
     );
-    acc::rebalancer::rebalance(&mut j.transactions, "EUR", &j.prices);
+    acc::rebalancer::rebalance(&mut j.transactions, "EUR", &j.prices, false);
     let amt = j.transactions[0].value.postings[0].value.amount.as_ref().unwrap();
     // 100 × 0.9 × 1.02 = 91.8
     let expected = Decimal::from(100) * Decimal::parse("0.9").unwrap() * Decimal::parse("1.02").unwrap();
@@ -60,7 +83,7 @@ fn missing_rate_leaves_amount_unchanged() {
          \ta  100 USD\n\
          \tb  -100 USD\n",
     );
-    acc::rebalancer::rebalance(&mut j.transactions, "EUR", &j.prices);
+    acc::rebalancer::rebalance(&mut j.transactions, "EUR", &j.prices, false);
     let amt = j.transactions[0].value.postings[0].value.amount.as_ref().unwrap();
     // No P-directive → rebalancer leaves it as USD
     assert_eq!(amt.commodity, "USD");
@@ -74,7 +97,7 @@ fn same_commodity_is_noop() {
          \ta  100 EUR\n\
          \tb  -100 EUR\n",
     );
-    acc::rebalancer::rebalance(&mut j.transactions, "EUR", &j.prices);
+    acc::rebalancer::rebalance(&mut j.transactions, "EUR", &j.prices, false);
     let amt = j.transactions[0].value.postings[0].value.amount.as_ref().unwrap();
     assert_eq!(amt.value, Decimal::from(100));
     assert_eq!(amt.commodity, "EUR");
@@ -93,7 +116,7 @@ fn rebalance_lot_posting_converts_via_cost_weight() {
          \tassets:cash    1900 EUR\n\
          \tincome:capital -950 EUR\n",
     );
-    acc::rebalancer::rebalance(&mut j.transactions, "EUR", &j.prices);
+    acc::rebalancer::rebalance(&mut j.transactions, "EUR", &j.prices, false);
     let broker = j.transactions[0].value.postings[0].value.amount.as_ref().unwrap();
     assert_eq!(broker.value, Decimal::from(-950)); // weight, not market -2000
     assert_eq!(broker.commodity, "EUR");
@@ -136,7 +159,7 @@ fn cta_labels_transit_drift_on_declared_account() {
         "expenses:cta",
         2,
     );
-    acc::rebalancer::rebalance(&mut j.transactions, "USD", &j.prices);
+    acc::rebalancer::rebalance(&mut j.transactions, "USD", &j.prices, false);
 
     // At least one CTA account must now carry a non-zero drift.
     let mut cta_sum = Decimal::zero();